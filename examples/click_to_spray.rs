@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+
+// Click anywhere in the window to spray graffiti where the cursor points. Right click
+// lights the graffiti as if it faced the camera, compare both on curved low-poly surfaces.
+
+#[derive(Resource)]
+struct Graffiti(Handle<StandardMaterial>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(DecalPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, click_to_spray)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    commands.insert_resource(Graffiti(materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("graffiti1.png")),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    })));
+
+    // Bevy's built in shapes use U32 indices, which can't be decaled, so use a glTF scene instead
+    commands.spawn((
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("playground.glb"))),
+        DecalableScene,
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(-10., 8., 14.).looking_at(Vec3::ZERO, Vec3::Y)));
+}
+
+fn click_to_spray(
+    mut commands: Commands,
+    graffiti: Res<Graffiti>,
+    btn: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    if !btn.just_pressed(MouseButton::Left) && !btn.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+
+    for (camera, camera_transform) in cameras.iter() {
+        // Projects onto everything along the ray, up to 30 meters away from the camera
+        if let Some(mut spray) = SprayDecal::from_cursor(graffiti.0.clone(), camera, camera_transform, cursor, Vec2::splat(2.), 30.) {
+            if btn.just_pressed(MouseButton::Right) {
+                spray = spray.with_projector_normals();
+            }
+            spray.spray(&mut commands);
+        }
+    }
+}
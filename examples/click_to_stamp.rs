@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy_mesh_decal::prelude::*;
+
+// Click the cube or the sphere to stamp graffiti where the pointer hits them, the torus
+// gets its own stamp instead. The floor isn't Decalable, so clicking it does nothing.
+// Run with `cargo run --example click_to_stamp --features picking`
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((MeshPickingPlugin, DecalPlugin, DecalPickingPlugin))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let stamp = |materials: &mut Assets<StandardMaterial>, texture: &str| materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load(texture.to_owned())),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    });
+    commands.insert_resource(ClickToStamp::new(stamp(&mut materials, "graffiti1.png"), Vec2::splat(0.6)));
+    let torus_stamp = ClickToStamp::new(stamp(&mut materials, "splatter1.png"), Vec2::splat(0.4));
+
+    let white = materials.add(StandardMaterial::default());
+    let shapes = [
+        (u16_indices(Cuboid::new(1.5, 1.5, 1.5).into()), Vec3::new(-2.5, 0.75, 0.)),
+        (u16_indices(Sphere::new(0.9).mesh().uv(32, 18)), Vec3::new(0., 0.9, 0.)),
+        (u16_indices(Torus::new(0.4, 0.9).into()), Vec3::new(2.5, 0.5, 0.)),
+    ];
+    for (index, (mesh, translation)) in shapes.into_iter().enumerate() {
+        let mut entity = commands.spawn((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(white.clone()),
+            Transform::from_translation(translation),
+            Decalable::default(),
+        ));
+        if index == 2 {
+            entity.insert(torus_stamp.clone());
+        }
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(12., 12.))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.3),
+            ..default()
+        })),
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(0., 4., 7.).looking_at(Vec3::new(0., 0.5, 0.), Vec3::Y)));
+}
+
+// Decals need U16 indices, Bevy's built in shapes use U32
+fn u16_indices(mut mesh: Mesh) -> Mesh {
+    let indices: Vec<u16> = mesh.indices().unwrap().iter().map(|index| index as u16).collect();
+    mesh.insert_indices(Indices::U16(indices));
+    return mesh;
+}
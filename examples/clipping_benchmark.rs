@@ -0,0 +1,90 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+
+// Compares serial and parallel clipping of a 2 meter decal on a dense, 130k triangle plane,
+// along with the number of heap allocations per spray.
+// Run with `cargo run --release --example clipping_benchmark`
+
+const GRID: usize = 256;    // Vertices per side, the most U16 indices can address
+const RUNS: u32 = 20;
+
+// Counts every allocation, to check that clipping doesn't allocate per triangle
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let plane = dense_plane(GRID, 20.);
+    let projector = Transform::from_xyz(0., 1., 0.)
+        .looking_to(Vec3::NEG_Y, Vec3::Z)
+        .with_scale(Vec3::new(1., 1., 2.));
+
+    for parallel_clipping in [false, true] {
+        let settings = DecalSettings { parallel_clipping, ..default() };
+
+        // Warm up, so the task pool is spun up before measuring
+        project_decal_with(&plane, &GlobalTransform::IDENTITY, &projector, 0., &settings, &SprayOptions::default());
+
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..RUNS {
+            project_decal_with(&plane, &GlobalTransform::IDENTITY, &projector, 0., &settings, &SprayOptions::default());
+        }
+        let elapsed = start.elapsed() / RUNS;
+        let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / RUNS as usize;
+
+        println!(
+            "{}: {:.3} ms and {} allocations per spray",
+            if parallel_clipping { "parallel" } else { "serial" },
+            elapsed.as_secs_f64() * 1000.,
+            allocations,
+        );
+    }
+}
+
+fn dense_plane(grid: usize, size: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(grid * grid);
+    let mut normals = Vec::with_capacity(grid * grid);
+    for z in 0..grid {
+        for x in 0..grid {
+            let uv = Vec2::new(x as f32, z as f32) / (grid - 1) as f32 - 0.5;
+            positions.push([uv.x * size, 0., uv.y * size]);
+            normals.push([0., 1., 0.]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity((grid - 1) * (grid - 1) * 6);
+    for z in 0..grid - 1 {
+        for x in 0..grid - 1 {
+            let i = (z * grid + x) as u16;
+            let row = grid as u16;
+            indices.extend_from_slice(&[i, i + row, i + 1, i + 1, i + row, i + row + 1]);
+        }
+    }
+
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U16(indices));
+}
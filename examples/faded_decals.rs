@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy_mesh_decal::prelude::*;
+
+// Left click sprays a mask mode StandardMaterial decal, right click the same texture
+// with the faded DecalMaterial, to compare the edges where the projection ends.
+// Middle click fades a blended StandardMaterial through vertex colors instead, which
+// also fades out toward the silhouette of the sphere.
+
+#[derive(Resource)]
+struct Splatters {
+    mask: Handle<StandardMaterial>,
+    faded: Handle<DecalMaterial>,
+    blended: Handle<StandardMaterial>,
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((DecalPlugin, DecalMaterialPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, click_to_spray)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut decal_materials: ResMut<Assets<DecalMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let texture = assets.load("splatter2.png");
+
+    let mut faded = decal_material(texture.clone());
+    faded.base.base_color = Color::srgb(0.2, 0.4, 1.);
+    faded.extension.edge_fade = 0.25;
+    faded.extension.depth_fade = 0.5;
+
+    commands.insert_resource(Splatters {
+        mask: standard_materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.4, 1.),
+            base_color_texture: Some(texture.clone()),
+            alpha_mode: AlphaMode::Mask(0.5),
+            ..default()
+        }),
+        faded: decal_materials.add(faded),
+        blended: standard_materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.4, 1.),
+            base_color_texture: Some(texture.clone()),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+
+    commands.spawn((
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("playground.glb"))),
+        DecalableScene,
+    ));
+
+    // Decals need U16 indices
+    let mut sphere = Sphere::new(1.5).mesh().uv(32, 18);
+    let indices: Vec<u16> = sphere.indices().unwrap().iter().map(|index| index as u16).collect();
+    sphere.insert_indices(Indices::U16(indices));
+
+    commands.spawn((
+        Mesh3d(meshes.add(sphere)),
+        MeshMaterial3d(standard_materials.add(StandardMaterial::default())),
+        Transform::from_xyz(4., 1.5, -2.),
+        Decalable::default(),
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(-10., 8., 14.).looking_at(Vec3::ZERO, Vec3::Y)));
+}
+
+fn click_to_spray(
+    mut commands: Commands,
+    splatters: Res<Splatters>,
+    btn: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+
+    for (camera, camera_transform) in cameras.iter() {
+        if btn.just_pressed(MouseButton::Left) {
+            if let Some(spray) = SprayDecal::from_cursor(splatters.mask.clone(), camera, camera_transform, cursor, Vec2::splat(3.), 30.) {
+                spray.spray(&mut commands);
+            }
+        }
+
+        if btn.just_pressed(MouseButton::Right) {
+            if let Some(spray) = SprayDecal::from_cursor(splatters.faded.clone(), camera, camera_transform, cursor, Vec2::splat(3.), 30.) {
+                spray.spray(&mut commands);
+            }
+        }
+
+        if btn.just_pressed(MouseButton::Middle) {
+            if let Some(spray) = SprayDecal::from_cursor(splatters.blended.clone(), camera, camera_transform, cursor, Vec2::splat(3.), 30.) {
+                spray.with_edge_fade(0.25)
+                    .with_angle_fade(45_f32.to_radians()..80_f32.to_radians())
+                    .spray(&mut commands);
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy_mesh_decal::prelude::*;
+
+// Sprays 500 decals onto the playground, 5 per frame, with decal merging on.
+// The number of decal entities stays flat, as sprays of the same material on a
+// target are appended to a single mesh instead of spawning a new entity each.
+
+const SPRAYS: usize = 500;
+const SPRAYS_PER_FRAME: usize = 5;
+
+#[derive(Resource)]
+struct Paint(Handle<StandardMaterial>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(
+            DecalPlugin::new()
+                .with_decal_merging(true)
+                .with_max_decals_per_entity(SPRAYS)
+        )
+        .add_systems(Startup, setup)
+        .add_systems(Update, (
+            spray,
+            log_counts.run_if(on_timer(std::time::Duration::from_secs(1))),
+        ))
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    commands.insert_resource(Paint(materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.2, 0.3),
+        base_color_texture: Some(assets.load("splatter1.png")),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    })));
+
+    commands.spawn((
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("playground.glb"))),
+        DecalableScene,
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(-10., 8., 14.).looking_at(Vec3::ZERO, Vec3::Y)));
+}
+
+fn spray(
+    mut commands: Commands,
+    paint: Res<Paint>,
+    mut sprayed: Local<usize>,
+    mut seed: Local<u32>,
+) {
+    if *sprayed >= SPRAYS {
+        return;
+    }
+
+    // A tiny LCG is plenty for scattering decals
+    let mut random = || {
+        *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        return (*seed >> 8) as f32 / (1 << 24) as f32;
+    };
+
+    let sprays: Vec<(Handle<StandardMaterial>, Transform)> = (0..SPRAYS_PER_FRAME)
+        .map(|_| {
+            let target = Vec3::new(random() * 16. - 8., 0., random() * 16. - 8.);
+            let rotation = Transform::IDENTITY.looking_to(Vec3::NEG_Y, Vec3::Z).rotation * Quat::from_rotation_z(random() * TAU);
+            let transform = projector_transform(target + Vec3::Y * 10., rotation, Vec2::splat(1.5), 0.0..20.);
+            (paint.0.clone(), transform)
+        })
+        .collect();
+
+    spray_decals(&mut commands, sprays);
+    *sprayed += SPRAYS_PER_FRAME;
+}
+
+fn log_counts(decals: Query<(), With<Decal>>, decalables: Query<&Decalable>, meshes: Res<Assets<Mesh>>) {
+    let sprays: usize = decalables.iter().map(|decalable| decalable.count()).sum();
+    info!("{} sprays in {} decal entities, {} meshes", sprays, decals.iter().count(), meshes.len());
+}
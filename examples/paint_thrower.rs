@@ -3,8 +3,11 @@ use std::f32::consts::TAU;
 use bevy::{
     color::palettes::tailwind, gltf::{Gltf, GltfMesh, GltfNode}, math::Vec3Swizzles, prelude::*, render::camera::Exposure, window::CursorGrabMode
 };
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use bevy_mesh_decal::prelude::*;
 use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::geometry::TriMeshFlags;
 
 use bevy_fps_controller::controller::*;
 
@@ -13,33 +16,45 @@ const SPAWN_POINT: Vec3 = Vec3::new(0.0, 1.625, 0.0);
 
 
 fn main() {
-    App::new()
-        .insert_resource(AmbientLight {
-            color: Color::WHITE,
-            brightness: 30000.0,
-        })
-        .insert_resource(SprayMaterials::default())
-        .insert_resource(ClearColor(Color::linear_rgb(0.83, 0.96, 0.96)))
-        .add_plugins(DefaultPlugins)
-        .add_plugins(DecalPlugin)
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugins(FpsControllerPlugin)
-        .add_systems(Startup, setup)
-        .add_systems(
-            Update,
-            (manage_cursor, scene_colliders, display_text, respawn, painter, make_all_decalable),
-        )
-        .add_systems(
-            Last,   // Last just to avoid race conditions
-            clear_decals
-        )
-        .run();
+    let mut app = App::new();
+    app.insert_resource(AmbientLight {
+        color: Color::WHITE,
+        brightness: 30000.0,
+    })
+    .insert_resource(SprayMaterials::default())
+    .insert_resource(SprayHistory::default())
+    .insert_resource(ClearColor(Color::linear_rgb(0.83, 0.96, 0.96)))
+    .add_plugins(DefaultPlugins)
+    .add_plugins(DecalPlugin::new().with_settings(DecalSettings {
+        generate_tangents: true,    // Needed for the normal mapped crater decal
+        ..default()
+    }))
+    .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+    .add_plugins(FpsControllerPlugin)
+    .add_systems(Startup, setup)
+    .add_systems(
+        Update,
+        (manage_cursor, scene_colliders, display_text, respawn, painter, undo_spray, orbit_light),
+    )
+    .add_systems(
+        Last,   // Last just to avoid race conditions
+        clear_decals
+    );
+
+    // When running with `--features rapier,debug`, F3 shows how full every object is
+    #[cfg(feature = "debug")]
+    app.add_plugins(DecalDebugPlugin)
+        .insert_resource(DecalDebugOverlay { enabled: false, ..default() })
+        .add_systems(Update, toggle_debug_overlay);
+
+    app.run();
 }
 
 fn setup(
     mut commands: Commands, 
     mut window: Query<&mut Window>, 
     mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     assets: Res<AssetServer>, 
     mut sprays: ResMut<SprayMaterials>,
 ) {
@@ -70,7 +85,7 @@ fn setup(
                 StandardMaterial {
                     base_color: (colors[i % colors.len()] * 2.).into(),
                     base_color_texture: Some(textures[i % textures.len()].clone()),
-                    // Preferably use mask for these if you can. Blend can create artifacts due to built in blend sorting
+                    // Mask is cheaper. Blend works as well, overlapping decals are sorted by their stacking layer
                     alpha_mode: AlphaMode::Mask(0.5),
                     perceptual_roughness: 1.,
                     ..default()
@@ -110,17 +125,42 @@ fn setup(
         )
     );
 
+    // A normal mapped crater, lit correctly thanks to the generated decal tangents
+
+    sprays.0.push(
+        standard_materials.add(
+            StandardMaterial {
+                base_color: Color::srgb(0.3, 0.3, 0.3),
+                base_color_texture: Some(textures[0].clone()),
+                normal_map_texture: Some(images.add(crater_normal_map(128))),
+                alpha_mode: AlphaMode::Mask(0.5),
+                perceptual_roughness: 0.6,
+                ..default()
+            }
+        )
+    );
+
     // Add some light
 
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
+    commands.spawn((
+        PointLight {
+            color: Color::srgb(1.0, 0.6, 0.2),
+            intensity: 2_000_000.0,
+            range: 30.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 3.0, 0.0),
+        OrbitingLight,
+    ));
+
+    commands.spawn((
+        DirectionalLight {
             illuminance: light_consts::lux::FULL_DAYLIGHT,
             shadows_enabled: true,
             ..default()
         },
-        transform: Transform::from_xyz(4.0, 7.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+        Transform::from_xyz(4.0, 7.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
 
 
     // Spawn some spheres!
@@ -128,45 +168,33 @@ fn setup(
     commands.spawn((
         RigidBody::Dynamic,
         Collider::ball(1.),
-        SceneBundle {
-            scene: assets
-                .load(GltfAssetLabel::Scene(0).from_asset("sphere.glb")),
-            transform: Transform::from_translation(Vec3::Y * 10.),
-            ..default()
-        }
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("sphere.glb"))),
+        Transform::from_translation(Vec3::Y * 10.),
+        DecalableScene,
     ));
 
     commands.spawn((
         RigidBody::Dynamic,
         Collider::ball(1.),
-        SceneBundle {
-            scene: assets
-                .load(GltfAssetLabel::Scene(0).from_asset("sphere.glb")),
-            transform: Transform::from_translation(Vec3::Y * 10.),
-            ..default()
-        }
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("sphere.glb"))),
+        Transform::from_translation(Vec3::Y * 10.),
+        DecalableScene,
     ));
 
     commands.spawn((
         RigidBody::Dynamic,
         Collider::ball(1.),
-        SceneBundle {
-            scene: assets
-                .load(GltfAssetLabel::Scene(0).from_asset("sphere.glb")),
-            transform: Transform::from_translation(Vec3::Y * 10. + Vec3::X * 5.),
-            ..default()
-        }
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("sphere.glb"))),
+        Transform::from_translation(Vec3::Y * 10. + Vec3::X * 5.),
+        DecalableScene,
     ));
 
     commands.spawn((
         RigidBody::Dynamic,
         Collider::ball(1.),
-        SceneBundle {
-            scene: assets
-                .load(GltfAssetLabel::Scene(0).from_asset("sphere.glb")),
-            transform: Transform::from_translation(Vec3::Y * 10. + -Vec3::X * 5.).with_scale(Vec3::ONE * 3.),
-            ..default()
-        }
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("sphere.glb"))),
+        Transform::from_translation(Vec3::Y * 10. + -Vec3::X * 5.).with_scale(Vec3::ONE * 3.),
+        DecalableScene,
     ));
 
     // Note that we have two entities for the player
@@ -198,7 +226,7 @@ fn setup(
             AdditionalMassProperties::Mass(1.0),
             GravityScale(0.0),
             Ccd { enabled: true }, // Prevent clipping when going fast
-            TransformBundle::from_transform(Transform::from_translation(SPAWN_POINT)),
+            Transform::from_translation(SPAWN_POINT),
             LogicalPlayer,
             FpsControllerInput {
                 pitch: -TAU / 12.0,
@@ -216,14 +244,12 @@ fn setup(
         .id();
 
     commands.spawn((
-        Camera3dBundle {
-            projection: Projection::Perspective(PerspectiveProjection {
-                fov: TAU / 5.0,
-                ..default()
-            }),
-            exposure: Exposure::SUNLIGHT,
+        Camera3d::default(),
+        Projection::Perspective(PerspectiveProjection {
+            fov: TAU / 5.0,
             ..default()
-        },
+        }),
+        Exposure::SUNLIGHT,
         RenderPlayer { logical_entity },
     ));
 
@@ -233,24 +259,73 @@ fn setup(
         is_loaded: false,
     });
 
-    commands.spawn(
-        TextBundle::from_section(
-            "",
-            TextStyle {
-                font: assets.load("fira_mono.ttf"),
-                font_size: 24.0,
-                color: Color::BLACK,
-            },
-        )
-            .with_style(Style {
-                position_type: PositionType::Absolute,
-                top: Val::Px(5.0),
-                left: Val::Px(5.0),
-                ..default()
-            }),
+    commands.spawn((
+        Hud,
+        Text::default(),
+        TextFont {
+            font: assets.load("fira_mono.ttf"),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Px(5.0),
+            ..default()
+        },
+    ));
+}
+
+// Procedural normal map of a bowl shaped crater with a raised rim
+fn crater_normal_map(size: u32) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let p = Vec2::new(x as f32, y as f32) / (size - 1) as f32 * 2. - 1.;
+            let r = p.length();
+            // Derivative of the crater height along the radius
+            let slope = if r < 0.6 {
+                2. * r
+            } else if r < 0.9 {
+                -1.2
+            } else {
+                0.
+            };
+            let gradient = if r > 0. { p / r * slope } else { Vec2::ZERO };
+            let normal = Vec3::new(-gradient.x, -gradient.y, 1.).normalize() * 0.5 + 0.5;
+            data.extend_from_slice(&[
+                (normal.x * 255.) as u8,
+                (normal.y * 255.) as u8,
+                (normal.z * 255.) as u8,
+                255,
+            ]);
+        }
+    }
+
+    return Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
     );
 }
 
+// The text in the corner, not the labels of the debug overlay
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct OrbitingLight;
+
+fn orbit_light(time: Res<Time>, mut lights: Query<&mut Transform, With<OrbitingLight>>) {
+    for mut transform in lights.iter_mut() {
+        let angle = time.elapsed_secs() * 0.5;
+        transform.translation = Vec3::new(angle.cos() * 8., 3., angle.sin() * 8.);
+    }
+}
+
 fn respawn(mut query: Query<(&mut Transform, &mut Velocity)>) {
     for (mut transform, mut velocity) in &mut query {
         if transform.translation.y > -50.0 {
@@ -284,7 +359,7 @@ fn scene_colliders(
 
     if let Some(gltf) = gltf {
         let scene = gltf.scenes.first().unwrap().clone();
-        commands.spawn(SceneBundle { scene, ..default() });
+        commands.spawn((SceneRoot(scene), DecalableScene));
         for node in &gltf.nodes {
             let node = gltf_node_assets.get(node).unwrap();
             if let Some(gltf_mesh) = node.mesh.clone() {
@@ -292,9 +367,9 @@ fn scene_colliders(
                 for mesh_primitive in &gltf_mesh.primitives {
                     let mesh = mesh_assets.get(&mesh_primitive.mesh).unwrap();
                     commands.spawn((
-                        Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh).unwrap(),
+                        Collider::from_bevy_mesh(mesh, &ComputedColliderShape::TriMesh(TriMeshFlags::default())).unwrap(),
                         RigidBody::Fixed,
-                        TransformBundle::from_transform(node.transform),
+                        node.transform,
                     ));
                 }
             }
@@ -306,27 +381,56 @@ fn scene_colliders(
 #[derive(Resource, Default)]
 pub struct SprayMaterials(Vec<Handle<StandardMaterial>>);
 
+#[derive(Resource, Default)]
+pub struct SprayHistory(Vec<SprayId>);
+
 fn painter(
     mut commands: Commands,
     materials: Res<SprayMaterials>,
+    mut history: ResMut<SprayHistory>,
     btn: Res<ButtonInput<MouseButton>>,
-    player: Query<&Transform, With<RenderPlayer>>,
+    rapier_context: ReadDefaultRapierContext,
+    player: Query<(&Transform, &RenderPlayer)>,
     mut material_index: Local<usize>,
 ) {
     if btn.just_pressed(MouseButton::Left) {
-        for transform in player.iter() {
-            let spray_transform = transform.with_scale(Vec3::ONE * 2. + Vec3::Z * 50.);
+        for (transform, render_player) in player.iter() {
+            let filter = QueryFilter::default().exclude_collider(render_player.logical_entity);
 
             if materials.0.is_empty() {
                 panic!("No materials to spray with!");
             }
 
-            spray_decal(&mut commands, materials.0[*material_index % materials.0.len()].clone(), spray_transform);
+            // Spray a 4 by 4 meter decal onto whatever the player is looking at
+            let material = materials.0[*material_index % materials.0.len()].clone();
+            let Some(hit) = spray_decal_raycast(&mut commands, rapier_context.single(), transform.translation, *transform.forward(), 50., filter, material, Vec2::splat(4.), 1.) else {
+                continue;
+            };
+            history.0.push(hit.spray);
             *material_index = (*material_index + 1) % materials.0.len();
         }
     }
 }
 
+fn undo_spray(
+    mut commands: Commands,
+    key: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<SprayHistory>,
+    decals: Query<(Entity, &DecalSpray)>,
+) {
+    if !key.just_pressed(KeyCode::KeyZ) {
+        return;
+    }
+
+    if let Some(spray) = history.0.pop() {
+        for (entity, decal_spray) in decals.iter() {
+            if decal_spray.0 == spray {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
 fn manage_cursor(
     btn: Res<ButtonInput<MouseButton>>,
     key: Res<ButtonInput<KeyCode>>,
@@ -335,15 +439,15 @@ fn manage_cursor(
 ) {
     for mut window in &mut window_query {
         if btn.just_pressed(MouseButton::Left) {
-            window.cursor.grab_mode = CursorGrabMode::Locked;
-            window.cursor.visible = false;
+            window.cursor_options.grab_mode = CursorGrabMode::Locked;
+            window.cursor_options.visible = false;
             for mut controller in &mut controller_query {
                 controller.enable_input = true;
             }
         }
         if key.just_pressed(KeyCode::Escape) {
-            window.cursor.grab_mode = CursorGrabMode::None;
-            window.cursor.visible = true;
+            window.cursor_options.grab_mode = CursorGrabMode::None;
+            window.cursor_options.visible = true;
             for mut controller in &mut controller_query {
                 controller.enable_input = false;
             }
@@ -353,12 +457,12 @@ fn manage_cursor(
 
 fn display_text(
     mut controller_query: Query<(&Transform, &Velocity)>,
-    mut text_query: Query<&mut Text>,
+    mut text_query: Query<&mut Text, With<Hud>>,
 ) {
     for (transform, velocity) in &mut controller_query {
         for mut text in &mut text_query {
-            text.sections[0].value = format!(
-                "vel: {:.2}, {:.2}, {:.2}\npos: {:.2}, {:.2}, {:.2}\nspd: {:.2}\nPress C to clear decals!\nIf an object has too many decals, decaling won't work!",
+            text.0 = format!(
+                "vel: {:.2}, {:.2}, {:.2}\npos: {:.2}, {:.2}, {:.2}\nspd: {:.2}\nPress C to clear decals, Z to undo the last spray!\nIf an object has too many decals, decaling won't work!",
                 velocity.linvel.x,
                 velocity.linvel.y,
                 velocity.linvel.z,
@@ -371,28 +475,19 @@ fn display_text(
     }
 }
 
-fn make_all_decalable( // Make absolutely everything decalable, just for demonstration purposes
-    mut commands: Commands,
-    entities: Query<Entity, (With<Handle<Mesh>>, Without<Decal>, Without<Decalable>)>,
-) {
-    for entity in entities.iter() {
-        commands.entity(entity).insert(Decalable::default());
+#[cfg(feature = "debug")]
+fn toggle_debug_overlay(key: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DecalDebugOverlay>) {
+    if key.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
     }
 }
 
-fn clear_decals( // Make absolutely everything decalable, just for demonstration purposes
+fn clear_decals(
     mut commands: Commands,
     key: Res<ButtonInput<KeyCode>>,
-    decals: Query<Entity, With<Decal>>,
-    decalables: Query<Entity, With<Decalable>>,
 ) {
     if key.just_pressed(KeyCode::KeyC) {
-        for entity in decals.iter() {
-            commands.entity(entity).despawn();
-        }
-
-        for entity in decalables.iter() {
-            commands.entity(entity).insert(Decalable::default());
-        }
+        // Every spray in this example uses the default group
+        clear_decals_in_group(&mut commands, DecalGroup::default());
     }
 }
\ No newline at end of file
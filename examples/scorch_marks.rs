@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy_mesh_decal::prelude::*;
+
+// Click near the corner between the floor and the wall to leave a spherical scorch mark,
+// reaching both surfaces around the explosion. Right click sprays a box decal of the
+// same size for comparison, which streaks across the wall.
+
+const WALL_Z: f32 = -4.;
+const RADIUS: f32 = 1.5;
+
+#[derive(Resource)]
+struct Scorch(Handle<StandardMaterial>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(DecalPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, explode)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    commands.insert_resource(Scorch(materials.add(StandardMaterial {
+        base_color: Color::srgb(0.05, 0.04, 0.03),
+        base_color_texture: Some(assets.load("splatter3.png")),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    })));
+
+    let concrete = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.7, 0.7, 0.65),
+        ..default()
+    });
+
+    // A handful of large triangles, the sphere subdivides them where it reaches
+    commands.spawn((
+        Mesh3d(meshes.add(u16_indices(Cuboid::new(12., 0.2, 12.).into()))),
+        MeshMaterial3d(concrete.clone()),
+        Transform::from_xyz(0., -0.1, WALL_Z + 6.),
+        Decalable::default(),
+    ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(u16_indices(Cuboid::new(12., 6., 0.2).into()))),
+        MeshMaterial3d(concrete),
+        Transform::from_xyz(0., 3., WALL_Z - 0.1),
+        Decalable::default(),
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-6., 5., 6.).looking_at(Vec3::new(0., 0.5, WALL_Z), Vec3::Y),
+    ));
+}
+
+// Decals need U16 indices, Bevy's built in shapes use U32
+fn u16_indices(mut mesh: Mesh) -> Mesh {
+    let indices: Vec<u16> = mesh.indices().unwrap().iter().map(|index| index as u16).collect();
+    mesh.insert_indices(Indices::U16(indices));
+    return mesh;
+}
+
+fn explode(
+    mut commands: Commands,
+    scorch: Res<Scorch>,
+    btn: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    if !btn.just_pressed(MouseButton::Left) && !btn.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let Some(cursor) = windows.single().cursor_position() else {
+        return;
+    };
+
+    for (camera, camera_transform) in cameras.iter() {
+        let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+            continue;
+        };
+
+        // Nearest hit on the floor or the front of the wall
+        let floor = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y));
+        let wall = ray.intersect_plane(Vec3::Z * WALL_Z, InfinitePlane3d::new(Vec3::Z));
+        let Some(distance) = [floor, wall].into_iter().flatten().min_by(f32::total_cmp) else {
+            continue;
+        };
+        let hit = ray.get_point(distance);
+
+        if btn.just_pressed(MouseButton::Left) {
+            // The explosion goes off half a meter in front of the surface
+            let center = hit - *ray.direction * 0.5;
+            let projector = Transform::from_translation(center)
+                .looking_to(*ray.direction, Vec3::Y)
+                .with_scale(Vec3::splat(RADIUS));
+            SprayDecal::new(scorch.0.clone(), projector)
+                .with_shape(DecalShape::Sphere)
+                .spray(&mut commands);
+        } else {
+            let projector = projector_transform(ray.origin, Transform::IDENTITY.looking_to(*ray.direction, Vec3::Y).rotation, Vec2::splat(RADIUS * 2.), distance - RADIUS..distance + RADIUS);
+            SprayDecal::new(scorch.0.clone(), projector).spray(&mut commands);
+        }
+    }
+}
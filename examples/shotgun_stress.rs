@@ -0,0 +1,80 @@
+use std::f32::consts::TAU;
+
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+
+// Stress test for batched spraying: fires a shotgun of 12 pellets every frame
+// while the left mouse button is held, and logs the frame time
+
+const PELLETS: usize = 12;
+const SPREAD: f32 = 0.15;
+
+#[derive(Resource)]
+struct BulletHole(Handle<StandardMaterial>);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()))
+        .add_plugins(DecalPlugin::new().with_max_decals_per_entity(1024))
+        .add_systems(Startup, setup)
+        .add_systems(Update, shoot)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    commands.insert_resource(BulletHole(materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        base_color_texture: Some(assets.load("splatter1.png")),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    })));
+
+    commands.spawn((
+        SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("playground.glb"))),
+        DecalableScene,
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(-10., 8., 14.).looking_at(Vec3::ZERO, Vec3::Y)));
+}
+
+fn shoot(
+    mut commands: Commands,
+    bullet_hole: Res<BulletHole>,
+    btn: Res<ButtonInput<MouseButton>>,
+    camera: Query<&Transform, With<Camera>>,
+    mut seed: Local<u32>,
+) {
+    if !btn.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let camera = camera.single();
+
+    // A tiny LCG is plenty for scattering pellets
+    let mut random = || {
+        *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        return (*seed >> 8) as f32 / (1 << 24) as f32;
+    };
+
+    let pellets: Vec<(Handle<StandardMaterial>, Transform)> = (0..PELLETS)
+        .map(|_| {
+            let angle = random() * TAU;
+            let spread = random() * SPREAD;
+            let rotation = camera.rotation
+                * Quat::from_rotation_z(angle)
+                * Quat::from_rotation_x(spread);
+            let transform = projector_transform(camera.translation, rotation, Vec2::splat(0.3), 0.0..40.);
+            (bullet_hole.0.clone(), transform)
+        })
+        .collect();
+
+    spray_decals(&mut commands, pellets);
+}
@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::mesh::skinning::SkinnedMeshInverseBindposes;
+use bevy::render::primitives::Aabb;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+
+// Headless comparison of finding spray targets through the spatial index and by testing every
+// Decalable, with 5000 quads and 10 sprays per frame.
+// Run with `cargo run --release --example spatial_index_benchmark`
+
+const GRID: usize = 71;     // Quads per side, about 5000 in total
+const SPACING: f32 = 4.;
+const SPRAYS_PER_FRAME: usize = 10;
+const FRAMES: u32 = 100;
+
+fn main() {
+    for spatial_index in [false, true] {
+        let elapsed = run(spatial_index);
+        println!(
+            "{}: {:.3} ms per frame",
+            if spatial_index { "spatial index" } else { "linear scan" },
+            elapsed.as_secs_f64() * 1000. / FRAMES as f64,
+        );
+    }
+}
+
+fn run(spatial_index: bool) -> Duration {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, HierarchyPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<Image>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<SkinnedMeshInverseBindposes>()
+        .add_plugins(DecalPlugin::new().with_settings(DecalSettings {
+            spatial_index,
+            limit_mode: DecalLimitMode::ReplaceOldest,
+            ..default()
+        }));
+
+    let quad = app.world_mut().resource_mut::<Assets<Mesh>>().add(quad());
+    let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+    for x in 0..GRID {
+        for z in 0..GRID {
+            app.world_mut().spawn((
+                Mesh3d(quad.clone()),
+                Transform::from_xyz(x as f32 * SPACING, 0., z as f32 * SPACING),
+                // No render plugin computing bounds here
+                Aabb::from_min_max(Vec3::new(-1., 0., -1.), Vec3::new(1., 0., 1.)),
+                Decalable::default(),
+            ));
+        }
+    }
+    app.update();
+
+    // A tiny LCG is plenty for scattering sprays
+    let mut seed: u32 = 1;
+    let mut random = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        return (seed >> 8) as f32 / (1 << 24) as f32;
+    };
+
+    let extent = GRID as f32 * SPACING;
+    let rotation = Transform::IDENTITY.looking_to(Vec3::NEG_Y, Vec3::Z).rotation;
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..FRAMES {
+        let sprays: Vec<(Handle<StandardMaterial>, Transform)> = (0..SPRAYS_PER_FRAME)
+            .map(|_| {
+                let target = Vec3::new(random() * extent, 0., random() * extent);
+                (material.clone(), projector_transform(target + Vec3::Y, rotation, Vec2::splat(1.), 0.0..2.))
+            })
+            .collect();
+        spray_decals(&mut app.world_mut().commands(), sprays);
+
+        let start = Instant::now();
+        app.update();
+        elapsed += start.elapsed();
+    }
+    return elapsed;
+}
+
+// A 2 meter quad with the U16 indices decals need
+fn quad() -> Mesh {
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[-1., 0., -1.], [-1., 0., 1.], [1., 0., 1.], [1., 0., -1.]])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 1., 0.]; 4])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::mesh::skinning::SkinnedMeshInverseBindposes;
+use bevy::render::primitives::Aabb;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+
+// Headless comparison of clipping every triangle of a dense, 130k triangle plane and
+// gathering the triangles near the projector through a BVH, with 100 bullet hole sized decals.
+// Run with `cargo run --release --example triangle_bvh_benchmark`
+
+const GRID: usize = 256;    // Vertices per side, the most U16 indices can address
+const SIZE: f32 = 20.;
+const SPRAYS: usize = 100;
+
+fn main() {
+    for triangle_bvh in [false, true] {
+        let elapsed = run(triangle_bvh);
+        println!(
+            "{}: {:.3} ms for {} sprays",
+            if triangle_bvh { "triangle bvh" } else { "every triangle" },
+            elapsed.as_secs_f64() * 1000.,
+            SPRAYS,
+        );
+    }
+}
+
+fn run(triangle_bvh: bool) -> Duration {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, HierarchyPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<Image>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<SkinnedMeshInverseBindposes>()
+        .add_plugins(DecalPlugin::new().with_settings(DecalSettings {
+            triangle_bvh,
+            max_decals_per_entity: SPRAYS,
+            ..default()
+        }));
+
+    let plane = app.world_mut().resource_mut::<Assets<Mesh>>().add(dense_plane(GRID, SIZE));
+    let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+    app.world_mut().spawn((
+        Mesh3d(plane),
+        // No render plugin computing bounds here
+        Aabb::from_min_max(Vec3::new(-SIZE / 2., 0., -SIZE / 2.), Vec3::new(SIZE / 2., 0., SIZE / 2.)),
+        Decalable::default(),
+    ));
+    app.update();
+
+    // A tiny LCG is plenty for scattering sprays
+    let mut seed: u32 = 1;
+    let mut random = || {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        return (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+    };
+
+    let rotation = Transform::IDENTITY.looking_to(Vec3::NEG_Y, Vec3::Z).rotation;
+    let sprays: Vec<(Handle<StandardMaterial>, Transform)> = (0..SPRAYS)
+        .map(|_| {
+            let target = Vec3::new(random() * SIZE, 0., random() * SIZE);
+            (material.clone(), projector_transform(target + Vec3::Y * 0.1, rotation, Vec2::splat(0.1), 0.0..0.2))
+        })
+        .collect();
+    spray_decals(&mut app.world_mut().commands(), sprays);
+
+    // Includes building the BVH on the first spray
+    let start = Instant::now();
+    app.update();
+    let elapsed = start.elapsed();
+
+    let decals = app.world_mut().query::<&Decal>().iter(app.world()).count();
+    assert_eq!(decals, SPRAYS, "every spray should hit the plane");
+    return elapsed;
+}
+
+fn dense_plane(grid: usize, size: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(grid * grid);
+    let mut normals = Vec::with_capacity(grid * grid);
+    for z in 0..grid {
+        for x in 0..grid {
+            let uv = Vec2::new(x as f32, z as f32) / (grid - 1) as f32 - 0.5;
+            positions.push([uv.x * size, 0., uv.y * size]);
+            normals.push([0., 1., 0.]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity((grid - 1) * (grid - 1) * 6);
+    for z in 0..grid - 1 {
+        for x in 0..grid - 1 {
+            let i = (z * grid + x) as u16;
+            let row = grid as u16;
+            indices.extend_from_slice(&[i, i + row, i + 1, i + 1, i + row, i + row + 1]);
+        }
+    }
+
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U16(indices));
+}
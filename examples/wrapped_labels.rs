@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy_mesh_decal::prelude::*;
+
+// Wraps labels around two barrels with cylindrical projectors: the left one all the way
+// around, with the seam facing away from the camera, the right one across a 90° arc.
+
+const BARREL_RADIUS: f32 = 0.5;
+const BARREL_HEIGHT: f32 = 1.5;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(DecalPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let label = materials.add(StandardMaterial {
+        base_color_texture: Some(assets.load("graffiti2.png")),
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..default()
+    });
+
+    let barrel = meshes.add(u16_indices(Cylinder::new(BARREL_RADIUS, BARREL_HEIGHT).mesh().resolution(48).build()));
+    let steel = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.2, 0.35, 0.6),
+        perceptual_roughness: 0.4,
+        ..default()
+    });
+
+    for (x, shape) in [(-1., DecalShape::CYLINDER), (1., DecalShape::Cylinder { arc: 90_f32.to_radians() })] {
+        let center = Vec3::new(x, BARREL_HEIGHT * 0.5, 0.);
+        commands.spawn((
+            Mesh3d(barrel.clone()),
+            MeshMaterial3d(steel.clone()),
+            Transform::from_translation(center),
+            Decalable::default(),
+        ));
+
+        // Slightly wider than the barrel and around its middle, with the local Y axis along
+        // the barrel and +Z toward the camera, where the label is centered
+        let projector = Transform::from_translation(center)
+            .with_scale(Vec3::new(BARREL_RADIUS * 1.1, BARREL_HEIGHT * 0.3, BARREL_RADIUS * 1.1));
+        SprayDecal::new(label.clone(), projector)
+            .with_shape(shape)
+            .spray(&mut commands);
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(10., 10.))),
+        MeshMaterial3d(materials.add(StandardMaterial::default())),
+    ));
+
+    commands.spawn((DirectionalLight::default(), Transform::from_xyz(4., 8., 6.).looking_at(Vec3::ZERO, Vec3::Y)));
+
+    commands.spawn((Camera3d::default(), Transform::from_xyz(0., 1.8, 4.).looking_at(Vec3::new(0., 0.75, 0.), Vec3::Y)));
+}
+
+// Decals need U16 indices, Bevy's built in shapes use U32
+fn u16_indices(mut mesh: Mesh) -> Mesh {
+    let indices: Vec<u16> = mesh.indices().unwrap().iter().map(|index| index as u16).collect();
+    mesh.insert_indices(Indices::U16(indices));
+    return mesh;
+}
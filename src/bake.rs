@@ -0,0 +1,274 @@
+use std::any::TypeId;
+
+use bevy::asset::UntypedHandle;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::utils::HashMap;
+
+use crate::{Decalable, SprayId};
+
+/// Bakes the decals sprayed onto this entity into `texture`, in the UV space of its mesh, instead of
+/// spawning decal meshes. The mesh is left untouched and nothing stacks up, however often the surface
+/// gets painted, e.g. for an arena floor that's painted all the time. The decals only show up if the
+/// material of the entity samples `texture` somewhere, e.g. as its emissive or base color texture.
+///
+/// # Example:
+///
+/// ```
+/// let paint = images.add(DecalBakeTarget::image(1024, 1024));
+/// commands.spawn((
+///     Mesh3d(arena_floor),
+///     MeshMaterial3d(materials.add(StandardMaterial {
+///         base_color_texture: Some(paint.clone()),
+///         ..default()
+///     })),
+///     DecalBakeTarget::new(paint),
+/// ));
+/// ```
+///
+/// # Note
+///
+/// Decals are projected like any other, then drawn into the texture by the system of their material
+/// right after [`crate::DecalSet::Apply`], on the CPU, see [`DecalBake`]. Every triangle is drawn at
+/// the UVs of the mesh in `ATTRIBUTE_UV_0`, so overlapping UVs get the decal on all of their surfaces,
+/// and meshes without UVs fail with [`crate::DecalFailureReason::UnsupportedMesh`]. Skinned and morphed
+/// meshes are baked in their bind pose, and asynchronous sprays are projected right away.
+///
+/// Baked sprays don't spawn decal entities, so they don't count toward any decal limit, and send
+/// [`DecalBakedEvent`] instead of [`crate::DecalAppliedEvent`]. Clearing the decals means clearing the
+/// texture. The texture has to stay in the main world, and be in a format `Image::set_color_at` supports.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+#[require(Decalable)]
+pub struct DecalBakeTarget {
+    pub texture: Handle<Image>,
+}
+
+impl DecalBakeTarget {
+    pub fn new(texture: Handle<Image>) -> Self {
+        return DecalBakeTarget { texture };
+    }
+
+    /// A transparent `Rgba8UnormSrgb` texture to bake decals into, kept in the main world.
+    pub fn image(width: u32, height: u32) -> Image {
+        return Image::new_fill(
+            Extent3d { width, height, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        );
+    }
+}
+
+/// Sent once a spray was baked into the texture of a [`DecalBakeTarget`].
+#[derive(Event, Clone, Debug)]
+pub struct DecalBakedEvent {
+    /// The spray that was baked.
+    pub spray: SprayId,
+    /// The entity the spray was baked onto.
+    pub target: Entity,
+    /// Number of texels of the texture the decal covers.
+    pub texels: usize,
+}
+
+/// Materials that can be baked into a [`DecalBakeTarget`]. Decals are drawn with the color of
+/// the material, times its texture sampled at the decal UVs, blended over the texture by its alpha.
+pub trait DecalBake: Material {
+    /// Color the decal is baked with.
+    fn bake_color(&self) -> LinearRgba;
+    /// Texture the decal is baked with, sampled without filtering.
+    fn bake_texture(&self) -> Option<AssetId<Image>>;
+}
+
+impl DecalBake for StandardMaterial {
+    fn bake_color(&self) -> LinearRgba {
+        return self.base_color.to_linear();
+    }
+
+    fn bake_texture(&self) -> Option<AssetId<Image>> {
+        return self.base_color_texture.as_ref().map(|texture| texture.id());
+    }
+}
+
+impl<E: MaterialExtension> DecalBake for ExtendedMaterial<StandardMaterial, E> {
+    fn bake_color(&self) -> LinearRgba {
+        return self.base.bake_color();
+    }
+
+    fn bake_texture(&self) -> Option<AssetId<Image>> {
+        return self.base.bake_texture();
+    }
+}
+
+// What a material bakes with, see DecalBake
+pub(crate) struct DecalBrush {
+    color: LinearRgba,
+    texture: Option<AssetId<Image>>,
+}
+
+pub(crate) fn bake_brush<M: DecalBake>(material: &M) -> DecalBrush {
+    return DecalBrush { color: material.bake_color(), texture: material.bake_texture() };
+}
+
+// How the bake system of a material gets its brush, None without DecalPlugin::with_material_bake
+#[derive(Resource)]
+pub(crate) struct DecalBakeBrush<M: Material>(pub(crate) Option<fn(&M) -> DecalBrush>);
+
+// Sprays projected onto DecalBakeTargets, waiting for the system of their material
+#[derive(Resource, Default)]
+pub(crate) struct DecalBakeQueue(pub(crate) Vec<PendingBake>);
+
+pub(crate) struct PendingBake {
+    pub(crate) spray: SprayId,
+    pub(crate) target: Entity,
+    pub(crate) texture: Handle<Image>,
+    pub(crate) material: UntypedHandle,
+    pub(crate) triangles: Vec<[BakeVertex; 3]>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct BakeVertex {
+    target_uv: Vec2,    // Where the vertex is drawn in the texture
+    decal_uv: Vec2,     // Where the brush is sampled
+    color: Vec4,        // Vertex color, with the fades of the decal in its alpha
+}
+
+// Triangles of a decal mesh projected with DecalSettings::copy_target_uvs, None if the target has no UVs
+pub(crate) fn bake_triangles(mesh: &Mesh) -> Option<Vec<[BakeVertex; 3]>> {
+    let Some(VertexAttributeValues::Float32x2(decal_uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        return None;
+    };
+    let Some(VertexAttributeValues::Float32x2(target_uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_1) else {
+        return None;
+    };
+    let colors = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+        _ => None,
+    };
+    let indices = mesh.indices()?;
+
+    // Large decals and merged ones have U32 indices
+    let indices: Vec<usize> = indices.iter().collect();
+    let vertex = |index: usize| -> BakeVertex {
+        return BakeVertex {
+            target_uv: Vec2::from(target_uvs[index]),
+            decal_uv: Vec2::from(decal_uvs[index]),
+            color: colors.map_or(Vec4::ONE, |colors| Vec4::from(colors[index])),
+        };
+    };
+    return Some(indices.chunks_exact(3).map(|triangle| [vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2])]).collect());
+}
+
+// Draws the pending bakes of M into their textures, keeping those whose assets aren't loaded yet
+pub(crate) fn bake_decals<M: Material>(
+    mut queue: ResMut<DecalBakeQueue>,
+    brush: Res<DecalBakeBrush<M>>,
+    materials: Res<Assets<M>>,
+    mut images: ResMut<Assets<Image>>,
+    mut baked: EventWriter<DecalBakedEvent>,
+    mut warned: Local<bool>,
+) {
+    if !queue.0.iter().any(|bake| bake.material.type_id() == TypeId::of::<M>()) {
+        return;
+    }
+
+    let Some(brush) = brush.0 else {
+        if !*warned {
+            warn!("Baking decals needs DecalPlugin::with_material_bake, {} decals sprayed onto a DecalBakeTarget are dropped.", std::any::type_name::<M>());
+            *warned = true;
+        }
+        queue.0.retain(|bake| bake.material.type_id() != TypeId::of::<M>());
+        return;
+    };
+
+    queue.0.retain(|bake| {
+        if bake.material.type_id() != TypeId::of::<M>() {
+            return true;
+        }
+        let Some(material) = materials.get(bake.material.id().typed::<M>()) else {
+            return true;
+        };
+        let brush = brush(material);
+        let texture = match brush.texture {
+            Some(texture) => match images.get(texture) {
+                Some(texture) => Some(texture),
+                None => return true,
+            },
+            None => None,
+        };
+        let Some(target) = images.get(&bake.texture) else {
+            return true;
+        };
+
+        let texels = rasterize(&bake.triangles, target.width(), target.height(), &brush, texture);
+        let count = texels.len();
+        let target = images.get_mut(&bake.texture).unwrap();
+        for ((x, y), color) in texels {
+            let below = target.get_color_at(x, y).map(|color| color.to_linear()).unwrap_or(LinearRgba::NONE);
+            let alpha = color.alpha;
+            let blended = LinearRgba::new(
+                color.red * alpha + below.red * (1. - alpha),
+                color.green * alpha + below.green * (1. - alpha),
+                color.blue * alpha + below.blue * (1. - alpha),
+                alpha + below.alpha * (1. - alpha),
+            );
+            if let Err(error) = target.set_color_at(x, y, Color::LinearRgba(blended)) {
+                warn!("Can't bake decals into the texture of DecalBakeTarget {}: {error}", bake.target);
+                return false;
+            }
+        }
+
+        baked.send(DecalBakedEvent { spray: bake.spray, target: bake.target, texels: count });
+        return false;
+    });
+}
+
+// Color of every texel whose center is covered by the triangles. Each texel is only drawn once,
+// so the edges shared by triangles of the decal aren't blended twice.
+fn rasterize(triangles: &[[BakeVertex; 3]], width: u32, height: u32, brush: &DecalBrush, texture: Option<&Image>) -> HashMap<(u32, u32), LinearRgba> {
+    let size = Vec2::new(width as f32, height as f32);
+    let mut texels = HashMap::new();
+
+    for [a, b, c] in triangles {
+        let [pa, pb, pc] = [a, b, c].map(|vertex| vertex.target_uv * size);
+        let area = (pb - pa).perp_dot(pc - pa);
+        if area == 0. {
+            continue;
+        }
+
+        let min = pa.min(pb).min(pc).floor().max(Vec2::ZERO);
+        let max = pa.max(pb).max(pc).ceil().min(size);
+        for y in min.y as u32..max.y as u32 {
+            for x in min.x as u32..max.x as u32 {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                // Barycentric coordinates, positive inside of either winding
+                let weights = Vec3::new((pc - pb).perp_dot(p - pb), (pa - pc).perp_dot(p - pc), (pb - pa).perp_dot(p - pa)) / area;
+                if weights.min_element() < 0. || texels.contains_key(&(x, y)) {
+                    continue;
+                }
+
+                let decal_uv = a.decal_uv * weights.x + b.decal_uv * weights.y + c.decal_uv * weights.z;
+                let vertex_color = a.color * weights.x + b.color * weights.y + c.color * weights.z;
+                let sampled = texture.map_or(LinearRgba::WHITE, |texture| sample(texture, decal_uv));
+                let color = (components(brush.color) * components(sampled) * vertex_color).clamp(Vec4::ZERO, Vec4::ONE);
+                texels.insert((x, y), LinearRgba::new(color.x, color.y, color.z, color.w));
+            }
+        }
+    }
+    return texels;
+}
+
+fn components(color: LinearRgba) -> Vec4 {
+    return Vec4::new(color.red, color.green, color.blue, color.alpha);
+}
+
+// Nearest texel of the brush texture at a UV, clamped to its edges
+fn sample(texture: &Image, uv: Vec2) -> LinearRgba {
+    let x = ((uv.x * texture.width() as f32) as u32).min(texture.width().saturating_sub(1));
+    let y = ((uv.y * texture.height() as f32) as u32).min(texture.height().saturating_sub(1));
+    return texture.get_color_at(x, y).map(|color| color.to_linear()).unwrap_or(LinearRgba::WHITE);
+}
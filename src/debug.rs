@@ -0,0 +1,238 @@
+use bevy::color::palettes::css::{LIME, ORANGE, RED};
+use bevy::prelude::*;
+
+use crate::{DecalSettings, DecalShape, Decalable, DecalableGroup};
+
+/// Draws the projection volume of sprays with gizmos, to see where they reach while tuning their
+/// sizes. Inserted by the [`crate::DecalPlugin`] with the `debug` feature, and disabled by default.
+/// Every spray is drawn in the frame it's applied in, as an outline of its box, or of its frustum
+/// for [`DecalShape::Perspective`], with a cross on the projector origin and an arrow along the
+/// projection. Sprays still queued, see [`crate::DecalSettings::max_sprays_per_frame`], or
+/// waiting for their atlas layout are drawn every frame until they're applied.
+///
+/// # Example:
+///
+/// ```
+/// fn toggle_projector_gizmos(keys: Res<ButtonInput<KeyCode>>, mut gizmos: ResMut<DecalProjectorGizmos>) {
+///     if keys.just_pressed(KeyCode::F3) {
+///         gizmos.enabled = !gizmos.enabled;
+///     }
+/// }
+/// ```
+///
+/// # Note
+///
+/// Sprays applied with [`crate::spray_decal_immediate`] don't go through the queue and aren't drawn.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource, Default)]
+pub struct DecalProjectorGizmos {
+    pub enabled: bool,
+    /// Number of frames applied sprays keep being drawn after the frame they were applied in.
+    pub frames: u32,
+    pub applied_color: Color,
+    pub queued_color: Color,
+}
+
+impl Default for DecalProjectorGizmos {
+    fn default() -> Self {
+        return DecalProjectorGizmos {
+            enabled: false,
+            frames: 0,
+            applied_color: LIME.into(),
+            queued_color: ORANGE.into(),
+        };
+    }
+}
+
+// Projectors recorded by the decal systems of every material while DecalProjectorGizmos is enabled
+#[derive(Resource, Default)]
+pub(crate) struct ProjectorGizmoLog {
+    applied: Vec<(Transform, DecalShape, u32)>,   // Along with the frames left to draw them
+    queued: Vec<(Transform, DecalShape)>,         // Only for the current frame
+}
+
+impl ProjectorGizmoLog {
+    pub(crate) fn applied(&mut self, projector: Transform, shape: DecalShape, frames: u32) {
+        self.applied.push((projector, shape, frames));
+    }
+
+    pub(crate) fn queued(&mut self, projector: Transform, shape: DecalShape) {
+        self.queued.push((projector, shape));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.applied.clear();
+        self.queued.clear();
+    }
+}
+
+pub(crate) fn draw_projector_gizmos(settings: Res<DecalProjectorGizmos>, mut log: ResMut<ProjectorGizmoLog>, mut gizmos: Gizmos) {
+    for (projector, shape, _) in log.applied.iter() {
+        draw_projector(&mut gizmos, projector, shape, settings.applied_color);
+    }
+    for (projector, shape) in log.queued.iter() {
+        draw_projector(&mut gizmos, projector, shape, settings.queued_color);
+    }
+
+    log.queued.clear();
+    log.applied.retain_mut(|(_, _, frames)| {
+        if *frames == 0 {
+            return false;
+        }
+        *frames -= 1;
+        return true;
+    });
+}
+
+fn draw_projector(gizmos: &mut Gizmos, projector: &Transform, shape: &DecalShape, color: Color) {
+    // Projector space spans -1 to 1, sprays project from the near end at +Z toward -Z
+    let near_scale = match shape {
+        DecalShape::Perspective { near_scale } => *near_scale,
+        _ => 1.,
+    };
+    let corner = |x: f32, y: f32, z: f32| -> Vec3 {
+        let scale = if z > 0. { near_scale } else { 1. };
+        return projector.transform_point(Vec3::new(x * scale, y * scale, z));
+    };
+
+    let square = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)];
+    for (index, (x, y)) in square.iter().enumerate() {
+        let (next_x, next_y) = square[(index + 1) % square.len()];
+        gizmos.line(corner(*x, *y, 1.), corner(next_x, next_y, 1.), color);
+        gizmos.line(corner(*x, *y, -1.), corner(next_x, next_y, -1.), color);
+        gizmos.line(corner(*x, *y, 1.), corner(*x, *y, -1.), color);
+    }
+
+    let origin = projector.translation;
+    let size = projector.scale.truncate().min_element() * 0.25;
+    for axis in [projector.right(), projector.up(), projector.forward()] {
+        gizmos.line(origin - axis * size, origin + axis * size, color);
+    }
+    gizmos.arrow(projector.transform_point(Vec3::Z), projector.transform_point(Vec3::NEG_Z), color);
+}
+
+/// Shows how full every [`Decalable`] is, in a label next to it: its decals out of its limit and
+/// the triangles of its decals, in red once it's at its limit, since that's when sprays stop
+/// sticking to it. Toggled with the [`DecalDebugOverlay`] resource, enabled by default.
+///
+/// # Example:
+///
+/// ```
+/// app.add_plugins((DefaultPlugins, DecalPlugin, DecalDebugPlugin));
+/// ```
+///
+/// # Note
+///
+/// Labels are UI text following the first active camera, one frame behind the decals. The
+/// meshes of a [`DecalableGroup`] show the sprays of their group out of its limit.
+pub struct DecalDebugPlugin;
+
+impl Plugin for DecalDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DecalDebugOverlay>()
+            .init_resource::<DecalDebugOverlay>()
+            .add_systems(Update, update_decal_labels);
+    }
+}
+
+/// Settings of the labels of the [`DecalDebugPlugin`].
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource, Default)]
+pub struct DecalDebugOverlay {
+    pub enabled: bool,
+    pub font_size: f32,
+    pub color: Color,
+    /// Color of the labels of entities at their limit.
+    pub full_color: Color,
+}
+
+impl Default for DecalDebugOverlay {
+    fn default() -> Self {
+        return DecalDebugOverlay {
+            enabled: true,
+            font_size: 14.,
+            color: Color::WHITE,
+            full_color: RED.into(),
+        };
+    }
+}
+
+// The label of a Decalable
+#[derive(Component)]
+struct DecalDebugLabel(Entity);
+
+fn update_decal_labels(
+    mut commands: Commands,
+    overlay: Res<DecalDebugOverlay>,
+    settings: Res<DecalSettings>,
+    decalables: Query<(Entity, &Decalable, &GlobalTransform)>,
+    groups: Query<&DecalableGroup>,
+    parents: Query<&Parent>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut labels: Query<(Entity, &DecalDebugLabel, &mut Text, &mut TextColor, &mut TextFont, &mut Node)>,
+) {
+    if !overlay.enabled {
+        for (label, ..) in labels.iter() {
+            commands.entity(label).despawn();
+        }
+        return;
+    }
+
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+    let mut labeled = Vec::new();
+    for (label, DecalDebugLabel(target), mut text, mut text_color, mut text_font, mut node) in labels.iter_mut() {
+        let Ok((_, decalable, transform)) = decalables.get(*target) else {
+            commands.entity(label).despawn();
+            continue;
+        };
+        labeled.push(*target);
+
+        let (usage, full) = usage(*target, decalable, &settings, &groups, &parents);
+        if text.0 != usage {
+            text.0 = usage;
+        }
+        text_color.0 = if full { overlay.full_color } else { overlay.color };
+        text_font.font_size = overlay.font_size;
+
+        // Hidden behind the camera or without any camera
+        match camera.and_then(|(camera, camera_transform)| camera.world_to_viewport(camera_transform, transform.translation()).ok()) {
+            Some(position) => {
+                node.display = Display::Flex;
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+            }
+            None => node.display = Display::None,
+        }
+    }
+
+    // Labels show up the frame after, once the UI laid them out
+    for (target, ..) in decalables.iter().filter(|(target, ..)| !labeled.contains(target)) {
+        commands.spawn((
+            DecalDebugLabel(target),
+            Text::default(),
+            TextFont { font_size: overlay.font_size, ..default() },
+            TextColor(overlay.color),
+            Node { position_type: PositionType::Absolute, ..default() },
+        ));
+    }
+}
+
+// What the label of a Decalable reads, and whether it's at its limit
+fn usage(target: Entity, decalable: &Decalable, settings: &DecalSettings, groups: &Query<&DecalableGroup>, parents: &Query<&Parent>) -> (String, bool) {
+    let triangles = decalable.triangle_count();
+    let group = std::iter::once(target)
+        .chain(parents.iter_ancestors(target))
+        .find_map(|entity| groups.get(entity).ok());
+    if let Some(group) = group {
+        let limit = group.max_decals().unwrap_or(settings.max_decals_per_entity);
+        return (format!("{}/{limit} group sprays\n{triangles} triangles", group.count()), group.count() >= limit);
+    }
+
+    return match decalable.triangle_limit(settings) {
+        Some(max_triangles) => (format!("{} decals\n{triangles}/{max_triangles} triangles", decalable.count()), triangles >= max_triangles),
+        None => {
+            let limit = decalable.max_decals().unwrap_or(settings.max_decals_per_entity);
+            (format!("{}/{limit} decals\n{triangles} triangles", decalable.count()), decalable.count() >= limit)
+        }
+    };
+}
@@ -0,0 +1,69 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+    mesh_bindings::mesh,
+}
+#import bevy_render::maths::affine3_to_square
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct DecalMaterialExtension {
+    edge_fade: f32,
+    depth_fade: f32,
+}
+
+@group(2) @binding(100)
+var<uniform> decal_material: DecalMaterialExtension;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    var fade = 1.0;
+
+#ifdef VERTEX_UVS_A
+    // Decal UVs span 0 to 1 across the projector, so the border distance is the distance to the clip planes
+    if decal_material.edge_fade > 0.0 {
+        let border = min(min(in.uv.x, 1.0 - in.uv.x), min(in.uv.y, 1.0 - in.uv.y));
+        fade *= smoothstep(0.0, decal_material.edge_fade, border);
+    }
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    // The decal entity sits at the projector, whose local z runs from 1 at the near end to -1 at the far end.
+    // The projector has no shear, so each local coordinate is a projection onto its axis.
+    if decal_material.depth_fade > 0.0 {
+        let world_from_local = affine3_to_square(mesh[in.instance_index].world_from_local);
+        let z_axis = world_from_local[2].xyz;
+        let z = dot(z_axis, in.world_position.xyz - world_from_local[3].xyz) / dot(z_axis, z_axis);
+        let depth = (1.0 - z) * 0.5;
+        fade *= 1.0 - smoothstep(1.0 - decal_material.depth_fade, 1.0, depth);
+    }
+#endif
+
+    pbr_input.material.base_color.a *= fade;
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
@@ -0,0 +1,321 @@
+use std::fmt::Write;
+
+use bevy::asset::UntypedAssetId;
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+
+use crate::{Decal, DecalGroup, DecalMaterialIds, DecalMerge, DecalOf, DecalSource, DecalTintColor, Decalable};
+
+/// Which decals [`export_decals`] exports.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecalExportSelection {
+    /// Every decal applied to this [`Decalable`].
+    Target(Entity),
+    /// Every decal of this group, on any target.
+    Group(DecalGroup),
+}
+
+/// Space of the vertices written by [`export_decals`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DecalExportSpace {
+    /// Relative to the target of each decal, so the decals line up with the target mesh
+    /// exported from the level on its own.
+    #[default]
+    Local,
+    /// World space, e.g. for decals across several targets.
+    World,
+}
+
+/// File format written by [`export_decals`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecalExportFormat {
+    /// Wavefront OBJ, without a material library. Objects use their material names.
+    Obj,
+    /// Binary glTF 2.0, with a material of the same name for every object.
+    Glb,
+}
+
+/// Error returned by [`export_decals`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DecalExportError {
+    /// The selection doesn't hold any decal.
+    NoDecals,
+    /// The mesh of this decal was only uploaded to the render world, see [`crate::DecalSettings::asset_usage`].
+    MeshUnavailable(Entity),
+}
+
+impl std::fmt::Display for DecalExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            DecalExportError::NoDecals => write!(f, "there are no decals to export"),
+            DecalExportError::MeshUnavailable(decal) => write!(f, "the mesh of decal {decal} isn't kept in the main world, add RenderAssetUsages::MAIN_WORLD to DecalSettings::asset_usage"),
+        };
+    }
+}
+
+impl std::error::Error for DecalExportError {}
+
+/// Writes the meshes of the selected decals into a file of `format`, with UVs and normals and
+/// one object per material, e.g. to bake the decals into the textures of a level in Blender.
+/// Objects are named after the [`DecalMaterialIds`] of their material, its asset path or its
+/// index, with the tint appended as hex sRGB.
+///
+/// # Example:
+///
+/// ```
+/// app.add_plugins(DecalPlugin::new().with_asset_usage(RenderAssetUsages::default()));
+///
+/// fn export(world: &mut World) {
+///     let obj = export_decals(world, DecalExportSelection::Target(wall), DecalExportSpace::Local, DecalExportFormat::Obj).unwrap();
+///     std::fs::write("wall_decals.obj", obj).unwrap();
+/// }
+/// ```
+///
+/// # Note
+///
+/// Needs [`crate::DecalSettings::asset_usage`] to include `RenderAssetUsages::MAIN_WORLD`,
+/// as the meshes are read back from `Assets<Mesh>`. Skinned and morphed decals are written in
+/// the bind pose of their target, and asynchronous decals still being computed are skipped.
+pub fn export_decals(world: &mut World, selection: DecalExportSelection, space: DecalExportSpace, format: DecalExportFormat) -> Result<Vec<u8>, DecalExportError> {
+    let decals: Vec<Entity> = match selection {
+        DecalExportSelection::Target(target) => {
+            let mut decals: Vec<Entity> = Vec::new();
+            // A merged decal is listed once per spray
+            for decal in world.get::<Decalable>(target).into_iter().flat_map(Decalable::decals) {
+                if !decals.contains(&decal) {
+                    decals.push(decal);
+                }
+            }
+            decals
+        }
+        DecalExportSelection::Group(group) => {
+            let mut decals: Vec<Entity> = world.query_filtered::<(Entity, &DecalGroup), With<Decal>>().iter(world)
+                .filter(|(_, decal_group)| **decal_group == group)
+                .map(|(entity, _)| entity)
+                .collect();
+            decals.sort_unstable();
+            decals
+        }
+    };
+
+    let mut objects: Vec<ExportObject> = Vec::new();
+    for decal in decals {
+        if world.get::<Decal>(decal).is_none() {
+            continue;
+        }
+
+        let mesh = world.get::<Mesh3d>(decal).and_then(|mesh| world.resource::<Assets<Mesh>>().get(mesh));
+        let Some(mesh) = mesh else {
+            return Err(DecalExportError::MeshUnavailable(decal));
+        };
+
+        let decal_transform = world.get::<GlobalTransform>(decal).copied().unwrap_or_default().compute_matrix();
+        let transform = match space {
+            DecalExportSpace::World => decal_transform,
+            DecalExportSpace::Local => {
+                let target = world.get::<DecalOf>(decal).and_then(|decal_of| world.get::<GlobalTransform>(decal_of.target));
+                target.copied().unwrap_or_default().compute_matrix().inverse() * decal_transform
+            }
+        };
+
+        let key = material_key(world, decal);
+        let object = match objects.iter().position(|object| object.key == key) {
+            Some(index) => &mut objects[index],
+            None => {
+                let name = material_name(world, key, objects.len());
+                objects.push(ExportObject { key, name, positions: Vec::new(), normals: Vec::new(), uvs: Vec::new(), indices: Vec::new() });
+                objects.last_mut().unwrap()
+            }
+        };
+        object.append(mesh, transform);
+    }
+
+    objects.retain(|object| !object.indices.is_empty());
+    if objects.is_empty() {
+        return Err(DecalExportError::NoDecals);
+    }
+
+    return Ok(match format {
+        DecalExportFormat::Obj => write_obj(&objects).into_bytes(),
+        DecalExportFormat::Glb => write_glb(&objects),
+    });
+}
+
+// Material of a decal before any variant, and its tint
+type MaterialKey = (Option<UntypedAssetId>, Option<[u8; 4]>);
+
+fn material_key(world: &World, decal: Entity) -> MaterialKey {
+    let source = world.get::<DecalSource>(decal)
+        .or_else(|| world.get::<DecalMerge>(decal).and_then(|merged| merged.parts.first()).map(|part| &part.source));
+    return (source.map(|source| source.material), world.get::<DecalTintColor>(decal).map(|tint| tint.0));
+}
+
+fn material_name(world: &World, (material, tint): MaterialKey, index: usize) -> String {
+    let registered = material.and_then(|material| world.get_resource::<DecalMaterialIds>()?.ids.get(&material).cloned());
+    let path = || material.and_then(|material| world.get_resource::<AssetServer>()?.get_path(material)).map(|path| path.to_string());
+    let mut name = registered.or_else(path).unwrap_or_else(|| format!("decal_material_{index}"));
+
+    if let Some([r, g, b, a]) = tint {
+        write!(name, "_{r:02x}{g:02x}{b:02x}{a:02x}").unwrap();
+    }
+    // Neither format allows line breaks in names, and OBJ splits them at spaces
+    return name.chars().map(|c| if c.is_whitespace() { '_' } else { c }).collect();
+}
+
+// The triangles of every decal of one material
+struct ExportObject {
+    key: MaterialKey,
+    name: String,
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    indices: Vec<u32>,
+}
+
+impl ExportObject {
+    fn append(&mut self, mesh: &Mesh, transform: Mat4) {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return;
+        };
+        let Some(indices) = mesh.indices() else {
+            return;
+        };
+
+        let normal_matrix = Mat3::from_mat4(transform).inverse().transpose();
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => normals.clone(),
+            _ => vec![[0.; 3]; positions.len()],
+        };
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => uvs.clone(),
+            _ => vec![[0.; 2]; positions.len()],
+        };
+
+        let first = self.positions.len() as u32;
+        self.positions.extend(positions.iter().map(|position| transform.transform_point3(Vec3::from(*position))));
+        self.normals.extend(normals.iter().map(|normal| (normal_matrix * Vec3::from(*normal)).normalize_or_zero()));
+        self.uvs.extend(uvs.iter().map(|uv| Vec2::from(*uv)));
+        self.indices.extend(indices.iter().map(|index| first + index as u32));
+    }
+}
+
+fn write_obj(objects: &[ExportObject]) -> String {
+    let mut obj = String::from("# Decals exported by bevy_mesh_decal\n");
+    let mut first = 1;
+    for object in objects {
+        writeln!(obj, "o {}", object.name).unwrap();
+        for position in object.positions.iter() {
+            writeln!(obj, "v {} {} {}", position.x, position.y, position.z).unwrap();
+        }
+        // OBJ puts the origin of the texture at the bottom left, Bevy at the top left
+        for uv in object.uvs.iter() {
+            writeln!(obj, "vt {} {}", uv.x, 1. - uv.y).unwrap();
+        }
+        for normal in object.normals.iter() {
+            writeln!(obj, "vn {} {} {}", normal.x, normal.y, normal.z).unwrap();
+        }
+
+        // Positions, UVs and normals share their indices
+        writeln!(obj, "usemtl {}", object.name).unwrap();
+        for triangle in object.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0] + first, triangle[1] + first, triangle[2] + first];
+            writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}").unwrap();
+        }
+        first += object.positions.len() as u32;
+    }
+    return obj;
+}
+
+const GLB_MAGIC: u32 = 0x4654_6C67;         // "glTF"
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;    // "JSON"
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;     // "BIN\0"
+const GLTF_ARRAY_BUFFER: u32 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const GLTF_FLOAT: u32 = 5126;
+const GLTF_UNSIGNED_INT: u32 = 5125;
+
+fn write_glb(objects: &[ExportObject]) -> Vec<u8> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let (mut nodes, mut meshes, mut materials, mut views, mut accessors) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    for (index, object) in objects.iter().enumerate() {
+        let name = json_string(&object.name);
+        let count = object.positions.len();
+        let (min, max) = object.positions.iter().fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), position| (min.min(*position), max.max(*position)));
+
+        // Every attribute gets its own view, all of them are multiples of 4 bytes long
+        let attributes: [(Vec<f32>, &str, u32); 3] = [
+            (object.positions.iter().flat_map(|position| position.to_array()).collect(), "VEC3", GLTF_ARRAY_BUFFER),
+            (object.normals.iter().flat_map(|normal| normal.to_array()).collect(), "VEC3", GLTF_ARRAY_BUFFER),
+            (object.uvs.iter().flat_map(|uv| uv.to_array()).collect(), "VEC2", GLTF_ARRAY_BUFFER),
+        ];
+        let first_accessor = accessors.len();
+        for (attribute, (values, kind, target)) in attributes.into_iter().enumerate() {
+            views.push(format!(r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{target}}}"#, buffer.len(), values.len() * 4));
+            buffer.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+            let bounds = if attribute == 0 {
+                format!(r#","min":[{},{},{}],"max":[{},{},{}]"#, min.x, min.y, min.z, max.x, max.y, max.z)
+            } else {
+                String::new()
+            };
+            accessors.push(format!(r#"{{"bufferView":{},"componentType":{GLTF_FLOAT},"count":{count},"type":"{kind}"{bounds}}}"#, views.len() - 1));
+        }
+
+        views.push(format!(r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{GLTF_ELEMENT_ARRAY_BUFFER}}}"#, buffer.len(), object.indices.len() * 4));
+        buffer.extend(object.indices.iter().flat_map(|index| index.to_le_bytes()));
+        accessors.push(format!(r#"{{"bufferView":{},"componentType":{GLTF_UNSIGNED_INT},"count":{},"type":"SCALAR"}}"#, views.len() - 1, object.indices.len()));
+
+        nodes.push(format!(r#"{{"name":{name},"mesh":{index}}}"#));
+        meshes.push(format!(
+            r#"{{"name":{name},"primitives":[{{"attributes":{{"POSITION":{},"NORMAL":{},"TEXCOORD_0":{}}},"indices":{},"material":{index}}}]}}"#,
+            first_accessor, first_accessor + 1, first_accessor + 2, first_accessor + 3,
+        ));
+
+        // The tint is the only part of the material the decal itself knows about
+        let color = match object.key.1 {
+            Some([r, g, b, a]) => {
+                let color = Color::srgba_u8(r, g, b, a).to_linear();
+                format!(r#","pbrMetallicRoughness":{{"baseColorFactor":[{},{},{},{}]}}"#, color.red, color.green, color.blue, color.alpha)
+            }
+            None => String::new(),
+        };
+        materials.push(format!(r#"{{"name":{name}{color}}}"#));
+    }
+
+    let node_indices: Vec<String> = (0..objects.len()).map(|index| index.to_string()).collect();
+    let json = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"bevy_mesh_decal"}},"scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{}],"materials":[{}],"buffers":[{{"byteLength":{}}}],"bufferViews":[{}],"accessors":[{}]}}"#,
+        node_indices.join(","), nodes.join(","), meshes.join(","), materials.join(","), buffer.len(), views.join(","), accessors.join(","),
+    );
+
+    // Chunks are padded to 4 bytes, JSON with spaces and binary data with zeros
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let length = 12 + 8 + json.len() + 8 + buffer.len();
+    let mut glb = Vec::with_capacity(length);
+    for word in [GLB_MAGIC, 2, length as u32, json.len() as u32, GLB_CHUNK_JSON] {
+        glb.extend(word.to_le_bytes());
+    }
+    glb.extend(json);
+    for word in [buffer.len() as u32, GLB_CHUNK_BIN] {
+        glb.extend(word.to_le_bytes());
+    }
+    glb.extend(buffer);
+    return glb;
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    return json;
+}
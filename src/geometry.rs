@@ -0,0 +1,490 @@
+use bevy::prelude::*;
+use smallvec::SmallVec;
+
+/// Planes `p · normal = 1` bounding the unit cube from -1 to 1, the projection volume of a
+/// box shaped decal. Clipped one after the other in this order by [`clip_triangle_to_unit_cube`].
+pub const UNIT_CUBE_PLANES: [Vec3; 6] = [Vec3::X, Vec3::Y, Vec3::Z, Vec3::NEG_X, Vec3::NEG_Y, Vec3::NEG_Z];
+
+// Distance from a plane within which corners count as lying on it, so corners barely off a plane
+// after being cut by another one, e.g. near the corners of the cube, don't leave slivers
+const PLANE_EPSILON: f32 = 1e-5;
+
+/// Data carried along by a [`ClipVertex`], interpolated between the corners of an edge
+/// wherever clipping cuts it, e.g. normals, UVs or colors. Tuples combine several of them.
+///
+/// # Example:
+///
+/// ```
+/// // A normal, a UV and a vertex color
+/// let vertex = ClipVertex::new(Vec3::ZERO, (Vec3::Y, Vec2::ZERO, Vec4::ONE));
+/// ```
+pub trait ClipPayload: Copy {
+    /// Interpolates from `self` at 0 to `rhs` at 1.
+    fn lerp(&self, rhs: &Self, t: f32) -> Self;
+}
+
+impl ClipPayload for () {
+    fn lerp(&self, _: &Self, _: f32) -> Self {}
+}
+
+impl ClipPayload for f32 {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return self + (rhs - self) * t;
+    }
+}
+
+impl ClipPayload for Vec2 {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return Vec2::lerp(*self, *rhs, t);
+    }
+}
+
+impl ClipPayload for Vec3 {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return Vec3::lerp(*self, *rhs, t);
+    }
+}
+
+impl ClipPayload for Vec4 {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return Vec4::lerp(*self, *rhs, t);
+    }
+}
+
+impl<A: ClipPayload, B: ClipPayload> ClipPayload for (A, B) {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return (self.0.lerp(&rhs.0, t), self.1.lerp(&rhs.1, t));
+    }
+}
+
+impl<A: ClipPayload, B: ClipPayload, C: ClipPayload> ClipPayload for (A, B, C) {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return (self.0.lerp(&rhs.0, t), self.1.lerp(&rhs.1, t), self.2.lerp(&rhs.2, t));
+    }
+}
+
+impl<A: ClipPayload, B: ClipPayload, C: ClipPayload, D: ClipPayload> ClipPayload for (A, B, C, D) {
+    fn lerp(&self, rhs: &Self, t: f32) -> Self {
+        return (self.0.lerp(&rhs.0, t), self.1.lerp(&rhs.1, t), self.2.lerp(&rhs.2, t), self.3.lerp(&rhs.3, t));
+    }
+}
+
+/// A corner of a [`ClipTriangle`], in the space of the clipping planes.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ClipVertex<P = ()> {
+    pub position: Vec3,
+    pub payload: P,
+}
+
+impl<P: ClipPayload> ClipVertex<P> {
+    pub fn new(position: Vec3, payload: P) -> Self {
+        return ClipVertex { position, payload };
+    }
+
+    /// Interpolates the position and the payload from `self` at 0 to `rhs` at 1.
+    pub fn lerp(&self, rhs: ClipVertex<P>, t: f32) -> ClipVertex<P> {
+        return ClipVertex {
+            position: self.position.lerp(rhs.position, t),
+            payload: self.payload.lerp(&rhs.payload, t),
+        };
+    }
+}
+
+/// A triangle being clipped. Clipping keeps the winding of `a`, `b`, `c`.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ClipTriangle<P = ()> {
+    pub a: ClipVertex<P>,
+    pub b: ClipVertex<P>,
+    pub c: ClipVertex<P>,
+}
+
+impl<P: ClipPayload> ClipTriangle<P> {
+    pub fn new(a: ClipVertex<P>, b: ClipVertex<P>, c: ClipVertex<P>) -> Self {
+        return ClipTriangle { a, b, c };
+    }
+
+    pub fn positions(&self) -> [Vec3; 3] {
+        return [self.a.position, self.b.position, self.c.position];
+    }
+}
+
+/// Clips `triangle` to the unit cube from -1 to 1, see [`UNIT_CUBE_PLANES`]. Returns nothing
+/// when it's entirely outside, the triangle itself when it's entirely inside, and otherwise the
+/// triangles covering the part inside, with their payloads interpolated along the cut edges.
+///
+/// # Example:
+///
+/// ```
+/// // Project trim textures: bring the triangle into the space of the projector, clip it,
+/// // and map the UVs from the projected positions
+/// let to_projector = projector.compute_matrix().inverse();
+/// let corner = |position: Vec3, normal: Vec3| ClipVertex::new(to_projector.transform_point3(position), normal);
+/// for triangle in clip_triangle_to_unit_cube(ClipTriangle::new(corner(a, na), corner(b, nb), corner(c, nc))) {
+///     let uvs = triangle.positions().map(|position| position.xy() * 0.5 + 0.5);
+/// }
+/// ```
+///
+/// # Note
+///
+/// Vertices cut onto a plane are snapped onto it, and shared edges are always cut the same way
+/// no matter the order of their corners, so neighboring triangles don't get cracks between them.
+pub fn clip_triangle_to_unit_cube<P: ClipPayload>(triangle: ClipTriangle<P>) -> SmallVec<[ClipTriangle<P>; 8]> {
+    let mut buffers = ClipBuffers::default();
+    clip_triangle(triangle, &UNIT_CUBE_PLANES, &mut buffers);
+    return buffers.output;
+}
+
+/// Buffers reused by [`clip_triangle`], so clipping many triangles doesn't allocate for each one.
+pub struct ClipBuffers<P> {
+    polygon: SmallVec<[ClipVertex<P>; 9]>,
+    clipped: SmallVec<[ClipVertex<P>; 9]>,
+    output: SmallVec<[ClipTriangle<P>; 8]>,
+}
+
+impl<P> Default for ClipBuffers<P> {
+    fn default() -> Self {
+        return ClipBuffers { polygon: SmallVec::new(), clipped: SmallVec::new(), output: SmallVec::new() };
+    }
+}
+
+/// Clips `triangle` against the planes `p · normal = 1` one after the other, e.g. the sides of a
+/// frustum, keeping the part where `p · normal <= 1` for all of them. Returns the resulting
+/// triangles, which stay in `buffers` until the next call.
+///
+/// The triangle is clipped as one polygon and only split into triangles at the end, so the
+/// result only has corners of the clipped polygon, whatever the order of the planes or corners.
+pub fn clip_triangle<'a, P: ClipPayload>(triangle: ClipTriangle<P>, normals: &[Vec3], buffers: &'a mut ClipBuffers<P>) -> &'a [ClipTriangle<P>] {
+    let ClipBuffers { polygon, clipped, output } = buffers;
+    polygon.clear();
+    output.clear();
+    polygon.extend([triangle.a, triangle.b, triangle.c]);
+
+    let on_plane = |f: f32| if (f - 1.).abs() < PLANE_EPSILON { 1. } else { f };
+    for normal in normals {
+        clipped.clear();
+        for (corner, a) in polygon.iter().enumerate() {
+            let b = polygon[(corner + 1) % polygon.len()];
+            let (fa, fb) = (on_plane(a.position.dot(*normal)), on_plane(b.position.dot(*normal)));
+            // Corners on the plane are kept, and only edges crossing it are cut
+            if fa <= 1. {
+                push_corner(clipped, *a);
+            }
+            if (fa < 1. && fb > 1.) || (fa > 1. && fb < 1.) {
+                push_corner(clipped, intersect(*a, b, fa, fb, *normal));
+            }
+        }
+        if clipped.len() > 1 && clipped[0].position.distance(clipped[clipped.len() - 1].position) < PLANE_EPSILON {
+            clipped.pop();
+        }
+        std::mem::swap(polygon, clipped);
+        if polygon.len() < 3 {
+            return &[];
+        }
+    }
+
+    // The clipped polygon is convex and keeps the winding of the triangle
+    for corner in 1..polygon.len() - 1 {
+        output.push(ClipTriangle { a: polygon[0], b: polygon[corner], c: polygon[corner + 1] });
+    }
+    return &output[..];
+}
+
+// Adds a corner to the clipped polygon, unless it lands on the previous one, like two cuts
+// meeting at a corner of the cube
+fn push_corner<P: ClipPayload>(polygon: &mut SmallVec<[ClipVertex<P>; 9]>, corner: ClipVertex<P>) {
+    if polygon.last().is_some_and(|last| last.position.distance(corner.position) < PLANE_EPSILON) {
+        return;
+    }
+    polygon.push(corner);
+}
+
+/// Slices `triangle` along the plane `p · normal = 1`, adding the triangles covering the part
+/// where `p · normal < 1` to `output`. Returns `false` when the triangle doesn't cross the plane
+/// and is entirely inside, which leaves `output` as it is.
+///
+/// There are seven cases: the whole triangle is outside and nothing is added, one corner is
+/// inside and one triangle is added, or two corners are inside and two triangles are added.
+/// Corners lying on the plane, within a tiny epsilon, count as outside when the triangle crosses
+/// it, so no degenerate triangles are added. Triangles only touching the plane from outside, or lying on
+/// it, are dropped, and those only touching it from inside are left as they are.
+pub fn slice<P: ClipPayload>(triangle: &ClipTriangle<P>, normal: Vec3, output: &mut impl Extend<ClipTriangle<P>>) -> bool {
+    let ClipTriangle { a, b, c } = *triangle;
+    let on_plane = |f: f32| if (f - 1.).abs() < PLANE_EPSILON { 1. } else { f };
+    let fa = on_plane(a.position.dot(normal));
+    let fb = on_plane(b.position.dot(normal));
+    let fc = on_plane(c.position.dot(normal));
+
+    if fa >= 1. && fb >= 1. && fc >= 1. { // Triangle is outside of the projection volume
+        return true;
+    }
+
+    if fa <= 1. && fb <= 1. && fc <= 1. { // Triangle is inside, at most touching the plane
+        return false;
+    }
+
+    if fa < 1. && fb >= 1. && fc >= 1. {
+        new_triangle(a, b, c, fa, fb, fc, normal, output);
+        return true;
+    }
+
+    if fa >= 1. && fb < 1. && fc >= 1. {
+        new_triangle(b, c, a, fb, fc, fa, normal, output);
+        return true;
+    }
+
+    if fa >= 1. && fb >= 1. && fc < 1. {
+        new_triangle(c, a, b, fc, fa, fb, normal, output);
+        return true;
+    }
+    // Quads
+    if fa >= 1. && fb < 1. && fc < 1. {
+        new_quad(a, b, c, fa, fb, fc, normal, output);
+        return true;
+    }
+
+    if fa < 1. && fb >= 1. && fc < 1. {
+        new_quad(b, c, a, fb, fc, fa, normal, output);
+        return true;
+    }
+
+    if fa < 1. && fb < 1. && fc >= 1. {
+        new_quad(c, a, b, fc, fa, fb, normal, output);
+        return true;
+    }
+
+    return false;
+}
+
+// Intersection of the edge between a and b with the clip plane. The edge is always
+// interpolated in the same direction and snapped onto axis aligned planes, so neighboring
+// triangles sharing the edge get exactly the same vertex and no cracks appear.
+fn intersect<P: ClipPayload>(a: ClipVertex<P>, b: ClipVertex<P>, fa: f32, fb: f32, normal: Vec3) -> ClipVertex<P> {
+    let (a, b, fa, fb) = if (a.position.x, a.position.y, a.position.z) <= (b.position.x, b.position.y, b.position.z) {
+        (a, b, fa, fb)
+    } else {
+        (b, a, fb, fa)
+    };
+
+    let mut vertex = a.lerp(b, (1. - fa) / (fb - fa));
+    if normal.y == 0. && normal.z == 0. {
+        vertex.position.x = normal.x;
+    } else if normal.x == 0. && normal.z == 0. {
+        vertex.position.y = normal.y;
+    } else if normal.x == 0. && normal.y == 0. {
+        vertex.position.z = normal.z;
+    }
+    return vertex;
+}
+
+// Create a new triangle between a, ab, ac
+fn new_triangle<P: ClipPayload>(
+    a: ClipVertex<P>, b: ClipVertex<P>, c: ClipVertex<P>,
+    fa: f32, fb: f32, fc: f32,
+    normal: Vec3,
+    output: &mut impl Extend<ClipTriangle<P>>,
+) {
+    let ab = intersect(a, b, fa, fb, normal);
+    let ac = intersect(a, c, fa, fc, normal);
+    output.extend([
+        ClipTriangle {
+            a,
+            b: ab,
+            c: ac,
+        },
+    ]);
+}
+
+// Create two new triangles between b, c, ab, ac
+fn new_quad<P: ClipPayload>(
+    a: ClipVertex<P>, b: ClipVertex<P>, c: ClipVertex<P>,
+    fa: f32, fb: f32, fc: f32,
+    normal: Vec3,
+    output: &mut impl Extend<ClipTriangle<P>>,
+) {
+    let ab = intersect(a, b, fa, fb, normal);
+    let ac = intersect(a, c, fa, fc, normal);
+
+    output.extend([
+        ClipTriangle {
+            a: b,
+            b: c,
+            c: ac,
+        },
+        ClipTriangle {
+            a: b,
+            b: ac,
+            c: ab,
+        },
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    type Triangle = ClipTriangle<(Vec2, f32)>;
+
+    #[test]
+    fn slice_outside() {
+        let mut output: Vec<Triangle> = Vec::new();
+        assert!(slice(&triangle([Vec2::new(2., 0.), Vec2::new(3., 0.), Vec2::new(2., 1.)]), Vec3::X, &mut output));
+        assert!(output.is_empty(), "triangles outside of the plane are dropped");
+    }
+
+    #[test]
+    fn slice_one_corner_inside() {
+        let one_inside = triangle([Vec2::new(0., 0.), Vec2::new(2., 0.), Vec2::new(2., 2.)]);
+        for turn in 0..3 {
+            let mut output: Vec<Triangle> = Vec::new();
+            assert!(slice(&rotate(one_inside, turn), Vec3::X, &mut output));
+            assert_eq!(output.len(), 1);
+            check(&one_inside, &output, Vec3::X, 0.5);
+        }
+    }
+
+    #[test]
+    fn slice_two_corners_inside() {
+        let two_inside = triangle([Vec2::new(2., 0.), Vec2::new(0., -1.), Vec2::new(0., 1.)]);
+        for turn in 0..3 {
+            let mut output: Vec<Triangle> = Vec::new();
+            assert!(slice(&rotate(two_inside, turn), Vec3::X, &mut output));
+            assert_eq!(output.len(), 2);
+            check(&two_inside, &output, Vec3::X, 1.5);
+        }
+    }
+
+    #[test]
+    fn slice_inside() {
+        let mut output: Vec<Triangle> = Vec::new();
+        assert!(!slice(&triangle([Vec2::new(0., 0.), Vec2::new(0.5, 0.), Vec2::new(0., 0.5)]), Vec3::X, &mut output));
+        assert!(output.is_empty(), "triangles inside of the plane are left to the caller");
+    }
+
+    #[test]
+    fn slice_corners_on_plane() {
+        // Touching the plane from outside, with a corner or an edge, or lying on it
+        for outside in [
+            triangle([Vec2::new(1., 0.), Vec2::new(2., 0.), Vec2::new(2., 1.)]),
+            triangle([Vec2::new(1., 0.), Vec2::new(2., 0.), Vec2::new(1., 1.)]),
+            ClipTriangle::new(vertex(Vec3::new(1., 0., 0.)), vertex(Vec3::new(1., 1., 0.)), vertex(Vec3::new(1., 0., 1.))),
+        ] {
+            for turn in 0..3 {
+                let mut output: Vec<Triangle> = Vec::new();
+                assert!(slice(&rotate(outside, turn), Vec3::X, &mut output));
+                assert!(output.is_empty(), "triangles touching the plane from outside are dropped");
+            }
+        }
+
+        // Touching the plane from inside, with a corner or an edge
+        for inside in [
+            triangle([Vec2::new(1., 0.), Vec2::new(0., 0.), Vec2::new(0., 1.)]),
+            triangle([Vec2::new(1., 0.), Vec2::new(1., 1.), Vec2::new(0., 0.)]),
+        ] {
+            for turn in 0..3 {
+                let mut output: Vec<Triangle> = Vec::new();
+                assert!(!slice(&rotate(inside, turn), Vec3::X, &mut output));
+                assert!(output.is_empty(), "triangles touching the plane from inside are left to the caller");
+            }
+        }
+
+        // Crossing the plane with a corner on it, cut along the edge from that corner
+        let crossing = triangle([Vec2::new(0., 0.), Vec2::new(2., 0.), Vec2::new(1., 1.)]);
+        for turn in 0..3 {
+            let mut output: Vec<Triangle> = Vec::new();
+            assert!(slice(&rotate(crossing, turn), Vec3::X, &mut output));
+            assert_eq!(output.len(), 1);
+            check(&crossing, &output, Vec3::X, 0.5);
+        }
+    }
+
+    #[test]
+    fn clip_to_unit_cube() {
+        let outside = ClipTriangle::new(vertex(Vec3::new(-1., -1., 5.)), vertex(Vec3::new(1., -1., 5.)), vertex(Vec3::new(0., 1., 5.)));
+        assert!(clip_triangle_to_unit_cube(outside).is_empty(), "triangles outside of the cube are dropped");
+
+        let inside = triangle([Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5), Vec2::new(0., 0.5)]);
+        assert_eq!(clip_triangle_to_unit_cube(inside).as_slice(), &[inside], "triangles inside of the cube are kept as they are");
+
+        let covering = triangle([Vec2::new(-10., -10.), Vec2::new(10., -10.), Vec2::new(0., 10.)]);
+        let clipped = clip_triangle_to_unit_cube(covering);
+        for plane in [Vec3::X, Vec3::Y, Vec3::NEG_X, Vec3::NEG_Y] {
+            check(&covering, &clipped, plane, 4.);
+        }
+    }
+
+    #[test]
+    fn edges_through_cube_corners() {
+        // The long edge runs through the corners at (-1, 1) and (1, -1), where every cut leaves
+        // a vertex barely off the next plane
+        let diagonal = triangle([Vec2::new(-2., 2.), Vec2::new(-2., -2.), Vec2::new(2., -2.)]);
+        for turn in 0..3 {
+            let clipped = clip_triangle_to_unit_cube(rotate(diagonal, turn));
+            for plane in [Vec3::X, Vec3::Y, Vec3::NEG_X, Vec3::NEG_Y] {
+                check(&diagonal, &clipped, plane, 2.);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_edges_cut_alike() {
+        // Both triangles share the edge from (0, 0) to (2, 1), in opposite directions
+        let mut buffers = ClipBuffers::default();
+        let left: Vec<Vec3> = clip_triangle(triangle([Vec2::new(0., 0.), Vec2::new(2., 1.), Vec2::new(0., 1.)]), &[Vec3::X], &mut buffers)
+            .iter()
+            .flat_map(|triangle| triangle.positions())
+            .collect();
+        let right: Vec<Vec3> = clip_triangle(triangle([Vec2::new(2., 1.), Vec2::new(0., 0.), Vec2::new(2., 0.)]), &[Vec3::X], &mut buffers)
+            .iter()
+            .flat_map(|triangle| triangle.positions())
+            .collect();
+
+        let cut = |positions: &[Vec3]| positions.iter().copied().find(|position| position.distance(Vec3::new(1., 0.5, 0.)) < EPSILON);
+        let (Some(left_cut), Some(right_cut)) = (cut(&left), cut(&right)) else {
+            panic!("the shared edge is cut in both triangles");
+        };
+        assert_eq!(left_cut.to_array().map(f32::to_bits), right_cut.to_array().map(f32::to_bits), "both triangles get bit identical vertices on the shared edge");
+        assert_eq!(left_cut.x, 1., "the cut is snapped onto the plane");
+    }
+
+    // A triangle on the z = 0 plane, carrying a UV and a value that both vary linearly with the position
+    fn triangle(corners: [Vec2; 3]) -> Triangle {
+        let [a, b, c] = corners.map(|corner| vertex(corner.extend(0.)));
+        return ClipTriangle::new(a, b, c);
+    }
+
+    fn vertex(position: Vec3) -> ClipVertex<(Vec2, f32)> {
+        return ClipVertex::new(position, (position.xy() * 0.5 + 0.5, linear(position)));
+    }
+
+    fn linear(position: Vec3) -> f32 {
+        return 2. * position.x - position.y + 3.;
+    }
+
+    // The same triangle, starting from another corner
+    fn rotate<P: Copy>(triangle: ClipTriangle<P>, turns: usize) -> ClipTriangle<P> {
+        let mut corners = [triangle.a, triangle.b, triangle.c];
+        corners.rotate_left(turns);
+        return ClipTriangle { a: corners[0], b: corners[1], c: corners[2] };
+    }
+
+    // The clipped triangles are inside of the plane, cover the expected area without degenerate
+    // triangles, keep the winding of the source triangle, and their payloads match their positions
+    fn check(source: &Triangle, clipped: &[Triangle], plane: Vec3, area: f32) {
+        let winding = |[a, b, c]: [Vec3; 3]| (b - a).cross(c - a).z;
+        let total: f32 = clipped.iter().map(|triangle| winding(triangle.positions()).abs() * 0.5).sum();
+        assert!((total - area).abs() < EPSILON, "the clipped triangles cover an area of {total}, not {area}");
+
+        for triangle in clipped {
+            let triangle_winding = winding(triangle.positions());
+            assert!(triangle_winding.abs() > EPSILON, "{:?} is degenerate", triangle.positions());
+            assert_eq!(triangle_winding.signum(), winding(source.positions()).signum(), "clipping keeps the winding");
+            for vertex in [triangle.a, triangle.b, triangle.c] {
+                assert!(vertex.position.dot(plane) <= 1. + EPSILON, "{} is outside of the plane", vertex.position);
+                assert!((vertex.payload.0 - (vertex.position.xy() * 0.5 + 0.5)).length() < EPSILON, "the UV is interpolated along the cut");
+                assert!((vertex.payload.1 - linear(vertex.position)).abs() < EPSILON, "the value is interpolated along the cut");
+            }
+        }
+    }
+}
@@ -1,16 +1,81 @@
-use bevy::pbr::NotShadowCaster;
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::asset::{LoadState, UntypedAssetId, UntypedHandle};
+use bevy::core::FrameCount;
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::ecs::archetype::Archetypes;
+use bevy::ecs::component::{ComponentHooks, Components, StorageType};
+use bevy::ecs::entity::{Entities, EntityMapper, MapEntities};
+use bevy::ecs::schedule::InternedScheduleLabel;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::ecs::system::{EntityCommands, SystemParam, SystemState};
+use bevy::pbr::{ExtendedMaterial, MaterialExtension, NotShadowCaster, NotShadowReceiver};
 
 use bevy::prelude::*;
 use bevy::render::mesh::Indices;
+use bevy::render::mesh::MeshAabb;
+use bevy::render::mesh::MeshVertexAttribute;
 use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::mesh::morph::MeshMorphWeights;
+use bevy::render::mesh::morph::MorphAttributes;
+use bevy::render::mesh::morph::MorphTargetImage;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::mesh::skinning::SkinnedMeshInverseBindposes;
+use bevy::render::primitives::Aabb;
 use bevy::render::render_asset::RenderAssetUsages;
 use bevy::render::render_resource::PrimitiveTopology;
+use bevy::render::RenderPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{ComputeTaskPool, TaskPool};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy::transform::TransformSystem;
+use bevy::utils::{HashMap, HashSet, Instant};
+
+use crate::bake::{bake_brush, bake_decals, bake_triangles, DecalBake, DecalBakeBrush, DecalBakeQueue, DecalBakeTarget, DecalBakedEvent, DecalBrush, PendingBake};
+use crate::geometry::{clip_triangle, ClipBuffers, ClipPayload, ClipTriangle, ClipVertex};
+#[cfg(feature = "debug")]
+use crate::debug::{draw_projector_gizmos, DecalProjectorGizmos, ProjectorGizmoLog};
 
 pub mod prelude;
+pub mod geometry;
+pub mod bake;
+#[cfg(feature = "decal_material")]
+pub mod material;
+#[cfg(feature = "rapier")]
+pub mod rapier;
+#[cfg(feature = "picking")]
+pub mod picking;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "debug")]
+pub mod debug;
 
+// Defaults of the DecalSettings resource
 const DECAL_REMOVE_BACKFACES: bool = true; // When false, both sides of the mesh will be sprayed with a decal
 const DECAL_MAX_PER_ENTTIY: usize = 16;    // Max number of decals you can stick on one entity
-const DECAL_EPSILON: f32 = 0.00016;        // The offset of the decal from the base mesh, to prevent Z-fighting
+const DECAL_MAX_TRIANGLES_PER_ENTITY: usize = 4096; // Max number of decal triangles on one entity, when limiting triangles
+const DECAL_EPSILON: f32 = 0.00016;        // The offset of the decal from the base mesh in world units, to prevent Z-fighting
+
+const DECAL_WELD_EPSILON: f32 = 0.00001;   // Distance in projector space under which vertices are welded together
+const DECAL_PARALLEL_CHUNK: usize = 4096;  // Triangles per task when clipping large meshes in parallel
+const DECAL_MAX_QUEUED: usize = 1024;      // Sprays waiting for a frame budget, further sprays are dropped
+const DECAL_INDEX_CELL: f32 = 8.;          // Cell size of the spatial index over Decalables, in world units
+const DECAL_INDEX_MAX_CELLS: i64 = 4096;   // Entities spanning more cells are always candidates instead
+const DECAL_CACHE_EPSILON: f32 = 0.00001;  // Change of a target's transform that invalidates its cached vertices
+const DECAL_BVH_LEAF: usize = 4;           // Triangles per leaf of a triangle BVH
+const DECAL_DEPTH_BIAS: f32 = 16.;         // Depth bias per stacking layer, see DecalOffsetMode::DepthBias
+const DECAL_BLEND_BIAS: f32 = 0.001;       // Sorting bias per stacking layer, too small to change the depth test
+const DECAL_OCCLUSION_RESOLUTION: usize = 32;   // Cells per side of the occlusion grid
+const DECAL_OCCLUSION_TOLERANCE: f32 = 0.05;    // Depth in world units behind the nearest surface that still gets sprayed
+const DECAL_CONNECT_EPSILON: f32 = 0.0001;      // Distance in world units at which clipped triangles count as connected
+const DECAL_CURVED_EDGE: f32 = 0.2;             // Longest triangle edge in projector space of curved decal shapes
 
 /// Decalable component. Add this to entities that you wish to apply decals onto.
 /// 
@@ -18,9 +83,400 @@ const DECAL_EPSILON: f32 = 0.00016;        // The offset of the decal from the b
 /// 
 /// ```
 /// commands.entity(my_entity).insert(Decalable::default());
+///
+/// // A huge floor that can hold a lot more decals than usual
+/// commands.entity(my_floor).insert(Decalable::with_limit(64));
 /// ```
-#[derive(Component, Default)]
-pub struct Decalable(usize); // Stores the number of decals already applied
+///
+/// # Note
+///
+/// Removing the component, or despawning the entity, also despawns its decals,
+/// which frees their generated meshes. Use [`DecalBlocked`] to only pause spraying.
+#[derive(Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct Decalable {
+    #[reflect(ignore)]
+    decals: Vec<DecalSlot>,                 // Decals applied to this entity, oldest first
+    max_decals: Option<usize>,              // Overrides DecalSettings::max_decals_per_entity
+    max_triangles: Option<usize>,           // Limits triangles instead, see DecalLimitUnit::Triangles
+    limit_mode: Option<DecalLimitMode>,     // Overrides DecalSettings::limit_mode
+}
+
+impl Decalable {
+    /// Decalable that holds at most `max_decals` decals, instead of
+    /// [`DecalSettings::max_decals_per_entity`].
+    pub fn with_limit(max_decals: usize) -> Self {
+        return Decalable {
+            max_decals: Some(max_decals),
+            ..default()
+        };
+    }
+
+    /// Decalable that holds at most `max_triangles` decal triangles, instead of a number
+    /// of decals, see [`DecalLimitUnit::Triangles`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// // A terrain chunk weighs its decals by how much they cost to render
+    /// commands.entity(my_chunk).insert(Decalable::with_triangle_limit(20_000).with_limit_mode(DecalLimitMode::ReplaceOldest));
+    /// ```
+    pub fn with_triangle_limit(max_triangles: usize) -> Self {
+        return Decalable {
+            max_triangles: Some(max_triangles),
+            ..default()
+        };
+    }
+
+    /// What happens once this entity reached its limit, instead of [`DecalSettings::limit_mode`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// // The wall the player shoots most always shows the latest bullet holes
+    /// commands.entity(my_wall).insert(Decalable::with_limit(32).with_limit_mode(DecalLimitMode::ReplaceOldest));
+    /// ```
+    pub fn with_limit_mode(mut self, limit_mode: DecalLimitMode) -> Self {
+        self.limit_mode = Some(limit_mode);
+        return self;
+    }
+
+    /// Number of decals currently applied to this entity.
+    pub fn count(&self) -> usize {
+        return self.decals.len();
+    }
+
+    /// Number of decal triangles currently on this entity. Asynchronous decals count once they're done.
+    pub fn triangle_count(&self) -> usize {
+        return slot_triangles(&self.decals);
+    }
+
+    /// The decals currently applied to this entity, oldest first. A merged decal
+    /// is listed once for every spray it holds, see [`DecalSettings::merge_decals`].
+    pub fn decals(&self) -> impl Iterator<Item = Entity> + '_ {
+        return self.decals.iter().map(|slot| slot.decal);
+    }
+
+    /// The per-entity limit, `None` when using [`DecalSettings::max_decals_per_entity`].
+    pub fn max_decals(&self) -> Option<usize> {
+        return self.max_decals;
+    }
+
+    /// The per-entity limit mode, `None` when using [`DecalSettings::limit_mode`].
+    pub fn limit_mode(&self) -> Option<DecalLimitMode> {
+        return self.limit_mode;
+    }
+
+    /// The per-entity triangle limit, `None` unless set with [`Decalable::with_triangle_limit`].
+    pub fn max_triangles(&self) -> Option<usize> {
+        return self.max_triangles;
+    }
+
+    // The triangle limit of this entity, or None if it counts decals
+    fn triangle_limit(&self, settings: &DecalSettings) -> Option<usize> {
+        if self.max_triangles.is_some() || self.max_decals.is_some() || settings.limit_unit == DecalLimitUnit::Decals {
+            return self.max_triangles;
+        }
+        return Some(settings.max_triangles_per_entity);
+    }
+
+    fn remove_decal(&mut self, decal: Entity) {
+        self.decals.retain(|slot| slot.decal != decal);
+    }
+}
+
+impl Component for Decalable {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // Decals are children of their target, but a non-recursive despawn would orphan them and keep
+    // their meshes alive forever. Decals already despawned along with the target are skipped.
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(|mut world, target, _| {
+            let decals: Vec<Entity> = world.get::<Decalable>(target).unwrap().decals().collect();
+            world.commands().queue(move |world: &mut World| {
+                for decal in decals {
+                    if let Ok(decal) = world.get_entity_mut(decal) {
+                        decal.despawn_recursive();
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// What happens to sprays reaching an entity that already holds its maximum
+/// number of decals, see [`DecalSettings::limit_mode`].
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[reflect(PartialEq, Debug, Default)]
+pub enum DecalLimitMode {
+    /// The spray isn't applied to the entity.
+    #[default]
+    Refuse,
+    /// The oldest decal on the entity is despawned to make room, and the new
+    /// decal takes over its offset layer.
+    ReplaceOldest,
+}
+
+/// What the decal limit of an entity counts, see [`DecalSettings::limit_unit`].
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[reflect(PartialEq, Debug, Default)]
+pub enum DecalLimitUnit {
+    /// Every decal counts once, up to [`DecalSettings::max_decals_per_entity`].
+    #[default]
+    Decals,
+    /// Decals count by their triangles, up to [`DecalSettings::max_triangles_per_entity`],
+    /// so one decal covering a whole terrain chunk weighs as much as the bullet holes it could
+    /// hold instead. A decal with more triangles than the limit on its own is always refused.
+    ///
+    /// # Note
+    ///
+    /// Asynchronous sprays are let in by the triangles already on the entity, as their own
+    /// are only known once they're done, so they may go past the limit until the next spray.
+    Triangles,
+}
+
+// A decal on a Decalable, along with the layer used for its offset from the surface
+#[derive(Clone, Copy)]
+struct DecalSlot {
+    decal: Entity,
+    layer: usize,
+    triangles: usize,   // Of the decal, or of its spray's part of a merged decal
+}
+
+fn slot_triangles(slots: &[DecalSlot]) -> usize {
+    return slots.iter().map(|slot| slot.triangles).sum();
+}
+
+// Number of oldest slots to evict so `triangles` more fit within `max_triangles`, None if the decal is refused
+fn triangle_eviction(slots: &[DecalSlot], triangles: usize, max_triangles: usize, limit_mode: DecalLimitMode) -> Option<usize> {
+    if triangles > max_triangles {
+        return None;
+    }
+    let mut total = slot_triangles(slots);
+    let mut evict = 0;
+    while total + triangles > max_triangles {
+        if limit_mode == DecalLimitMode::Refuse {
+            return None;
+        }
+        total -= slots[evict].triangles;
+        evict += 1;
+    }
+    return Some(evict);
+}
+
+// Lowest layer not taken by any of the slots, starting at 1 so decals never sit on the surface itself
+fn free_layer(slots: &[DecalSlot]) -> usize {
+    return (1..).find(|layer| slots.iter().all(|slot| slot.layer != *layer)).unwrap();
+}
+
+/// Makes every mesh of a scene [`Decalable`], including meshes spawned into
+/// it later on. Add it to a `SceneRoot`, where the meshes are
+/// further down the hierarchy. Removing it removes the propagated [`Decalable`]s again.
+///
+/// # Example:
+///
+/// ```
+/// commands.spawn((
+///     SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("level.glb"))),
+///     DecalableScene,
+/// ));
+/// ```
+///
+/// # Note
+///
+/// Meshes that already are [`Decalable`] keep their own component.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct DecalableScene;
+
+// Marks Decalables inserted by a DecalableScene or a DecalableGroup, so they can be removed along with it
+#[derive(Component)]
+struct PropagatedDecalable;
+
+/// Treats the meshes below this entity as a single decal target, e.g. the primitives of a glTF mesh,
+/// which Bevy spawns as sibling entities with a mesh of their own. Every spray counts once toward the
+/// limit of the group, however many of its meshes it reaches, and takes the same offset layer on all
+/// of them. Like [`DecalableScene`], it makes the meshes below it [`Decalable`].
+///
+/// # Example:
+///
+/// ```
+/// // Decals on the crate vanish all at once, not primitive by primitive
+/// commands.spawn((
+///     SceneRoot(assets.load(GltfAssetLabel::Scene(0).from_asset("crate.glb"))),
+///     DecalableGroup::with_limit(8).with_limit_mode(DecalLimitMode::ReplaceOldest),
+/// ));
+/// ```
+///
+/// # Note
+///
+/// The group limit replaces the limits of its meshes, and only counts decals, never triangles. Once a
+/// spray goes past it with [`DecalLimitMode::ReplaceOldest`], the decals of the oldest spray on the group
+/// are despawned from all of its meshes. Meshes belong to the nearest group above them, and their decals
+/// aren't merged, see [`DecalSettings::merge_decals`], so each spray can be evicted on its own.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct DecalableGroup {
+    #[reflect(ignore)]
+    sprays: Vec<GroupSpray>,                // Sprays on the meshes of the group, oldest first
+    max_decals: Option<usize>,              // Overrides DecalSettings::max_decals_per_entity
+    limit_mode: Option<DecalLimitMode>,     // Overrides DecalSettings::limit_mode
+}
+
+impl DecalableGroup {
+    /// Group that holds at most `max_decals` sprays, instead of
+    /// [`DecalSettings::max_decals_per_entity`].
+    pub fn with_limit(max_decals: usize) -> Self {
+        return DecalableGroup {
+            max_decals: Some(max_decals),
+            ..default()
+        };
+    }
+
+    /// What happens once this group reached its limit, instead of [`DecalSettings::limit_mode`].
+    pub fn with_limit_mode(mut self, limit_mode: DecalLimitMode) -> Self {
+        self.limit_mode = Some(limit_mode);
+        return self;
+    }
+
+    /// Number of sprays currently applied to the meshes of this group, as of the last spray reaching it.
+    pub fn count(&self) -> usize {
+        return self.sprays.len();
+    }
+
+    /// The decals on the meshes of this group, oldest spray first.
+    pub fn decals(&self) -> impl Iterator<Item = Entity> + '_ {
+        return self.sprays.iter().flat_map(|spray| spray.decals.iter().map(|(decal, _)| *decal));
+    }
+
+    /// The group limit, `None` when using [`DecalSettings::max_decals_per_entity`].
+    pub fn max_decals(&self) -> Option<usize> {
+        return self.max_decals;
+    }
+
+    /// The group limit mode, `None` when using [`DecalSettings::limit_mode`].
+    pub fn limit_mode(&self) -> Option<DecalLimitMode> {
+        return self.limit_mode;
+    }
+
+    // Drops the decals despawned since, e.g. by their lifetime or by clearing their meshes
+    fn prune(&mut self, entities: &Entities) {
+        for spray in self.sprays.iter_mut() {
+            spray.decals.retain(|(decal, _)| entities.contains(*decal));
+        }
+        self.sprays.retain(|spray| !spray.decals.is_empty());
+    }
+}
+
+// The decals of one spray on the meshes of a DecalableGroup
+struct GroupSpray {
+    spray: SprayId,
+    decals: Vec<(Entity, Entity)>,  // Along with the mesh they're on
+}
+
+/// Entities with this component never receive decals from any spray, even if
+/// they are [`Decalable`].
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct DecalBlocked;
+
+/// Projects the decals on this [`Decalable`] again whenever its mesh asset is modified, e.g.
+/// terrain deformed by craters at runtime, so the decals keep following the surface. The decal
+/// meshes are replaced in place, so the decal entities and their components survive.
+///
+/// # Example:
+///
+/// ```
+/// commands.entity(terrain).insert((Decalable::with_limit(64), ReprojectOnMeshChange));
+/// ```
+///
+/// # Note
+///
+/// Every decal is projected again as if it was sprayed onto this target alone right now, which
+/// is as expensive as spraying it. Decals left without any triangle are despawned, merged
+/// decals once none of their sprays has any triangle left.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct ReprojectOnMeshChange;
+
+/// Decal layers, modeled after `RenderLayers`. A spray is only applied to
+/// [`Decalable`] entities whose layers intersect the layers of the spray.
+/// Entities without this component, and sprays that don't set
+/// [`SprayOptions::layers`], are on layer 0.
+///
+/// # Example:
+///
+/// ```
+/// const WORLD: usize = 0;
+/// const CHARACTERS: usize = 1;
+/// const PROPS: usize = 2;
+///
+/// commands.entity(my_prop).insert((Decalable::default(), DecalLayers::layer(PROPS)));
+///
+/// // Blood lands on characters and the world, but not on props
+/// SprayDecal::new(blood.clone(), my_transform)
+///     .with_layers(DecalLayers::from_layers(&[WORLD, CHARACTERS]))
+///     .spray(&mut commands);
+/// ```
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[reflect(Component, Default, PartialEq, Hash, Debug)]
+pub struct DecalLayers(u32);
+
+impl DecalLayers {
+    /// Number of available layers.
+    pub const TOTAL_LAYERS: usize = 32;
+
+    /// Only the given layer.
+    pub const fn layer(n: usize) -> Self {
+        return DecalLayers(0).with(n);
+    }
+
+    /// All of the given layers.
+    pub fn from_layers(layers: &[usize]) -> Self {
+        return layers.iter().fold(DecalLayers::none(), |mask, layer| mask.with(*layer));
+    }
+
+    /// Every layer.
+    pub const fn all() -> Self {
+        return DecalLayers(u32::MAX);
+    }
+
+    /// No layer at all, never intersects anything.
+    pub const fn none() -> Self {
+        return DecalLayers(0);
+    }
+
+    /// Add the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `layer` is not less than [`Self::TOTAL_LAYERS`].
+    pub const fn with(self, layer: usize) -> Self {
+        assert!(layer < Self::TOTAL_LAYERS);
+        return DecalLayers(self.0 | 1 << layer);
+    }
+
+    /// Remove the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `layer` is not less than [`Self::TOTAL_LAYERS`].
+    pub const fn without(self, layer: usize) -> Self {
+        assert!(layer < Self::TOTAL_LAYERS);
+        return DecalLayers(self.0 & !(1 << layer));
+    }
+
+    /// Whether both share at least one layer.
+    pub const fn intersects(&self, other: &DecalLayers) -> bool {
+        return self.0 & other.0 != 0;
+    }
+}
+
+impl Default for DecalLayers {
+    fn default() -> Self {
+        return DecalLayers::layer(0);
+    }
+}
 
 /// # Example:
 /// 
@@ -45,337 +501,5983 @@ pub struct Decalable(usize); // Stores the number of decals already applied
 /// world space. Decals will only be applied to entities
 /// with the Decalable component. This function will try to
 /// spray a decal only once after called.
-pub fn spray_decal(commands: &mut Commands, material: Handle<StandardMaterial>, transform: Transform) {
-    // This entity will be removed once the decals has been applied
-    commands.spawn((
-        transform,
-        ApplyingDecal(material),
-    ));
+///
+/// A negative scale mirrors the decal, e.g. `-1` along X flips the texture horizontally,
+/// and the decal still faces the same way. Negative scale along Z sprays from the other side.
+pub fn spray_decal<M: Material>(commands: &mut Commands, material: Handle<M>, transform: Transform) -> SprayId {
+    return spray_decal_with_options(commands, material, transform, SprayOptions::default());
 }
 
-#[derive(Component)]
-pub struct Decal;   // Marker component for all decals
-
-pub struct DecalPlugin;
-
-impl Plugin for DecalPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(Update, decal_system); 
-    }
+/// Same as [`spray_decal`], with per-spray [`SprayOptions`].
+///
+/// # Example:
+///
+/// ```
+/// spray_decal_with_options(
+///     &mut commands,
+///     my_material.clone(),
+///     my_transform,
+///     SprayOptions {
+///         // Large terrain, push the decal further from the surface
+///         offset: Some(0.002),
+///         ..default()
+///     },
+/// );
+/// ```
+pub fn spray_decal_with_options<M: Material>(
+    commands: &mut Commands,
+    material: Handle<M>,
+    transform: Transform,
+    options: SprayOptions,
+) -> SprayId {
+    return SprayDecal { material, transform, options }.spray(commands);
 }
 
-#[derive(Component)]
-struct ApplyingDecal(Handle<StandardMaterial>);
-
-#[derive(Clone, Copy)]
-struct Vertex {
-    position: Vec3,
-    normal: Vec3,
-    uv: Vec2,
+/// A spray along with all of its options, as a builder. Decals can use any
+/// [`Material`] with a [`DecalPlugin`] added for it, [`StandardMaterial`] by default.
+///
+/// # Example:
+///
+/// ```
+/// SprayDecal::new(my_material.clone(), my_transform)
+///     .with_offset(0.001)
+///     .with_backfaces(false)
+///     .with_uv_rotation(rng.gen_range(0. ..TAU))
+///     .spray(&mut commands);
+/// ```
+#[derive(Reflect, Clone)]
+pub struct SprayDecal<M: Material = StandardMaterial> {
+    pub material: Handle<M>,
+    /// Transform of the projector, see [`spray_decal`].
+    pub transform: Transform,
+    pub options: SprayOptions,
 }
 
-impl Vertex {
-    pub fn lerp(&self, rhs: Vertex, d: f32) -> Vertex {
-        return Vertex {
-            position: self.position.lerp(rhs.position, d),
-            normal: self.normal.lerp(rhs.normal, d),
-            uv: self.uv.lerp(rhs.uv, d),
-        }
+impl<M: Material> SprayDecal<M> {
+    pub fn new(material: Handle<M>, transform: Transform) -> Self {
+        return SprayDecal {
+            material,
+            transform,
+            options: SprayOptions::default(),
+        };
     }
-}
-
-struct Triangle {
-    a: Vertex,
-    b: Vertex,
-    c: Vertex,
-}
 
-fn is_inside_unit_cube (p: Vec3) -> bool {
-    return p.x.abs() <= 1. && p.y.abs() <= 1. && p.z.abs() <= 1.;
-}
+    /// Sprays from the camera through the `cursor` position, for example from
+    /// [`Window::cursor_position`], reaching up to `max_distance` along the ray.
+    /// Returns `None` when the cursor is outside of the viewport of the camera.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// if let Some(cursor) = window.cursor_position() {
+    ///     if let Some(spray) = SprayDecal::from_cursor(graffiti.clone(), camera, camera_transform, cursor, Vec2::ONE, 20.) {
+    ///         spray.spray(&mut commands);
+    ///     }
+    /// }
+    /// ```
+    pub fn from_cursor(
+        material: Handle<M>,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cursor: Vec2,
+        size: Vec2,
+        max_distance: f32,
+    ) -> Option<Self> {
+        let ray = cursor_ray(camera, camera_transform, cursor)?;
+        let rotation = cursor_rotation(ray, camera_transform);
+        return Some(SprayDecal::new(material, projector_transform(ray.origin, rotation, size, 0.0..max_distance)));
+    }
 
-// Create a new triangle between a, ab, ac
-fn new_triangle(
-    a: Vertex, b: Vertex, c: Vertex,
-    fa: f32, fb: f32, fc: f32,
-    triangles: &mut Vec<Triangle>,
-) {
-    let d_ab = (1. - fa) / (fb - fa);
-    let d_ac = (1. - fa) / (fc - fa);
-    let ab = a.lerp(b, d_ab);
-    let ac = a.lerp(c, d_ac);
-    triangles.push(
-        Triangle {
-            a: a,
-            b: ab,
-            c: ac,
-        }
-    );
-}
+    /// Same as [`SprayDecal::from_cursor`], but centered on the surface hit by the ray.
+    /// `hit_test` receives the ray and `max_distance` and returns the distance
+    /// along the ray to the hit, for example from a physics raycast. The projection
+    /// reaches `size.z / 2` in front of and behind the hit. Returns `None` when
+    /// nothing was hit.
+    pub fn from_cursor_hit(
+        material: Handle<M>,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cursor: Vec2,
+        size: Vec3,
+        max_distance: f32,
+        hit_test: impl FnOnce(Ray3d, f32) -> Option<f32>,
+    ) -> Option<Self> {
+        let ray = cursor_ray(camera, camera_transform, cursor)?;
+        let distance = hit_test(ray, max_distance).filter(|distance| *distance <= max_distance)?;
+        let rotation = cursor_rotation(ray, camera_transform);
+        let half_depth = size.z.abs() * 0.5;
+        return Some(SprayDecal::new(material, projector_transform(ray.origin, rotation, size.truncate(), distance - half_depth..distance + half_depth)));
+    }
 
-// Create two new triangles between b, c, ab, ac
-fn new_quad(
-    a: Vertex, b: Vertex, c: Vertex,
-    fa: f32, fb: f32, fc: f32,
-    triangles: &mut Vec<Triangle>,
-) {
-    let db = (1. - fa) / (fb - fa);
-    let dc = (1. - fa) / (fc - fa);
-    let ab = a.lerp(b, db);
-    let ac = a.lerp(c, dc);
+    /// See [`SprayOptions::offset`].
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.options.offset = Some(offset);
+        return self;
+    }
 
-    triangles.push(
-        Triangle {
-            a: b,
-            b: c,
-            c: ac,
-        }
-    );
-    triangles.push(
-        Triangle {
-            a: b,
-            b: ac,
-            c: ab,
-        }
-    );
-}
+    /// See [`SprayOptions::shape`].
+    pub fn with_shape(mut self, shape: DecalShape) -> Self {
+        self.options.shape = shape;
+        return self;
+    }
 
-// Attempt to slice the triangle along the plane defined by the axis-aligned normal
-fn slice(
-    triangle: &mut Triangle,
-    normal: Vec3,
-    triangles: &mut Vec<Triangle>,
-) -> bool {
-    let fa = triangle.a.position.dot(normal);
-    let fb = triangle.b.position.dot(normal);
-    let fc = triangle.c.position.dot(normal);
+    /// See [`SprayOptions::backfaces`].
+    pub fn with_backfaces(mut self, backfaces: bool) -> Self {
+        self.options.backfaces = Some(backfaces);
+        return self;
+    }
 
-    if fa > 1. && fb > 1. && fc > 1. { // Triangle is outside of the projection volume
-        return true;
+    /// See [`SprayOptions::uv_rotation`].
+    pub fn with_uv_rotation(mut self, uv_rotation: f32) -> Self {
+        self.options.uv_rotation = uv_rotation;
+        return self;
     }
 
-    if fa < 1. && fb > 1. && fc > 1. {
-        new_triangle(triangle.a, triangle.b, triangle.c, fa, fb, fc, triangles);
-        return true;
+    /// See [`SprayOptions::max_angle`].
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.options.max_angle = Some(max_angle);
+        return self;
     }
 
-    if fa > 1. && fb < 1. && fc > 1. {
-        new_triangle(triangle.b, triangle.c, triangle.a, fb, fc, fa, triangles);
-        return true;
+    /// See [`SprayOptions::uv_rect`].
+    pub fn with_uv_rect(mut self, uv_rect: Rect) -> Self {
+        self.options.uv_rect = Some(uv_rect);
+        return self;
     }
 
-    if fa > 1. && fb > 1. && fc < 1. {
-        new_triangle(triangle.c, triangle.a, triangle.b, fc, fa, fb, triangles);
-        return true;
+    /// Samples the cell at `index` of the atlas `layout`, see [`SprayOptions::atlas`].
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// // The same layout the inventory icons use
+    /// SprayDecal::new(stickers.clone(), my_transform)
+    ///     .with_atlas(sticker_layout.clone(), 7)
+    ///     .spray(&mut commands);
+    /// ```
+    pub fn with_atlas(mut self, layout: Handle<TextureAtlasLayout>, index: usize) -> Self {
+        self.options.atlas = Some(DecalAtlas { layout, index: DecalAtlasIndex::Index(index) });
+        return self;
     }
-    // Quads
-    if fa > 1. && fb < 1. && fc < 1. {
-        new_quad(triangle.a, triangle.b, triangle.c, fa, fb, fc, triangles);
-        return true;
+
+    /// Samples a random cell of the atlas `layout`, the same one for the same `seed`,
+    /// e.g. to pick one of several splatter variants per hit. See [`SprayOptions::atlas`].
+    pub fn with_random_atlas_cell(mut self, layout: Handle<TextureAtlasLayout>, seed: u64) -> Self {
+        self.options.atlas = Some(DecalAtlas { layout, index: DecalAtlasIndex::Random { seed } });
+        return self;
     }
 
-    if fa < 1. && fb > 1. && fc < 1. {
-        new_quad(triangle.b, triangle.c, triangle.a, fb, fc, fa, triangles);
-        return true;
+    /// See [`SprayOptions::flip_x`] and [`SprayOptions::flip_y`].
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.options.flip_x = flip_x;
+        self.options.flip_y = flip_y;
+        return self;
     }
 
-    if fa < 1. && fb < 1. && fc > 1. {
-        new_quad(triangle.c, triangle.a, triangle.b, fc, fa, fb, triangles);
-        return true;
+    /// See [`SprayOptions::targets`].
+    pub fn with_targets(mut self, targets: &[Entity]) -> Self {
+        self.options.targets = Some(targets.to_vec());
+        return self;
     }
 
-    return false;
-}
+    /// See [`SprayOptions::excluded`].
+    pub fn without(mut self, excluded: &[Entity]) -> Self {
+        self.options.excluded.extend_from_slice(excluded);
+        return self;
+    }
 
-fn apply_decal(
-    mesh: &Mesh,
-    mesh_transform: &Transform,
-    decal_transform: &Transform,
-    offset: f32,
-) -> Option<Mesh> {
-    let vertex_attribute = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
-    let normal_attribute = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap();
-    let indices = mesh.indices().unwrap();
+    /// See [`SprayOptions::layers`].
+    pub fn with_layers(mut self, layers: DecalLayers) -> Self {
+        self.options.layers = layers;
+        return self;
+    }
 
-    let VertexAttributeValues::Float32x3(vertex_attribute) = vertex_attribute else {
-        panic!("Unexpected vertex format, expected Float32x3.");
-    };
+    /// See [`SprayOptions::group`].
+    pub fn with_group(mut self, group: DecalGroup) -> Self {
+        self.options.group = group;
+        return self;
+    }
 
-    let VertexAttributeValues::Float32x3(normal_attribute) = normal_attribute else {
-        panic!("Unexpected normal format, expected Float32x3.");
-    };
+    /// See [`SprayOptions::max_triangles`].
+    pub fn with_max_triangles(mut self, max_triangles: usize) -> Self {
+        self.options.max_triangles = Some(max_triangles);
+        return self;
+    }
 
-    let Indices::U16(indices) = indices else {
-        panic!("Unexpected indices format, expected U16.");
-    };
-    
-    let mut axii = [
-        Vec3::X,
-        Vec3::Y,
-        Vec3::Z,
-        Vec3::NEG_X,
-        Vec3::NEG_Y,
-        Vec3::NEG_Z,
-    ];
+    /// See [`SprayOptions::edge_fade`].
+    pub fn with_edge_fade(mut self, edge_fade: f32) -> Self {
+        self.options.edge_fade = edge_fade;
+        return self;
+    }
 
-    let decal_proj = decal_transform.compute_matrix().inverse();
-    let inv_decal_transform = Transform::from_matrix(decal_proj);
+    /// See [`SprayOptions::depth_fade`].
+    pub fn with_depth_fade(mut self, depth_fade: Range<f32>) -> Self {
+        self.options.depth_fade = Some(depth_fade);
+        return self;
+    }
 
-    let mut new_triangles = Vec::with_capacity(1024);
+    /// See [`SprayOptions::angle_fade`].
+    pub fn with_angle_fade(mut self, angle_fade: Range<f32>) -> Self {
+        self.options.angle_fade = Some(angle_fade);
+        return self;
+    }
 
-    for triangle in indices.chunks(3) {
-        let vA = Vec3::from(vertex_attribute[triangle[0] as usize]) + Vec3::from(normal_attribute[triangle[0] as usize]) * offset;
-        let vB = Vec3::from(vertex_attribute[triangle[1] as usize]) + Vec3::from(normal_attribute[triangle[1] as usize]) * offset;
-        let vC = Vec3::from(vertex_attribute[triangle[2] as usize]) + Vec3::from(normal_attribute[triangle[2] as usize]) * offset;
+    /// See [`SprayOptions::projector_normals`].
+    pub fn with_projector_normals(mut self) -> Self {
+        self.options.projector_normals = true;
+        return self;
+    }
 
-        let pA = decal_proj.transform_point3(mesh_transform.transform_point(vA));
-        let pB = decal_proj.transform_point3(mesh_transform.transform_point(vB));
-        let pC = decal_proj.transform_point3(mesh_transform.transform_point(vC));
-        
+    /// See [`SprayOptions::cast_shadows`] and [`SprayOptions::receive_shadows`].
+    pub fn with_shadows(mut self, cast_shadows: bool, receive_shadows: bool) -> Self {
+        self.options.cast_shadows = Some(cast_shadows);
+        self.options.receive_shadows = Some(receive_shadows);
+        return self;
+    }
 
-        let mut removed = false;
-        for axis in axii.iter() {
-            let fA = pA.dot(*axis);
-            let fB = pB.dot(*axis);
-            let fC = pC.dot(*axis);
+    /// See [`SprayOptions::tint`].
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.options.tint = Some(tint);
+        return self;
+    }
 
-            if fA > 1. && fB > 1. && fC > 1. {
-                removed = true;
-                break;
-            }
-        }
-        if removed {
-            continue;
-        }
+    /// See [`SprayOptions::lifetime`].
+    pub fn with_lifetime(mut self, duration: Duration, fade: Duration) -> Self {
+        self.options.lifetime = Some(DecalLifetime { duration, fade });
+        return self;
+    }
 
-        let nA = inv_decal_transform.rotation * (mesh_transform.rotation * Vec3::from(normal_attribute[triangle[0] as usize]));
-        let nB = inv_decal_transform.rotation * (mesh_transform.rotation * Vec3::from(normal_attribute[triangle[1] as usize]));
-        let nC = inv_decal_transform.rotation * (mesh_transform.rotation * Vec3::from(normal_attribute[triangle[2] as usize])); 
+    /// See [`SprayOptions::budget_exempt`].
+    pub fn budget_exempt(mut self) -> Self {
+        self.options.budget_exempt = true;
+        return self;
+    }
 
-        // Set this to false to apply the decal to both sides of the mesh.
+    /// See [`SprayOptions::two_sided`].
+    pub fn two_sided(mut self) -> Self {
+        self.options.two_sided = true;
+        return self;
+    }
 
-        if DECAL_REMOVE_BACKFACES {
-            let normal = nA + nB + nC;
-            if normal.z < 0. {
-                continue;
-            }
-        }
+    /// See [`SprayOptions::attach_to_joint`].
+    pub fn attach_to_joint(mut self) -> Self {
+        self.options.attach_to_joint = true;
+        return self;
+    }
 
+    /// See [`SprayOptions::occlusion`].
+    pub fn with_occlusion(mut self, occlusion: DecalOcclusion) -> Self {
+        self.options.occlusion = Some(occlusion);
+        return self;
+    }
 
-        let A = Vertex { position: pA, normal: nA, uv: Vec2::ZERO };
-        let B = Vertex { position: pB, normal: nB, uv: Vec2::ZERO };
-        let C = Vertex { position: pC, normal: nC, uv: Vec2::ZERO };
+    /// See [`SprayOptions::connected`], with an optional [`SprayOptions::anchor`].
+    pub fn connected(mut self, anchor: Option<Vec3>) -> Self {
+        self.options.connected = true;
+        self.options.anchor = anchor;
+        return self;
+    }
 
-        if is_inside_unit_cube(A.position) && is_inside_unit_cube(B.position) && is_inside_unit_cube(C.position) {
-            new_triangles.push(Triangle {a: A, b: B, c: C});
-            continue;
-        }
+    /// See [`SprayOptions::asynchronous`].
+    pub fn asynchronous(mut self) -> Self {
+        self.options.asynchronous = true;
+        return self;
+    }
 
-        let mut input_triangles = Vec::with_capacity(1024);
-        let mut output_triangles = Vec::with_capacity(1024);
-        input_triangles.push(Triangle {a: A, b: B, c: C});
+    /// Only apply the decal to entities with the component `C`.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// // Paint the other team only
+    /// SprayDecal::new(red_paint.clone(), my_transform)
+    ///     .with_component::<BlueTeam>()
+    ///     .spray(&mut commands);
+    /// ```
+    pub fn with_component<C: Component>(mut self) -> Self {
+        self.options.with_components.push(TypeId::of::<C>());
+        return self;
+    }
 
-        for axis in axii.iter() {
-            while input_triangles.len() > 0 {
-                let mut triangle = input_triangles.pop().unwrap();
-                if !slice(&mut triangle, *axis, &mut output_triangles) {
-                    output_triangles.push(triangle);
-                }
-            }
-            if axis != axii.last().unwrap() {
-                let tmp = input_triangles;
-                input_triangles = output_triangles;
-                output_triangles = tmp;
-            }
-        }
+    /// Never apply the decal to entities with the component `C`.
+    pub fn without_component<C: Component>(mut self) -> Self {
+        self.options.without_components.push(TypeId::of::<C>());
+        return self;
+    }
 
-        while output_triangles.len() > 0 {
-            new_triangles.push(output_triangles.pop().unwrap());
-        }
-  
+    /// Replace all options at once.
+    pub fn with_options(mut self, options: SprayOptions) -> Self {
+        self.options = options;
+        return self;
     }
 
-    let mut positions = Vec::with_capacity(4096);
-    let mut normals = Vec::with_capacity(4096);
-    let mut uvs = Vec::with_capacity(4096);
-    let mut indices = Vec::with_capacity(4096);
-    let mut index: u16 = 0;
+    /// Randomizes the projector with `jitter`, so repeated sprays don't look identical.
+    /// The same `seed` always results in the same spray, e.g. a hash of the shot for replays.
+    /// Applied right away, so call it after setting the transform.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let jitter = SprayJitter { rotation: 0. ..TAU, scale: 0.8..1.2, flip_chance: 0.5 };
+    /// SprayDecal::new(bullet_hole.clone(), projector)
+    ///     .with_jitter(&jitter, shot_index)
+    ///     .spray(&mut commands);
+    /// ```
+    pub fn with_jitter(mut self, jitter: &SprayJitter, seed: u64) -> Self {
+        let mut rng = JitterRng(seed);
+        let angle = rng.range(&jitter.rotation);
+        let scale = rng.range(&jitter.scale);
+        let flip = rng.next() < jitter.flip_chance;
 
-    for triangle in new_triangles.iter() {
-        positions.push(triangle.a.position);
-        positions.push(triangle.b.position);
-        positions.push(triangle.c.position);
-        normals.push(triangle.a.normal);
-        normals.push(triangle.b.normal);
-        normals.push(triangle.c.normal);
-        indices.push(index);
-        index += 1;
-        indices.push(index);
-        index += 1;
-        indices.push(index);
-        index += 1;
-    }
-
-    if positions.len() == 0 {
-        return None
+        // Rotated around and scaled across the projection axis, the depth stays the same
+        self.transform.rotation *= Quat::from_rotation_z(angle);
+        self.transform.scale *= Vec3::new(scale, scale, 1.);
+        self.options.flip_x ^= flip;
+        return self;
     }
 
-    for i in 0..positions.len() {
-        uvs.push(Vec2::new(positions[i].x*0.5+0.5, positions[i].y*0.5+0.5));
+    /// Queue the spray, it's applied once the [`DecalPlugin`] systems run.
+    pub fn spray(self, commands: &mut Commands) -> SprayId {
+        return SprayId(commands.spray(self).id());
     }
-
-    let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_POSITION,
-            positions
-        )
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_UV_0,
-            uvs,
-        )
-        .with_inserted_attribute(
-            Mesh::ATTRIBUTE_NORMAL,
-            normals,
-        )
-        .with_inserted_indices(Indices::U16(indices));
-    return Some(mesh)
 }
 
+/// Extension trait to spray decals directly from [`Commands`].
+///
+/// # Example:
+///
+/// ```
+/// commands
+///     .spray_decal(my_material.clone(), my_transform)
+///     // The returned entity is the pending spray, it's despawned once the decal is applied
+///     .insert(MySprayTag);
+/// ```
+pub trait DecalCommandsExt {
+    /// See [`spray_decal`].
+    fn spray_decal<M: Material>(&mut self, material: Handle<M>, transform: Transform) -> EntityCommands<'_>;
 
-fn decal_system(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut decals: Query<(Entity, &Transform, &ApplyingDecal)>, 
-    mut models: Query<(Entity, &Handle<Mesh>, &Transform, &GlobalTransform, &mut Decalable)>,
-) {
-    for (decal_entity, transform,  decal) in decals.iter_mut() {
-        for (model_entity, model_mesh, model_transform, global_transform, mut decalable) in models.iter_mut() {
-            if decalable.0 >= DECAL_MAX_PER_ENTTIY {
-                continue;
-            }
-
-            let mesh_transform = Transform::from(global_transform.mul_transform(*model_transform));
+    /// See [`spray_decal_with_options`].
+    fn spray_decal_with_options<M: Material>(
+        &mut self,
+        material: Handle<M>,
+        transform: Transform,
+        options: SprayOptions,
+    ) -> EntityCommands<'_>;
 
-            if let Some(mesh) = apply_decal(meshes.get(model_mesh).unwrap(), &mesh_transform, transform, (decalable.0 + 1) as f32 * DECAL_EPSILON) {
+    /// See [`SprayDecal::spray`].
+    fn spray<M: Material>(&mut self, spray: SprayDecal<M>) -> EntityCommands<'_>;
+}
 
-                let applied_decal = commands.spawn((
-                    PbrBundle {
-                        mesh: meshes.add(mesh).clone(),
-                        material: decal.0.clone(),
-                        // Inverse matrices to make it work with Bevy's transform propagation
-                        transform: Transform::from_matrix(mesh_transform.compute_matrix().inverse()).mul_transform(*transform), 
-                        ..default()
-                    },
-                    NotShadowCaster,    // For extra performance
-                    Decal,
-                )).id();
+impl DecalCommandsExt for Commands<'_, '_> {
+    fn spray_decal<M: Material>(&mut self, material: Handle<M>, transform: Transform) -> EntityCommands<'_> {
+        return self.spray_decal_with_options(material, transform, SprayOptions::default());
+    }
 
-                commands.entity(model_entity).add_child(applied_decal);
-                decalable.0 += 1;
-            }
-        }
+    fn spray_decal_with_options<M: Material>(
+        &mut self,
+        material: Handle<M>,
+        transform: Transform,
+        options: SprayOptions,
+    ) -> EntityCommands<'_> {
+        return self.spray(SprayDecal { material, transform, options });
+    }
 
-        commands.entity(decal_entity).despawn();
+    fn spray<M: Material>(&mut self, spray: SprayDecal<M>) -> EntityCommands<'_> {
+        // This entity will be removed once the decals has been applied
+        return self.spawn(ApplyingDecal(spray));
     }
+}
 
+/// Sprays many decals at once, for example all pellets of a shotgun.
+///
+/// # Example:
+///
+/// ```
+/// let pellets = (0..12).map(|_| (bullet_hole.clone(), random_pellet_transform()));
+/// spray_decals(&mut commands, pellets);
+/// ```
+///
+/// # Note
+///
+/// All sprays applied in the same frame are processed together, target by target,
+/// so each target is prepared once no matter how many sprays reach it. This is
+/// the same as calling [`spray_decal`] for every item.
+pub fn spray_decals<M: Material>(
+    commands: &mut Commands,
+    sprays: impl IntoIterator<Item = (Handle<M>, Transform)>,
+) -> Vec<SprayId> {
+    return sprays.into_iter()
+        .map(|(material, transform)| spray_decal(commands, material, transform))
+        .collect();
+}
+
+/// Same as [`spray_decal`], but only applied to entities with the component `C`.
+///
+/// # Example:
+///
+/// ```
+/// spray_decal_filtered::<EnemyTeam>(&mut commands, blood.clone(), my_transform);
+/// ```
+pub fn spray_decal_filtered<C: Component>(
+    commands: &mut Commands,
+    material: Handle<impl Material>,
+    transform: Transform,
+) -> SprayId {
+    return SprayDecal::new(material, transform).with_component::<C>().spray(commands);
+}
+
+/// Spray a decal with an explicit size and projection depth, instead of
+/// encoding both in the scale of a transform.
+///
+/// # Example:
+///
+/// ```
+/// spray_decal_sized(
+///     &mut commands,
+///     my_material.clone(),
+///     // Spray from here...
+///     hit_point,
+///     // ...towards the forward direction of this rotation
+///     Quat::from_rotation_x(-FRAC_PI_2),
+///     // 1 by 1 meter decal
+///     Vec2::ONE,
+///     // Reaching 0.1 behind the hit point and 5 in front of it
+///     -0.1..5.,
+/// );
+/// ```
+///
+/// # Note
+///
+/// The depth range is measured along the projection direction from
+/// `translation`, negative values reach behind it.
+pub fn spray_decal_sized<M: Material>(
+    commands: &mut Commands,
+    material: Handle<M>,
+    translation: Vec3,
+    rotation: Quat,
+    size: Vec2,
+    depth_range: Range<f32>,
+) -> SprayId {
+    return spray_decal(commands, material, projector_transform(translation, rotation, size, depth_range));
+}
+
+/// Sprays a decal right away and returns the spawned decal entities, for exclusive
+/// systems that need the result within the same system, like level editors.
+/// Requires a [`DecalPlugin`] to be added.
+///
+/// # Example:
+///
+/// ```
+/// fn place_decal(world: &mut World) {
+///     let decals = spray_decal_immediate(world, my_material.clone(), my_transform, SprayOptions::default());
+///     world.entity_mut(decals[0]).insert(EditorSelection);
+/// }
+/// ```
+///
+/// # Note
+///
+/// Bypasses the queue of [`DecalSet::Apply`], so sprays queued with commands or
+/// [`SprayDecalEvent`] before this call are applied after it. [`DecalAppliedEvent`],
+/// [`DecalFailedEvent`] and [`OnDecalApplied`] are still sent as usual. Sprays can't
+/// wait for the layout of their [`SprayOptions::atlas`] here, so they fail if it isn't loaded.
+pub fn spray_decal_immediate<M: Material>(
+    world: &mut World,
+    material: Handle<M>,
+    transform: Transform,
+    options: SprayOptions,
+) -> Vec<Entity> {
+    // Like events, the spray only needs an entity for its SprayId
+    let spray_entity = world.spawn_empty().id();
+
+    return apply_spray_immediate(world, spray_entity, &SprayDecal::new(material, transform).with_options(options));
+}
+
+// Applies a spray right away, see spray_decal_immediate. Despawns the spray entity afterwards.
+fn apply_spray_immediate<M: Material>(world: &mut World, spray_entity: Entity, spray: &SprayDecal<M>) -> Vec<Entity> {
+    let settings = world.get_resource::<DecalSettings>().cloned().unwrap_or_default().clamped();
+
+    let mut resolved = None;
+    if spray.options.atlas.is_some() {
+        let mut atlas_spray = spray.clone();
+        let layout = resolve_atlas(&mut atlas_spray.options, world.get_resource::<Assets<TextureAtlasLayout>>(), world.get_resource::<AssetServer>());
+        if layout != AtlasLayoutState::Ready {
+            let mut state: SystemState<DecalApplication> = SystemState::new(world);
+            state.get_mut(world).fail(spray_entity, DecalFailureReason::AtlasUnavailable);
+            state.apply(world);
+            world.despawn(spray_entity);
+            return Vec::new();
+        }
+        resolved = Some(atlas_spray);
+    }
+    let spray = resolved.as_ref().unwrap_or(spray);
+
+    let mut state: SystemState<DecalApplication> = SystemState::new(world);
+    let decals = state.get_mut(world).apply_spray(spray_entity, spray, &settings);
+    state.apply(world);
+
+    return decals;
+}
+
+/// Builds the projector transform expected by [`spray_decal`], for a decal of
+/// `size` (width and height) projected along the forward direction of `rotation`,
+/// covering everything within `depth_range` of `translation`.
+pub fn projector_transform(translation: Vec3, rotation: Quat, size: Vec2, depth_range: Range<f32>) -> Transform {
+    // The projection volume is a unit cube in projector space, spanning -1 to 1 on each axis
+    let center = (depth_range.start + depth_range.end) * 0.5;
+    let half_depth = (depth_range.end - depth_range.start).abs() * 0.5;
+
+    return Transform {
+        translation: translation + rotation * Vec3::NEG_Z * center,
+        rotation,
+        scale: (size * 0.5).extend(half_depth),
+    };
+}
+
+/// Builds the projector of a [`DecalShape::Perspective`] spray from `translation` along the forward
+/// direction of `rotation`, like a slide projector. `fov` is the vertical field of view in radians,
+/// `aspect` the width over the height, and surfaces within `depth_range` of `translation` get the decal.
+///
+/// # Example:
+///
+/// ```
+/// let (projector, shape) = perspective_projector(gun.translation, gun.rotation, 30_f32.to_radians(), 1., 0.5..20.);
+/// SprayDecal::new(slide.clone(), projector)
+///     .with_shape(shape)
+///     .spray(&mut commands);
+/// ```
+pub fn perspective_projector(translation: Vec3, rotation: Quat, fov: f32, aspect: f32, depth_range: Range<f32>) -> (Transform, DecalShape) {
+    let (near, far) = (depth_range.start.max(0.), depth_range.end.max(0.));
+    let half_height = far * (fov * 0.5).tan();
+    let size = Vec2::new(half_height * aspect, half_height) * 2.;
+    let near_scale = if far > 0. { near / far } else { 1. };
+
+    return (projector_transform(translation, rotation, size, near..far), DecalShape::Perspective { near_scale });
+}
+
+/// Builds the projector transform for a decal of `size` on a surface hit at `point`
+/// with the surface `normal`, for example from a raycast.
+///
+/// The projection points into the surface and reaches `depth / 2` in front of and
+/// behind the hit point. The top of the decal faces world up, or world -Z when the
+/// normal is (nearly) vertical, and `roll` rotates the decal counter-clockwise
+/// around the normal in radians.
+///
+/// # Example:
+///
+/// ```
+/// if let Some((_, hit)) = rapier_context.cast_ray_and_get_normal(origin, direction, 50., true, QueryFilter::default()) {
+///     spray_decal(&mut commands, my_material.clone(), decal_transform_from_hit(hit.point, hit.normal, Vec2::ONE, 0.2, 0.));
+/// }
+/// ```
+pub fn decal_transform_from_hit(point: Vec3, normal: Vec3, size: Vec2, depth: f32, roll: f32) -> Transform {
+    let normal = normal.try_normalize().unwrap_or(Vec3::Y);
+    // Looking straight up or down leaves the up direction undefined
+    let up = if normal.dot(Vec3::Y).abs() > 0.999 { Vec3::NEG_Z } else { Vec3::Y };
+    let rotation = Transform::IDENTITY.looking_to(-normal, up).rotation * Quat::from_rotation_z(roll);
+    let half_depth = depth.abs() * 0.5;
+
+    return projector_transform(point, rotation, size, -half_depth..half_depth);
+}
+
+// Ray through a window cursor position, accounting for the viewport of the camera
+fn cursor_ray(camera: &Camera, camera_transform: &GlobalTransform, cursor: Vec2) -> Option<Ray3d> {
+    let viewport = camera.logical_viewport_rect()?;
+    if !viewport.contains(cursor) {
+        return None;
+    }
+    return camera.viewport_to_world(camera_transform, cursor - viewport.min).ok();
+}
+
+// Keeps the decal upright relative to the camera
+fn cursor_rotation(ray: Ray3d, camera_transform: &GlobalTransform) -> Quat {
+    return Transform::IDENTITY.looking_to(ray.direction, camera_transform.up()).rotation;
+}
+
+/// Options for a single spray, see [`spray_decal_with_options`].
+#[derive(Reflect, Clone, Default)]
+#[reflect(Default)]
+pub struct SprayOptions {
+    /// Offset of the decal from the surface in world units, to prevent Z-fighting.
+    /// Multiplied by the stacking layer of the decal on the target, so stacked
+    /// decals don't fight each other. `None` uses [`DecalSettings::offset`].
+    pub offset: Option<f32>,
+    /// Volume of the projector that gets the decal, and how its texture is mapped
+    /// onto the surfaces inside, see [`DecalShape`].
+    pub shape: DecalShape,
+    /// Counter-clockwise rotation of the decal texture in radians, around the
+    /// center of the projection. Unlike rotating the transform, this doesn't
+    /// change which geometry is covered by the projection.
+    pub uv_rotation: f32,
+    /// Sub-rectangle of the texture the decal samples from, e.g. one cell of
+    /// a texture atlas. Applied after the UV rotation. `None` uses the whole texture.
+    /// Keep some padding between atlas cells, so the alpha mask doesn't bleed.
+    pub uv_rect: Option<Rect>,
+    /// Cell of a texture atlas the decal samples from, instead of working out
+    /// [`SprayOptions::uv_rect`] by hand, which becomes a sub-rectangle of the cell then.
+    /// Sprays wait for the layout to be loaded, and fail with [`DecalFailureReason::AtlasUnavailable`]
+    /// if it fails to load or has no such cell. Resolved into [`SprayOptions::uv_rect`] when
+    /// applied, so [`DecalRecord`]s keep the cell even for random ones.
+    pub atlas: Option<DecalAtlas>,
+    /// Mirror the decal texture horizontally, e.g. for the left foot of a footprint.
+    pub flip_x: bool,
+    /// Mirror the decal texture vertically.
+    pub flip_y: bool,
+    /// Also spray the sides of surfaces facing away from the projector, e.g.
+    /// to coat both sides of thin fences and leaves. Those decals keep the
+    /// winding and normals of the surface they are on, so they are lit like
+    /// the surface itself. `None` uses [`DecalSettings::remove_backfaces`].
+    pub backfaces: Option<bool>,
+    /// Largest angle in radians between a surface and the projector it still gets
+    /// sprayed at, so steep surfaces don't get stretched streaks. `None` uses
+    /// [`DecalSettings::max_angle`].
+    pub max_angle: Option<f32>,
+    /// Only apply the decal to these entities, instead of every [`Decalable`]
+    /// inside the projection. Targets without [`Decalable`] are skipped.
+    pub targets: Option<Vec<Entity>>,
+    /// Never apply the decal to these entities, even if they are inside the projection.
+    pub excluded: Vec<Entity>,
+    /// Only apply the decal to entities on these layers, see [`DecalLayers`].
+    pub layers: DecalLayers,
+    /// Group of the resulting decals, see [`DecalGroup`].
+    pub group: DecalGroup,
+    /// Compute the decal on the [`AsyncComputeTaskPool`] instead of blocking the
+    /// frame, e.g. for big decals on dense meshes. The decal shows up one or more
+    /// frames later, attached where the target was when it was sprayed. Without
+    /// threads, e.g. on wasm32, see [`DecalSettings::frame_sliced_async`].
+    ///
+    /// # Note
+    ///
+    /// The decal takes its slot on the target right away, so it counts toward the
+    /// limit and may evict older decals even if the projection ends up empty.
+    /// Until it's done, the entity returned by [`spray_decal_immediate`] and
+    /// [`Decals`] is an empty placeholder. Targets despawned in the
+    /// meantime simply don't get the decal, and no [`DecalFailedEvent`] is sent then.
+    pub asynchronous: bool,
+    /// Maximum number of triangles of each resulting decal, counted after clipping.
+    /// `None` uses [`DecalSettings::max_triangles_per_decal`].
+    pub max_triangles: Option<usize>,
+    /// Width of a fade toward the sides of the projector, or the surface of a curved
+    /// [`DecalShape`], written to the alpha of `ATTRIBUTE_COLOR`, as a fraction of the
+    /// distance from the center to the sides.
+    /// Vertices on the sides get an alpha of 0, so any material multiplying vertex
+    /// colors with a blended or masked alpha mode loses the hard edge. 0 disables it.
+    ///
+    /// # Note
+    ///
+    /// The alpha is interpolated between vertices, so a large triangle covering the
+    /// whole decal fades across its full width instead.
+    pub edge_fade: f32,
+    /// Fade along the projection, written to the alpha of `ATTRIBUTE_COLOR` like
+    /// [`SprayOptions::edge_fade`], e.g. so a spray through a railing barely marks the
+    /// floor far behind it. Given as fractions of the projection depth, 0 at the near
+    /// end and 1 at the far end: fully opaque before the start of the range and
+    /// transparent after its end. `None` disables it.
+    ///
+    /// # Note
+    ///
+    /// The depth is measured after the decal is offset from the surface, which is
+    /// negligible unless the offset is large compared to the depth. Backfaces fade
+    /// by their own depth as well.
+    pub depth_fade: Option<Range<f32>>,
+    /// Fade as surfaces turn away from the projector, written to the alpha of
+    /// `ATTRIBUTE_COLOR` like [`SprayOptions::edge_fade`], e.g. toward the silhouette
+    /// of a sphere. Angles in radians between the vertex normal and the direction
+    /// back to the projector: fully opaque below the start of the range and transparent
+    /// beyond its end. Kept backfaces are measured against the opposite direction.
+    /// Unlike [`SprayOptions::max_angle`] there's no seam where the surface crosses it.
+    /// `None` disables it.
+    pub angle_fade: Option<Range<f32>>,
+    /// Light the decal as if it faced the projector, e.g. so flat stickers shade evenly
+    /// on low-poly surfaces, instead of using the interpolated normals of the surface.
+    /// Kept backfaces face away from the projector. Which triangles are sprayed still
+    /// depends on the normals of the surface.
+    pub projector_normals: bool,
+    /// Decorate both sides of the surfaces sprayed, e.g. a flag or a single sided fence
+    /// rendered without backface culling. Every triangle gets a second decal triangle
+    /// with flipped winding and normals, offset to the other side, so both are lit like
+    /// the side they are on. Backfaces are always sprayed then, see [`SprayOptions::backfaces`].
+    ///
+    /// # Note
+    ///
+    /// Closed meshes don't need this, their back side is made of separate triangles.
+    /// [`SprayOptions::max_triangles`] counts the triangles of one side.
+    pub two_sided: bool,
+    /// Only spray the surfaces nearest to the projector, so a spray at a thin wall
+    /// doesn't bleed through to the room behind it. `None` sprays everything inside
+    /// the projection.
+    pub occlusion: Option<DecalOcclusion>,
+    /// Only spray the surface connected to [`SprayOptions::anchor`], e.g. so a poster on a
+    /// pillar leaves alone the crate poking into the projection. Only the target nearest
+    /// to the anchor is sprayed, and only the triangles connected to its triangle nearest
+    /// to the anchor, through shared vertices and edges.
+    pub connected: bool,
+    /// World space point picking the surface for [`SprayOptions::connected`]. `None` uses
+    /// the first surface along the center line of the projector, or the surface nearest
+    /// to the center of the projection if the line misses everything.
+    pub anchor: Option<Vec3>,
+    /// Whether the decals cast shadows, e.g. for thick debris. `None` uses
+    /// [`DecalSettings::cast_shadows`].
+    pub cast_shadows: Option<bool>,
+    /// Whether the decals receive shadows, unlit graffiti may not want to.
+    /// `None` uses [`DecalSettings::receive_shadows`].
+    pub receive_shadows: Option<bool>,
+    /// Color the material of the decal is tinted with, e.g. team colored paint from a
+    /// single white splatter. Sprays of the same material and tint share one clone of
+    /// the material, which is freed once no decal uses it anymore. Colors are quantized
+    /// to 8 bit sRGB first, so a continuous range of colors still shares clones. Needs
+    /// a material implementing [`DecalTint`], see [`DecalPlugin::with_material_tint`].
+    /// `None` uses the material as is.
+    pub tint: Option<Color>,
+    /// Attach decals on skinned targets to the joint influencing the covered vertices most,
+    /// instead of skinning them like the target. The decal rides the bone rigidly, which is
+    /// cheaper and looks fine for small hit decals on limbs, but it doesn't bend with the
+    /// surface across joints. Decals without a joint to attach to, e.g. on morphed targets
+    /// or before the skin is loaded, are skinned as usual.
+    pub attach_to_joint: bool,
+    /// Despawn the decals after a while, fading them out first, see [`DecalLifetime`].
+    /// `None` keeps them until they're evicted or removed.
+    pub lifetime: Option<DecalLifetime>,
+    /// Never evict the decals to stay within the global budget, e.g. for graffiti the story
+    /// depends on. They still count toward it, see [`DecalSettings::max_total_decals`].
+    pub budget_exempt: bool,
+    /// Only apply the decal to entities with all of these components,
+    /// see [`SprayDecal::with_component`].
+    #[reflect(ignore)]
+    pub with_components: Vec<TypeId>,
+    /// Never apply the decal to entities with any of these components,
+    /// see [`SprayDecal::without_component`].
+    #[reflect(ignore)]
+    pub without_components: Vec<TypeId>,
+}
+
+/// Volume a spray decorates within the unit cube of its projector, see [`SprayOptions::shape`].
+#[derive(Reflect, Clone, Copy, PartialEq, Debug)]
+#[reflect(Default)]
+pub enum DecalShape {
+    /// The whole projector box, with the texture projected along its local -Z axis.
+    Box,
+    /// The sphere, or ellipsoid for non-uniform scales, inscribed in the projector box,
+    /// e.g. for explosion scorch marks reaching every surface around the center. Place the
+    /// projector at the center, scaled by the radius. Backfaces and angles are measured
+    /// against the direction from the center, and the texture is wrapped around it
+    /// with an octahedral mapping: the center of the texture faces the projector's local +Z,
+    /// the opposite direction is folded into the four corners, and the midpoints of the
+    /// left, right, bottom and top edges face -X, +X, -Y and +Y.
+    ///
+    /// # Note
+    ///
+    /// Triangles are subdivided so the mapping and outline follow the sphere closely, and
+    /// vertices beyond the sphere get an alpha of 0 in `ATTRIBUTE_COLOR` instead of an exact
+    /// cut, see [`SprayOptions::edge_fade`]. [`SprayOptions::depth_fade`] and
+    /// [`SprayOptions::occlusion`] still work along the local -Z axis.
+    Sphere,
+    /// The cylinder, or elliptic cylinder, inscribed in the projector box around its local
+    /// Y axis, e.g. for labels wrapped around barrels or stencils on pipes. Only the part
+    /// within `arc` radians of the projector's local +Z is sprayed, centered on it, and the
+    /// texture spans exactly that part, so a 90° label doesn't repeat. U grows with the angle
+    /// toward +X, so the texture reads left to right when looking at the cylinder along
+    /// -Z, and V follows the height like [`DecalShape::Box`]. Backfaces and angles are measured
+    /// against the direction away from the axis, and an `arc` of `TAU` wraps all the way
+    /// around with the seam at -Z, see [`DecalShape::CYLINDER`].
+    ///
+    /// # Note
+    ///
+    /// Triangles crossing the seam are split along it. Like [`DecalShape::Sphere`], triangles
+    /// are subdivided and vertices beyond the radius or the arc fade out instead of getting
+    /// cut exactly, [`SprayOptions::edge_fade`] only fades toward the ends and the sides of the arc.
+    Cylinder { arc: f32 },
+    /// The frustum of a perspective projection inscribed in the projector box, like a slide
+    /// projector: the far end spans the whole box and the near end `near_scale` of it, so
+    /// the texture grows with the distance from the apex in front of the near end. Triangles
+    /// are clipped to the frustum exactly, also where they cross the near end, and the texture
+    /// follows the perspective like [`DecalShape::Box`] does at the far end. Backfaces and angles
+    /// are measured against the direction from the apex. See [`perspective_projector`]
+    /// to build the projector from a field of view, and [`SprayOptions::depth_fade`] to dim
+    /// the decal with the distance.
+    ///
+    /// # Note
+    ///
+    /// Triangles are subdivided so the texture follows the perspective closely.
+    /// [`SprayOptions::occlusion`] still works parallel to the local -Z axis.
+    Perspective { near_scale: f32 },
+}
+
+impl Default for DecalShape {
+    fn default() -> Self {
+        return DecalShape::Box;
+    }
+}
+
+impl DecalShape {
+    /// [`DecalShape::Cylinder`] wrapping all the way around.
+    pub const CYLINDER: DecalShape = DecalShape::Cylinder { arc: std::f32::consts::TAU };
+
+    // Shapes mapping the texture non-linearly, whose triangles are subdivided to follow it
+    fn is_curved(&self) -> bool {
+        return *self != DecalShape::Box;
+    }
+
+    // Planes triangles are clipped against, as normals n of the planes p · n = 1
+    fn clip_planes(&self) -> [Vec3; 6] {
+        return match self {
+            DecalShape::Perspective { near_scale } => {
+                // The sides run from near_scale at z = 1 to 1 at z = -1, so x = ((1 + near_scale) + (near_scale - 1) z) / 2
+                let (half_sum, half_difference) = ((1. + near_scale) * 0.5, (near_scale - 1.) * 0.5);
+                let side = |axis: Vec3| (axis - Vec3::Z * half_difference) / half_sum;
+                [side(Vec3::X), side(Vec3::Y), Vec3::Z, side(Vec3::NEG_X), side(Vec3::NEG_Y), Vec3::NEG_Z]
+            }
+            _ => [Vec3::X, Vec3::Y, Vec3::Z, Vec3::NEG_X, Vec3::NEG_Y, Vec3::NEG_Z],
+        };
+    }
+
+    // Half size of the frustum of a perspective shape at a projector space depth
+    fn perspective_scale(near_scale: f32, z: f32) -> f32 {
+        return ((1. + near_scale) + (near_scale - 1.) * z) * 0.5;
+    }
+
+    // Whether a projector space triangle reaches into the shape, the planes are left to clipping
+    fn intersects(&self, a: Vec3, b: Vec3, c: Vec3) -> bool {
+        return match self {
+            DecalShape::Box | DecalShape::Perspective { .. } => true,
+            DecalShape::Sphere => closest_point_on_triangle(Vec3::ZERO, [a, b, c]).length_squared() <= 1.,
+            DecalShape::Cylinder { .. } => {
+                // Looking down the axis, an edge comes within the radius or the triangle surrounds the axis
+                let [a, b, c] = [a, b, c].map(|p| Vec2::new(p.x, p.z));
+                let near = |p: Vec2, q: Vec2| -> bool {
+                    let edge = q - p;
+                    let t = if edge == Vec2::ZERO { 0. } else { (-p.dot(edge) / edge.length_squared()).clamp(0., 1.) };
+                    return (p + edge * t).length_squared() <= 1.;
+                };
+                let sides = [(b - a).perp_dot(-a), (c - b).perp_dot(-b), (a - c).perp_dot(-c)];
+                near(a, b) || near(b, c) || near(c, a)
+                    || sides.iter().all(|side| *side >= 0.) || sides.iter().all(|side| *side <= 0.)
+            }
+        };
+    }
+
+    // Unit direction the spray reaches a projector space position from, reversed. In the axes
+    // of the projector without its scale, like normals divided by the projector scale.
+    fn back(&self, position: Vec3, projector_scale: Vec3) -> Vec3 {
+        // A projector mirrored along its depth sprays from the other side
+        let back = Vec3::Z * projector_scale.z.signum();
+        return match self {
+            DecalShape::Box => back,
+            DecalShape::Sphere => {
+                let direction = (position * projector_scale).normalize_or_zero();
+                if direction == Vec3::ZERO { back } else { -direction }
+            }
+            // Perpendicular to the elliptic cylinder, once unscaled
+            DecalShape::Cylinder { .. } => {
+                let direction = Vec3::new(position.x / projector_scale.x, 0., position.z / projector_scale.z).normalize_or_zero();
+                if direction == Vec3::ZERO { back } else { direction }
+            }
+            // The apex is where the sides meet, parallel sides are a box
+            DecalShape::Perspective { near_scale } if *near_scale < 1. => {
+                let apex = Vec3::Z * (1. + near_scale) / (1. - near_scale);
+                let direction = ((position - apex) * projector_scale).normalize_or_zero();
+                if direction == Vec3::ZERO { back } else { -direction }
+            }
+            DecalShape::Perspective { .. } => back,
+        };
+    }
+
+    // Distance of a projector space position to the sides of the shape, 1 at its center
+    fn border(&self, position: Vec3) -> f32 {
+        return match self {
+            DecalShape::Box | DecalShape::Perspective { .. } => 1. - self.texture_position(position, position).abs().max_element(),
+            DecalShape::Sphere => 1. - position.length(),
+            DecalShape::Cylinder { arc } => {
+                let radius = Vec2::new(position.x, position.z).length();
+                let mut border = 1. - position.y.abs();
+                if *arc < std::f32::consts::TAU {
+                    border = border.min(1. - position.x.atan2(position.z).abs() / (arc * 0.5));
+                }
+                // Only the ends and the sides of the arc fade, the surfaces are near the radius
+                if radius > 1. { border.min(1. - radius) } else { border }
+            }
+        };
+    }
+
+    // Position on the decal texture from -1 to 1, before rotating and flipping it. Vertices on a seam
+    // belong to the side of their triangle, given by its center.
+    fn texture_position(&self, position: Vec3, center: Vec3) -> Vec2 {
+        return match self {
+            DecalShape::Box => position.truncate(),
+            // Divided by the size of the frustum at the depth of the position, like clip space by w
+            DecalShape::Perspective { near_scale } => position.truncate() / DecalShape::perspective_scale(*near_scale, position.z).max(f32::EPSILON),
+            DecalShape::Sphere => {
+                let sum = position.abs().element_sum();
+                if sum == 0. {
+                    return Vec2::ZERO;
+                }
+                let octahedron = position / sum;
+                if octahedron.z >= 0. {
+                    octahedron.truncate()
+                } else {
+                    (Vec2::ONE - Vec2::new(octahedron.y.abs(), octahedron.x.abs())) * octahedron.truncate().signum()
+                }
+            }
+            DecalShape::Cylinder { arc } => {
+                let mut angle = position.x.atan2(position.z);
+                if position.x == 0. && position.z < 0. {
+                    angle = std::f32::consts::PI.copysign(center.x);
+                }
+                Vec2::new(angle / (arc * 0.5), position.y)
+            }
+        };
+    }
+}
+
+/// A cell of a [`TextureAtlasLayout`] for a spray to sample from, see [`SprayOptions::atlas`].
+#[derive(Reflect, Clone, PartialEq, Debug)]
+pub struct DecalAtlas {
+    pub layout: Handle<TextureAtlasLayout>,
+    pub index: DecalAtlasIndex,
+}
+
+/// Which cell of the layout a [`DecalAtlas`] samples from.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DecalAtlasIndex {
+    /// The cell at this index of `TextureAtlasLayout::textures`.
+    Index(usize),
+    /// Any cell of the layout, the same one for the same `seed`.
+    Random { seed: u64 },
+}
+
+// How far a spray is with the layout of its SprayOptions::atlas
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AtlasLayoutState {
+    Ready,
+    Loading,
+    Failed,
+}
+
+// Replaces SprayOptions::atlas by the UV rect of its cell, with SprayOptions::uv_rect as a sub-rectangle of it
+fn resolve_atlas(options: &mut SprayOptions, layouts: Option<&Assets<TextureAtlasLayout>>, asset_server: Option<&AssetServer>) -> AtlasLayoutState {
+    let Some(atlas) = options.atlas.as_ref() else {
+        return AtlasLayoutState::Ready;
+    };
+    let Some(layouts) = layouts else {
+        return AtlasLayoutState::Failed;
+    };
+    let Some(layout) = layouts.get(&atlas.layout) else {
+        let failed = asset_server.is_some_and(|asset_server| matches!(asset_server.get_load_state(atlas.layout.id()), Some(LoadState::Failed(_))));
+        return if failed { AtlasLayoutState::Failed } else { AtlasLayoutState::Loading };
+    };
+
+    let index = match atlas.index {
+        DecalAtlasIndex::Index(index) => index,
+        DecalAtlasIndex::Random { seed } => (JitterRng(seed).next() * layout.textures.len() as f32) as usize,
+    };
+    let Some(cell) = layout.textures.get(index) else {
+        return AtlasLayoutState::Failed;
+    };
+
+    let size = layout.size.as_vec2();
+    let cell = Rect::from_corners(cell.min.as_vec2() / size, cell.max.as_vec2() / size);
+    options.uv_rect = Some(match options.uv_rect {
+        Some(rect) => Rect::from_corners(cell.min + rect.min * cell.size(), cell.min + rect.max * cell.size()),
+        None => cell,
+    });
+    options.atlas = None;
+    return AtlasLayoutState::Ready;
+}
+
+/// Random variation of a spray, see [`SprayDecal::with_jitter`]. The default changes nothing.
+#[derive(Reflect, Clone, PartialEq, Debug)]
+#[reflect(Default)]
+pub struct SprayJitter {
+    /// Range of the rotation around the projection axis in radians.
+    pub rotation: Range<f32>,
+    /// Range of the factor the width and height of the projector are scaled by.
+    pub scale: Range<f32>,
+    /// Chance from 0 to 1 of mirroring the texture, see [`SprayOptions::flip_x`].
+    pub flip_chance: f32,
+}
+
+impl Default for SprayJitter {
+    fn default() -> Self {
+        return SprayJitter {
+            rotation: 0. ..0.,
+            scale: 1. ..1.,
+            flip_chance: 0.,
+        };
+    }
+}
+
+// SplitMix64, tiny and the same on every platform, so seeded sprays are reproducible
+struct JitterRng(u64);
+
+impl JitterRng {
+    // Uniform in [0, 1)
+    fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        return (z >> 40) as f32 / (1u64 << 24) as f32;
+    }
+
+    fn range(&mut self, range: &Range<f32>) -> f32 {
+        return range.start + (range.end - range.start) * self.next();
+    }
+}
+
+/// Approximate occlusion of a spray, see [`SprayOptions::occlusion`]. The projection
+/// is divided into a grid, and each cell only sprays the surfaces within the tolerance
+/// of the surface nearest to the projector in that cell.
+///
+/// # Note
+///
+/// Every target the spray may reach occludes in its current pose, skinned and morphed
+/// targets in their bind pose. Triangles are kept whole when any part of them is visible,
+/// so they may still reach a little behind an occluder.
+#[derive(Reflect, Clone, Copy, PartialEq, Debug)]
+#[reflect(Default)]
+pub struct DecalOcclusion {
+    /// Cells per side of the grid. Higher resolutions follow the outline of occluders
+    /// more closely, but small triangles may fall between the cell centers.
+    pub resolution: usize,
+    /// Depth in world units behind the nearest surface that still gets sprayed.
+    pub tolerance: f32,
+}
+
+impl Default for DecalOcclusion {
+    fn default() -> Self {
+        return DecalOcclusion {
+            resolution: DECAL_OCCLUSION_RESOLUTION,
+            tolerance: DECAL_OCCLUSION_TOLERANCE,
+        };
+    }
+}
+
+/// Sprays a decal when sent, as an alternative to [`spray_decal_with_options`].
+/// Both are applied by the same system with identical results. Events are
+/// applied in the order they were sent, after sprays spawned through commands.
+///
+/// # Example:
+///
+/// ```
+/// fn shoot(mut sprays: EventWriter<SprayDecalEvent>) {
+///     sprays.send(SprayDecalEvent::new(my_material.clone(), my_transform));
+/// }
+/// ```
+#[derive(Event, Clone)]
+pub struct SprayDecalEvent<M: Material = StandardMaterial> {
+    pub material: Handle<M>,
+    pub transform: Transform,
+    pub options: SprayOptions,
+}
+
+impl<M: Material> SprayDecalEvent<M> {
+    pub fn new(material: Handle<M>, transform: Transform) -> Self {
+        return SprayDecalEvent {
+            material,
+            transform,
+            options: SprayOptions::default(),
+        };
+    }
+}
+
+/// Sent for every decal mesh that was applied to an entity.
+///
+/// # Note
+///
+/// Events are sent by the system in [`DecalSet::Apply`], so systems ordered after
+/// the set read them in the same frame, other systems in the following frame.
+/// The decal entity is spawned through commands and exists once they are applied.
+#[derive(Event, Clone, Debug)]
+pub struct DecalAppliedEvent {
+    /// The spray that resulted in the decal, see [`DecalSpray`].
+    pub spray: SprayId,
+    /// The entity the decal was applied to.
+    pub target: Entity,
+    /// The spawned decal entity, a child of `target`.
+    pub decal: Entity,
+    /// Number of triangles in the decal mesh.
+    pub triangles: usize,
+    /// World space centroid of the decal surface, weighted by triangle area.
+    pub centroid: Vec3,
+}
+
+/// Triggered on the target entity whenever it receives a decal, for observers
+/// on individual entities. Sent alongside [`DecalAppliedEvent`].
+///
+/// # Example:
+///
+/// ```
+/// commands.spawn((Mesh3d(wall_mesh), MeshMaterial3d(wall_material), Decalable::default()))
+///     .observe(|trigger: Trigger<OnDecalApplied>, mut walls: Query<&mut Wall>| {
+///         walls.get_mut(trigger.entity()).unwrap().hits += 1;
+///     });
+/// ```
+#[derive(Event, Clone, Debug)]
+pub struct OnDecalApplied {
+    pub spray: SprayId,
+    pub decal: Entity,
+    pub triangles: usize,
+    pub centroid: Vec3,
+}
+
+/// Sent for every decal that reached its triangle limit, see [`SprayOptions::max_triangles`].
+/// Usually a sign of a decal way too big for the density of its target.
+#[derive(Event, Clone, Debug)]
+pub struct DecalTriangleLimitEvent {
+    pub spray: SprayId,
+    pub target: Entity,
+    /// The limit that was reached.
+    pub max_triangles: usize,
+    /// Whether the decal was kept with the triangles generated so far, or dropped.
+    pub policy: TriangleLimitPolicy,
+}
+
+/// What happens to decals reaching their triangle limit, see [`DecalSettings::triangle_limit_policy`].
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[reflect(Default)]
+pub enum TriangleLimitPolicy {
+    /// Keep the triangles generated until the limit, in index buffer order.
+    /// The decal looks cut off, but the target still gets one.
+    #[default]
+    Truncate,
+    /// Drop the decal on that target.
+    Drop,
+}
+
+/// How decals avoid Z-fighting with the surface and each other, see [`DecalSettings::offset_mode`].
+#[derive(Reflect, Clone, Copy, PartialEq, Debug)]
+#[reflect(Default)]
+pub enum DecalOffsetMode {
+    /// Move the vertices of the decal off the surface along its normals, by
+    /// [`DecalSettings::offset`] per stacking layer. Works with any material, but
+    /// the decal intersects or floats above strongly curved surfaces, and stacks
+    /// of many decals visibly lift off.
+    Geometric,
+    /// Leave the vertices on the surface and add `per_layer` times the stacking layer
+    /// to the depth bias of the material instead. Every material sprayed gets a clone per
+    /// layer it's used on, which is freed once no decal uses it anymore, so the number of
+    /// materials grows with [`DecalSettings::max_decals_per_entity`]. Needs a material
+    /// implementing [`DecalDepthBias`], see [`DecalPlugin::with_material_depth_bias`].
+    ///
+    /// # Note
+    ///
+    /// Merged decals, see [`DecalSettings::merge_decals`], use the bias of their oldest spray.
+    DepthBias { per_layer: f32 },
+}
+
+impl Default for DecalOffsetMode {
+    fn default() -> Self {
+        return DecalOffsetMode::Geometric;
+    }
+}
+
+impl DecalOffsetMode {
+    /// [`DecalOffsetMode::DepthBias`] with the default bias per layer.
+    pub const DEPTH_BIAS: DecalOffsetMode = DecalOffsetMode::DepthBias { per_layer: DECAL_DEPTH_BIAS };
+}
+
+/// Materials that can be tinted per spray, for [`SprayOptions::tint`].
+pub trait DecalTint: Material {
+    /// A copy of the material with `tint` as its color.
+    fn with_tint(&self, tint: Color) -> Self;
+}
+
+impl DecalTint for StandardMaterial {
+    fn with_tint(&self, tint: Color) -> Self {
+        return StandardMaterial { base_color: tint, ..self.clone() };
+    }
+}
+
+impl<E: MaterialExtension + Clone> DecalTint for ExtendedMaterial<StandardMaterial, E> {
+    fn with_tint(&self, tint: Color) -> Self {
+        return ExtendedMaterial { base: self.base.with_tint(tint), extension: self.extension.clone() };
+    }
+}
+
+/// Materials whose depth bias can be raised per stacking layer, for [`DecalOffsetMode::DepthBias`].
+pub trait DecalDepthBias: Material {
+    /// A copy of the material with `depth_bias` added to its own bias.
+    fn with_depth_bias(&self, depth_bias: f32) -> Self;
+}
+
+impl DecalDepthBias for StandardMaterial {
+    fn with_depth_bias(&self, depth_bias: f32) -> Self {
+        return StandardMaterial { depth_bias: self.depth_bias + depth_bias, ..self.clone() };
+    }
+}
+
+impl<E: MaterialExtension + Clone> DecalDepthBias for ExtendedMaterial<StandardMaterial, E> {
+    fn with_depth_bias(&self, depth_bias: f32) -> Self {
+        return ExtendedMaterial { base: self.base.with_depth_bias(depth_bias), extension: self.extension.clone() };
+    }
+}
+
+/// Materials that can fade out, for [`DecalLifetime::fade`].
+pub trait DecalFade: Material {
+    /// A copy of the material with its alpha multiplied by `alpha`.
+    fn with_alpha(&self, alpha: f32) -> Self;
+}
+
+impl DecalFade for StandardMaterial {
+    fn with_alpha(&self, alpha: f32) -> Self {
+        let alpha = self.base_color.alpha() * alpha;
+        return StandardMaterial { base_color: self.base_color.with_alpha(alpha), ..self.clone() };
+    }
+}
+
+impl<E: MaterialExtension + Clone> DecalFade for ExtendedMaterial<StandardMaterial, E> {
+    fn with_alpha(&self, alpha: f32) -> Self {
+        return ExtendedMaterial { base: self.base.with_alpha(alpha), extension: self.extension.clone() };
+    }
+}
+
+/// Sent when a spray didn't result in any decal.
+#[derive(Event, Clone, Debug)]
+pub struct DecalFailedEvent {
+    pub spray: SprayId,
+    pub reason: DecalFailureReason,
+}
+
+/// Why a spray didn't result in any decal, see [`DecalFailedEvent`].
+///
+/// When several candidates failed for different reasons, the first
+/// matching variant in declaration order is reported.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DecalFailureReason {
+    /// At least one candidate was skipped because it reached its decal limit,
+    /// see [`Decalable::with_limit`] and [`DecalSettings::max_decals_per_entity`].
+    /// Usually a tuning issue rather than a bug.
+    AllTargetsFull,
+    /// The mesh asset of a candidate isn't loaded.
+    MeshUnavailable,
+    /// A candidate has a mesh the decal can't be projected onto. Meshes need
+    /// a triangle list topology, `Float32x3` positions and normals and `U16` indices.
+    UnsupportedMesh,
+    /// A candidate was dropped for reaching its triangle limit, see [`TriangleLimitPolicy::Drop`].
+    TooManyTriangles,
+    /// No candidate intersected the projection volume, or nothing matched the spray filters.
+    NoTargets,
+    /// The spray was dropped before reaching any target, because too many sprays
+    /// were waiting for the frame budget, see [`DecalSettings::max_queued_sprays`].
+    QueueFull,
+    /// The layout of the spray's [`SprayOptions::atlas`] failed to load or has no cell at its index.
+    AtlasUnavailable,
+}
+
+impl std::fmt::Display for DecalFailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            DecalFailureReason::AllTargetsFull => write!(f, "the targets reached their decal limit"),
+            DecalFailureReason::MeshUnavailable => write!(f, "the target mesh isn't loaded"),
+            DecalFailureReason::UnsupportedMesh => write!(f, "the target mesh has an unsupported format"),
+            DecalFailureReason::TooManyTriangles => write!(f, "the decal reached its triangle limit"),
+            DecalFailureReason::NoTargets => write!(f, "nothing was inside the projection volume"),
+            DecalFailureReason::QueueFull => write!(f, "the spray queue was full"),
+            DecalFailureReason::AtlasUnavailable => write!(f, "the atlas layout isn't available"),
+        };
+    }
+}
+
+#[derive(Reflect, Default)]
+#[reflect(Component, Default)]
+pub struct Decal;   // Marker component for all decals
+
+impl Component for Decal {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // However a decal goes away, its slot on the target is freed for new decals
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, _, _| {
+            if let Some(mut stats) = world.get_resource_mut::<DecalStats>() {
+                stats.decals += 1;
+            }
+        });
+        hooks.on_remove(|mut world, decal, _| {
+            if let Some(mut stats) = world.get_resource_mut::<DecalStats>() {
+                stats.decals = stats.decals.saturating_sub(1);
+            }
+
+            let Some(target) = world.get::<DecalOf>(decal).map(|decal_of| decal_of.target) else {
+                return;
+            };
+            if let Some(mut decalable) = world.get_mut::<Decalable>(target) {
+                decalable.remove_decal(decal);
+            }
+        });
+    }
+}
+
+/// Identifies a single spray, returned by [`spray_decal`] and friends.
+/// Every decal resulting from the spray carries it in a [`DecalSpray`] component.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SprayId(Entity);
+
+impl SprayId {
+    /// The pending spray entity. It only exists until the decals have been applied.
+    pub fn entity(&self) -> Entity {
+        return self.0;
+    }
+}
+
+/// The spray a decal resulted from.
+///
+/// # Example:
+///
+/// ```
+/// // Undo a single spray
+/// for (entity, spray) in decals.iter() {
+///     if spray.0 == my_spray_id {
+///         commands.entity(entity).despawn();
+///     }
+/// }
+/// ```
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Component, PartialEq, Debug)]
+pub struct DecalSpray(pub SprayId);
+
+/// Links a decal to the entity it was applied to and the spray it resulted from.
+///
+/// # Example:
+///
+/// ```
+/// // Everything the player sprayed onto this wall
+/// let on_wall = decals.iter().filter(|decal_of| decal_of.target == wall);
+/// ```
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Component, PartialEq, Debug)]
+pub struct DecalOf {
+    /// The [`Decalable`] the decal was applied to, also the parent of the decal
+    /// unless it's attached to a joint, see [`SprayOptions::attach_to_joint`].
+    pub target: Entity,
+    pub spray: SprayId,
+    /// Offset layer of the decal on the target, the offset from the surface is
+    /// this layer times the offset of the spray, see [`SprayOptions::offset`].
+    pub layer: usize,
+}
+
+/// Statistics of a decal, computed along with its mesh, e.g. to debug decal sizes or to weigh decals
+/// against a budget without going through their meshes.
+///
+/// # Example:
+///
+/// ```
+/// // Decals covering more than a square meter
+/// let large = decals.iter().filter(|info| info.area > 1.);
+/// ```
+///
+/// # Note
+///
+/// Merged decals, see [`DecalSettings::merge_decals`], add up all of their sprays, with the projector
+/// and frame of the newest one. Reprojected decals, see [`ReprojectOnMeshChange`], keep both.
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Debug, Default)]
+#[reflect(Component, PartialEq, Debug, Default)]
+pub struct DecalInfo {
+    pub triangles: usize,
+    pub vertices: usize,
+    /// Surface covered by the decal in square world units, as of when its mesh was built.
+    pub area: f32,
+    /// Projector transform of the spray, see [`projector_transform`].
+    pub projector: Transform,
+    /// The `FrameCount` of the frame the decal was applied in.
+    pub frame_applied: u32,
+}
+
+impl DecalInfo {
+    fn new(mesh: &Mesh, triangles: &DecalTriangles, to_world: Mat4, projector: Transform, frame_applied: u32) -> Self {
+        return DecalInfo {
+            triangles: triangles.0.len(),
+            vertices: mesh.count_vertices(),
+            area: triangles.area(to_world),
+            projector,
+            frame_applied,
+        };
+    }
+}
+
+/// System parameter to look up the decals applied to an entity.
+///
+/// # Example:
+///
+/// ```
+/// fn paint_hits(decals: Decals, mut walls: Query<(Entity, &mut Text), With<Wall>>) {
+///     for (wall, mut text) in walls.iter_mut() {
+///         text.0 = format!("paint hits: {}", decals.count(wall));
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct Decals<'w, 's> {
+    decalables: Query<'w, 's, &'static Decalable>,
+}
+
+impl Decals<'_, '_> {
+    /// The decals on `entity`, oldest first. Empty if the entity isn't [`Decalable`].
+    pub fn on(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        return self.decalables.get(entity).into_iter().flat_map(|decalable| decalable.decals());
+    }
+
+    /// Number of decals on `entity`.
+    pub fn count(&self, entity: Entity) -> usize {
+        return self.decalables.get(entity).map_or(0, |decalable| decalable.count());
+    }
+}
+
+/// Group of a decal, copied from the spray onto every decal it results in.
+/// Decals are in group 0 unless the spray says otherwise, see [`SprayDecal::with_group`].
+///
+/// # Example:
+///
+/// ```
+/// const PAINT: DecalGroup = DecalGroup(1);
+///
+/// SprayDecal::new(paint.clone(), my_transform)
+///     .with_group(PAINT)
+///     .spray(&mut commands);
+///
+/// // Once the round is over
+/// clear_decals_in_group(&mut commands, PAINT);
+/// ```
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[reflect(Component, PartialEq, Debug, Default)]
+pub struct DecalGroup(pub u32);
+
+/// Despawns every decal in `group`, making room for new decals on their targets.
+pub fn clear_decals_in_group(commands: &mut Commands, group: DecalGroup) {
+    commands.queue(move |world: &mut World| {
+        let mut decals = world.query_filtered::<(Entity, &DecalGroup), With<Decal>>();
+        let cleared: Vec<Entity> = decals.iter(world)
+            .filter(|(_, decal_group)| **decal_group == group)
+            .map(|(entity, _)| entity)
+            .collect();
+
+        for entity in cleared {
+            despawn_decal(world, entity);
+        }
+    });
+}
+
+/// Despawns a decal once it's `duration` old, fading it out during the last `fade` of it,
+/// e.g. so blood and footprints don't pile up forever. Decals age on the virtual clock,
+/// so pausing [`Time<Virtual>`] pauses them too. Usually set through [`SprayOptions::lifetime`],
+/// but it can be inserted onto any decal later on as well.
+///
+/// # Example:
+///
+/// ```
+/// // Footprints last 10 seconds, fading out during the last 2
+/// SprayDecal::new(footprint.clone(), my_transform)
+///     .with_lifetime(Duration::from_secs(10), Duration::from_secs(2))
+///     .spray(&mut commands);
+/// ```
+///
+/// # Note
+///
+/// Fading decals get clones of their material with less alpha, shared by every decal of the
+/// same material and layer at the same 8 bit alpha step. This needs a material implementing
+/// [`DecalFade`] with a blended alpha mode, see [`DecalPlugin::with_material_fade`], other decals
+/// simply disappear at the end. A masked material erodes from its edges instead. Sprays with a
+/// lifetime are never merged, see [`DecalSettings::merge_decals`], and restored records start
+/// their lifetime over, see [`restore_decal_records`].
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[reflect(Component, PartialEq, Debug, Default)]
+#[require(DecalAge)]
+pub struct DecalLifetime {
+    /// Time from the spray until the decal is despawned.
+    pub duration: Duration,
+    /// Part of the duration at its end during which the decal fades out. Zero doesn't fade.
+    pub fade: Duration,
+}
+
+// Time a decal with a DecalLifetime has been around, on the virtual clock
+#[derive(Component, Default)]
+struct DecalAge(Duration);
+
+// Ages decals with a lifetime and despawns the expired ones, making room on their targets
+fn expire_decals(mut commands: Commands, time: Res<Time<Virtual>>, mut decals: Query<(Entity, &DecalLifetime, &mut DecalAge)>) {
+    for (decal, lifetime, mut age) in decals.iter_mut() {
+        age.0 += time.delta();
+        if age.0 >= lifetime.duration {
+            commands.queue(move |world: &mut World| despawn_decal(world, decal));
+        }
+    }
+}
+
+// When a decal was last applied to, orders decals for eviction, see DecalSettings::max_total_decals
+#[derive(Component, Clone, Copy)]
+struct DecalOrder(u64);
+
+// Decals never evicted for the global budget, see SprayOptions::budget_exempt
+#[derive(Component)]
+struct DecalBudgetExempt;
+
+// Despawns the least recently applied decals while the world is over budget, see DecalSettings::max_total_decals
+fn enforce_decal_budget(world: &mut World) {
+    let settings = world.resource::<DecalSettings>();
+    let (max_decals, max_triangles) = (settings.max_total_decals, settings.max_total_triangles);
+    let stats = world.resource::<DecalStats>();
+    let (mut decals, mut triangles) = (stats.decals, stats.triangles);
+    let over_budget = |decals: usize, triangles: usize| {
+        return max_decals.is_some_and(|max| decals > max) || max_triangles.is_some_and(|max| triangles > max);
+    };
+    if !over_budget(decals, triangles) {
+        return;
+    }
+
+    let exempt_groups = world.resource::<DecalSettings>().budget_exempt_groups.clone();
+    let mut evictable: Vec<(u64, Entity, usize)> = world
+        .query_filtered::<(Entity, &DecalOrder, &DecalGroup, Option<&DecalTriangles>), (With<Decal>, Without<DecalBudgetExempt>)>()
+        .iter(world)
+        .filter(|(_, _, group, _)| !exempt_groups.contains(group))
+        .map(|(decal, order, _, decal_triangles)| (order.0, decal, decal_triangles.map_or(0, |decal_triangles| decal_triangles.0.len())))
+        .collect();
+    evictable.sort_unstable_by_key(|(order, ..)| *order);
+
+    for (_, decal, decal_triangles) in evictable {
+        if !over_budget(decals, triangles) {
+            break;
+        }
+        despawn_decal(world, decal);
+        decals = decals.saturating_sub(1);
+        triangles = triangles.saturating_sub(decal_triangles);
+    }
+}
+
+/// A region in world space, see [`remove_decals_in_region`].
+#[derive(Clone, Copy, Debug)]
+pub enum DecalRegion {
+    Sphere { center: Vec3, radius: f32 },
+    /// Oriented box, the transform maps the unit cube from -1 to 1 into the world like a projector.
+    Box(Transform),
+}
+
+/// Despawns every decal with geometry inside `region`, like an eraser, making
+/// room for new decals on their targets.
+///
+/// # Example:
+///
+/// ```
+/// // Mop up everything within a meter of the cleaning crew
+/// remove_decals_in_region(&mut commands, DecalRegion::Sphere { center: mop.translation, radius: 1. });
+/// ```
+///
+/// # Note
+///
+/// Tests the actual triangles of each decal as they were generated, so decals
+/// that are merely close to the region are kept. Skinned and morphed decals are
+/// tested in the pose of their target at the time they were sprayed.
+pub fn remove_decals_in_region(commands: &mut Commands, region: DecalRegion) {
+    commands.queue(move |world: &mut World| {
+        let mut decals = world.query_filtered::<(Entity, &GlobalTransform, &DecalTriangles), With<Decal>>();
+        let removed: Vec<Entity> = decals.iter(world)
+            .filter(|(_, transform, triangles)| triangles.intersects(&transform.compute_matrix(), &region))
+            .map(|(entity, ..)| entity)
+            .collect();
+
+        for entity in removed {
+            despawn_decal(world, entity);
+        }
+    });
+}
+
+/// The parameters an applied decal was sprayed with, to spray it again later on, e.g. to keep
+/// the paint of a save game. Decal meshes are derived from their spray, so they aren't stored.
+/// See [`collect_decal_records`] and [`restore_decal_records`].
+///
+/// # Example:
+///
+/// ```
+/// // Saving, with the type registry of the app and any serde format
+/// let records = collect_decal_records(world);
+/// let registry = world.resource::<AppTypeRegistry>().read();
+/// let saved = ron::to_string(&ReflectSerializer::new(&records, &registry))?;
+/// ```
+///
+/// # Note
+///
+/// The component filters of [`SprayOptions`] aren't reflected, and aren't needed either,
+/// as restored decals only reach their own target. Map the target to the entity it was
+/// loaded as with [`MapEntities`], like the components of a scene.
+#[derive(Reflect, Clone)]
+pub struct DecalRecord {
+    /// The [`Decalable`] the decal was applied to.
+    pub target: Entity,
+    /// Projector of the spray, relative to the target.
+    pub transform: Transform,
+    /// Identifier of the material, see [`DecalMaterialIds`].
+    pub material: String,
+    /// Offset layer of the decal on the target, see [`DecalOf::layer`].
+    pub layer: usize,
+    /// Options of the spray, without the targets and excluded entities.
+    pub options: SprayOptions,
+}
+
+impl MapEntities for DecalRecord {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.target = entity_mapper.map_entity(self.target);
+    }
+}
+
+/// Stable identifiers of materials for [`DecalRecord`]s, e.g. their asset path or a name of your
+/// own. Inserted by the [`DecalPlugin`], register every material whose decals should be recorded.
+///
+/// # Example:
+///
+/// ```
+/// fn setup(mut ids: ResMut<DecalMaterialIds>, assets: Res<AssetServer>, mut materials: ResMut<Assets<StandardMaterial>>) {
+///     ids.register("red_paint", materials.add(StandardMaterial {
+///         base_color_texture: Some(assets.load("splatter1.png")),
+///         base_color: Color::srgb(1., 0., 0.),
+///         ..default()
+///     }));
+/// }
+/// ```
+///
+/// # Note
+///
+/// Registered materials are kept alive, so they can be restored at any time.
+#[derive(Resource, Default)]
+pub struct DecalMaterialIds {
+    ids: HashMap<UntypedAssetId, String>,
+    materials: HashMap<String, (UntypedHandle, RestoreSpray)>,
+}
+
+// Sprays a restored decal with a registered material, monomorphized for its material type
+type RestoreSpray = fn(&mut World, Entity, &UntypedHandle, Transform, SprayOptions) -> Vec<Entity>;
+
+impl DecalMaterialIds {
+    /// Registers `material` as `id`, replacing whatever was registered as either before.
+    pub fn register<M: Material>(&mut self, id: impl Into<String>, material: Handle<M>) {
+        let id = id.into();
+        if let Some((previous, _)) = self.materials.remove(&id) {
+            self.ids.remove(&previous.id());
+        }
+        if let Some(previous) = self.ids.insert(material.id().untyped(), id.clone()) {
+            self.materials.remove(&previous);
+        }
+        self.materials.insert(id, (material.untyped(), restore_spray::<M>));
+    }
+
+    /// The identifier `material` is registered as.
+    pub fn id<M: Material>(&self, material: &Handle<M>) -> Option<&str> {
+        return self.ids.get(&material.id().untyped()).map(String::as_str);
+    }
+
+    /// The material registered as `id`, `None` if there is none of this material type.
+    pub fn material<M: Material>(&self, id: &str) -> Option<Handle<M>> {
+        let (material, _) = self.materials.get(id)?;
+        return material.clone().try_typed::<M>().ok();
+    }
+}
+
+fn restore_spray<M: Material>(world: &mut World, spray_entity: Entity, material: &UntypedHandle, transform: Transform, options: SprayOptions) -> Vec<Entity> {
+    let spray = SprayDecal::new(material.clone().typed::<M>(), transform).with_options(options);
+    return apply_spray_immediate(world, spray_entity, &spray);
+}
+
+// What a decal was sprayed with, see collect_decal_records
+#[derive(Component, Clone)]
+struct DecalSource {
+    transform: Transform,       // Projector relative to the target, where it was when projected
+    material: UntypedAssetId,   // Before any variant, see vary_decal_materials
+    options: SprayOptions,
+}
+
+impl DecalSource {
+    fn new<M: Material>(spray_decal: &SprayDecal<M>, projected_from: &GlobalTransform) -> Self {
+        return DecalSource {
+            transform: Transform::from_matrix(projected_from.compute_matrix().inverse() * spray_decal.transform.compute_matrix()),
+            material: spray_decal.material.id().untyped(),
+            options: SprayOptions {
+                targets: None,
+                excluded: Vec::new(),
+                with_components: Vec::new(),
+                without_components: Vec::new(),
+                ..spray_decal.options.clone()
+            },
+        };
+    }
+}
+
+// Layer a restored spray takes on its target, instead of the lowest free one
+#[derive(Component, Clone, Copy)]
+struct RestoredLayer(usize);
+
+/// Records every decal applied so far, target by target and oldest first on each target,
+/// see [`DecalRecord`].
+///
+/// # Example:
+///
+/// ```
+/// fn save(world: &mut World) {
+///     let records = collect_decal_records(world);
+///     world.resource_mut::<SaveGame>().decals = records;
+/// }
+/// ```
+///
+/// # Note
+///
+/// Decals whose material isn't registered in [`DecalMaterialIds`] are skipped with a
+/// warning, as are asynchronous decals still being computed.
+pub fn collect_decal_records(world: &mut World) -> Vec<DecalRecord> {
+    let mut targets: Vec<(Entity, Vec<DecalSlot>)> = world.query::<(Entity, &Decalable)>().iter(world)
+        .map(|(entity, decalable)| (entity, decalable.decals.clone()))
+        .collect();
+    targets.sort_unstable_by_key(|(entity, _)| *entity);
+
+    let mut decals = world.query::<(Option<&DecalSource>, Option<&DecalMerge>)>();
+    let ids = world.get_resource::<DecalMaterialIds>();
+    let mut records = Vec::new();
+    let mut unregistered = 0;
+
+    for (target, slots) in targets {
+        // A merged decal takes one slot per part, in the same order
+        let mut merged_parts: HashMap<Entity, usize> = HashMap::new();
+        for slot in slots {
+            let source = match decals.get(world, slot.decal) {
+                Ok((Some(source), _)) => source,
+                Ok((None, Some(merged))) => {
+                    let part = merged_parts.entry(slot.decal).or_insert(0);
+                    *part += 1;
+                    let Some(part) = merged.parts.get(*part - 1) else {
+                        continue;
+                    };
+                    &part.source
+                }
+                _ => continue,
+            };
+
+            let Some(material) = ids.and_then(|ids| ids.ids.get(&source.material)) else {
+                unregistered += 1;
+                continue;
+            };
+
+            records.push(DecalRecord {
+                target,
+                transform: source.transform,
+                material: material.clone(),
+                layer: slot.layer,
+                options: source.options.clone(),
+            });
+        }
+    }
+
+    if unregistered > 0 {
+        warn!("{unregistered} decals weren't recorded, their materials aren't registered in DecalMaterialIds.");
+    }
+    return records;
+}
+
+/// Sprays recorded decals again, in order and onto the same layers, see [`DecalRecord`].
+/// Every record only reaches its own target, relative to where the target is now.
+///
+/// # Example:
+///
+/// ```
+/// // Once the level is loaded, with the targets mapped to the loaded entities
+/// restore_decal_records(&mut commands, save_game.decals.clone());
+/// ```
+///
+/// # Note
+///
+/// The records are sprayed right away like [`spray_decal_immediate`], so restore them onto
+/// targets without decals, once their meshes are loaded and their transforms propagated.
+/// Records of missing targets or unregistered materials are skipped with a warning.
+/// Skinned and morphed targets get the decals in their current pose, and
+/// [`SprayOptions::occlusion`] only considers the target itself.
+pub fn restore_decal_records(commands: &mut Commands, records: Vec<DecalRecord>) {
+    commands.queue(move |world: &mut World| restore_records(world, records));
+}
+
+fn restore_records(world: &mut World, records: Vec<DecalRecord>) {
+    let mut skipped = 0;
+    for record in records {
+        let material = world.get_resource::<DecalMaterialIds>().and_then(|ids| ids.materials.get(&record.material)).cloned();
+        let target = world.get::<GlobalTransform>(record.target).copied();
+        let (Some((material, restore)), Some(target)) = (material, target) else {
+            skipped += 1;
+            continue;
+        };
+
+        let transform = target.mul_transform(record.transform).compute_transform();
+        let options = SprayOptions { targets: Some(vec![record.target]), ..record.options };
+        let spray_entity = world.spawn(RestoredLayer(record.layer)).id();
+        restore(world, spray_entity, &material, transform, options);
+    }
+
+    if skipped > 0 {
+        warn!("{skipped} decal records weren't restored, their targets are missing or their materials aren't registered in DecalMaterialIds.");
+    }
+}
+
+/// Stable identifier of a [`Decalable`] across respawns, e.g. a wall of a streamed level
+/// chunk, to restore its decals from [`PersistentDecals`].
+#[derive(Component, Reflect, Clone, PartialEq, Eq, Hash, Debug)]
+#[reflect(Component, PartialEq, Debug)]
+pub struct DecalAnchor(pub String);
+
+/// Records of the decals on [`DecalAnchor`]ed targets, by anchor. Whenever an entity with a
+/// stored anchor is spawned, its records are sprayed again once it's [`Decalable`] and its
+/// mesh is loaded, see [`restore_decal_records`]. Insert the resource to opt in.
+///
+/// # Example:
+///
+/// ```
+/// app.init_resource::<PersistentDecals>();
+///
+/// // Before the chunk streams out
+/// fn unload_chunk(world: &mut World) {
+///     persist_anchored_decals(world);
+///     world.entity_mut(chunk).despawn_recursive();
+/// }
+/// ```
+///
+/// # Note
+///
+/// Only the newest records within the limit of the respawned target are restored, see
+/// [`Decalable::with_limit`]. Every respawn restores them once, the records stay stored
+/// until they are replaced by [`persist_anchored_decals`] or removed.
+#[derive(Resource, Default)]
+pub struct PersistentDecals {
+    records: HashMap<String, Vec<DecalRecord>>,
+}
+
+impl PersistentDecals {
+    /// Stores `records` for the targets anchored as `anchor`, replacing what was stored before.
+    pub fn insert(&mut self, anchor: impl Into<String>, records: Vec<DecalRecord>) {
+        self.records.insert(anchor.into(), records);
+    }
+
+    /// The records stored for `anchor`, oldest first.
+    pub fn get(&self, anchor: &str) -> Option<&[DecalRecord]> {
+        return self.records.get(anchor).map(Vec::as_slice);
+    }
+
+    /// Removes and returns the records stored for `anchor`, so its targets respawn without decals.
+    pub fn remove(&mut self, anchor: &str) -> Option<Vec<DecalRecord>> {
+        return self.records.remove(anchor);
+    }
+}
+
+/// Stores the decals of every [`DecalAnchor`]ed target in [`PersistentDecals`], replacing the
+/// records stored for their anchors, also for targets without decals now.
+pub fn persist_anchored_decals(world: &mut World) {
+    let anchors: HashMap<Entity, String> = world.query_filtered::<(Entity, &DecalAnchor), With<Decalable>>().iter(world)
+        .map(|(entity, anchor)| (entity, anchor.0.clone()))
+        .collect();
+
+    let mut records: HashMap<String, Vec<DecalRecord>> = anchors.values().map(|anchor| (anchor.clone(), Vec::new())).collect();
+    for record in collect_decal_records(world) {
+        if let Some(anchor) = anchors.get(&record.target) {
+            records.get_mut(anchor).unwrap().push(record);
+        }
+    }
+
+    world.get_resource_or_insert_with(PersistentDecals::default).records.extend(records);
+}
+
+// Marks anchored targets whose persisted decals were restored, so every respawn restores them once
+#[derive(Component)]
+struct RestoredAnchor;
+
+// Sprays the persisted decals of anchored targets once they can take decals, see PersistentDecals
+fn restore_anchored_decals(
+    world: &mut World,
+    anchored: &mut QueryState<(Entity, &DecalAnchor, &Mesh3d, &Decalable), Without<RestoredAnchor>>,
+) {
+    let settings = world.get_resource::<DecalSettings>().cloned().unwrap_or_default();
+    let meshes = world.resource::<Assets<Mesh>>();
+    // Only the newest records that fit, targets limiting their triangles find out when spraying them
+    let ready: Vec<(Entity, String, usize)> = anchored.iter(world)
+        .filter(|(_, _, mesh, _)| meshes.contains(*mesh))
+        .map(|(entity, anchor, _, decalable)| (entity, anchor.0.clone(), match decalable.triangle_limit(&settings) {
+            Some(_) => usize::MAX,
+            None => decalable.max_decals.unwrap_or(settings.max_decals_per_entity),
+        }))
+        .collect();
+
+    for (target, anchor, limit) in ready {
+        world.entity_mut(target).insert(RestoredAnchor);
+        let Some(stored) = world.resource::<PersistentDecals>().records.get(&anchor) else {
+            continue;
+        };
+        let records = stored[stored.len().saturating_sub(limit)..].iter()
+            .map(|record| DecalRecord { target, ..record.clone() })
+            .collect();
+        restore_records(world, records);
+    }
+}
+
+// Despawns a decal removed through the crate, keeping its mesh for a later decal
+// when the pool has room, see DecalSettings::mesh_pool_size
+fn despawn_decal(world: &mut World, decal: Entity) {
+    let pool_size = world.get_resource::<DecalSettings>().map_or(0, |settings| settings.mesh_pool_size);
+    let mesh = world.get::<Mesh3d>(decal).map(|mesh| mesh.0.clone());
+    if let (Some(mesh), Some(mut pool)) = (mesh, world.get_resource_mut::<DecalMeshPool>()) {
+        pool.recycle(mesh, pool_size);
+    }
+
+    if let Ok(decal) = world.get_entity_mut(decal) {
+        decal.despawn_recursive();
+    }
+}
+
+// Meshes of despawned decals, overwritten by new decals instead of adding assets
+#[derive(Resource, Default)]
+struct DecalMeshPool {
+    meshes: Vec<Handle<Mesh>>,
+}
+
+impl DecalMeshPool {
+    fn recycle(&mut self, mesh: Handle<Mesh>, pool_size: usize) {
+        if self.meshes.len() < pool_size && !self.meshes.contains(&mesh) {
+            self.meshes.push(mesh);
+        }
+    }
+
+    // Only hands out meshes the pool holds the last handle to, so no live entity still renders it.
+    // Decals despawned this frame are only reusable once their despawn is applied.
+    fn take(&mut self) -> Option<Handle<Mesh>> {
+        let index = self.meshes.iter()
+            .position(|mesh| matches!(mesh, Handle::Strong(strong) if Arc::strong_count(strong) == 1))?;
+        return Some(self.meshes.swap_remove(index));
+    }
+}
+
+// Present when the app doesn't render, so decals leave out their visual components
+#[derive(Resource)]
+struct DecalHeadless;
+
+/// Adds decal spraying to the app, for decals with the material `M`.
+///
+/// # Example:
+///
+/// ```
+/// // Default behavior
+/// app.add_plugins(DecalPlugin);
+///
+/// // Or configured
+/// app.add_plugins(
+///     DecalPlugin::new()
+///         .in_schedule(FixedPostUpdate)
+///         .with_max_decals_per_entity(32)
+///         .with_backface_removal(false)
+/// );
+///
+/// // Decals with a custom material, in addition to StandardMaterial
+/// app.add_plugins(DecalPlugin::<MyDecalMaterial>::default());
+/// ```
+///
+/// # Note
+///
+/// Add one plugin per material. Each material gets its own system in [`DecalSet::Apply`],
+/// while [`DecalSettings`] are shared, and only the settings of the first plugin are inserted.
+///
+/// Without the `RenderPlugin`, e.g. on a dedicated server with `MinimalPlugins`, decals are
+/// still applied and their meshes stay readable in `Assets<Mesh>`, but they don't get
+/// shadow components. Add the `AssetPlugin`, `TransformPlugin` and
+/// `HierarchyPlugin`, the assets decals need are initialized by the plugin then.
+pub struct DecalPlugin<M: Material = StandardMaterial> {
+    schedule: Option<InternedScheduleLabel>,
+    settings: DecalSettings,
+    tint: Option<fn(&M, Color) -> M>,
+    depth_bias: Option<fn(&M, f32) -> M>,
+    fade: Option<fn(&M, f32) -> M>,
+    bake: Option<fn(&M) -> DecalBrush>,
+    material: PhantomData<M>,
+}
+
+/// The default [`DecalPlugin`], so `app.add_plugins(DecalPlugin)` works without any configuration.
+#[allow(non_upper_case_globals)]
+pub const DecalPlugin: DecalPlugin = DecalPlugin::new();
+
+impl DecalPlugin {
+    /// The plugin for [`StandardMaterial`] decals, use [`DecalPlugin::default`] for other materials.
+    pub const fn new() -> Self {
+        return DecalPlugin {
+            schedule: None,
+            settings: DecalSettings::DEFAULT,
+            tint: Some(<StandardMaterial as DecalTint>::with_tint),
+            depth_bias: Some(<StandardMaterial as DecalDepthBias>::with_depth_bias),
+            fade: Some(<StandardMaterial as DecalFade>::with_alpha),
+            bake: Some(bake_brush::<StandardMaterial>),
+            material: PhantomData,
+        };
+    }
+}
+
+impl<M: DecalTint> DecalPlugin<M> {
+    /// Lets decals of `M` use [`SprayOptions::tint`]. Already the case for
+    /// the [`StandardMaterial`] plugin from [`DecalPlugin::new`].
+    pub fn with_material_tint(mut self) -> Self {
+        self.tint = Some(M::with_tint);
+        return self;
+    }
+}
+
+impl<M: DecalDepthBias> DecalPlugin<M> {
+    /// Lets decals of `M` use [`DecalOffsetMode::DepthBias`]. Already the case for
+    /// the [`StandardMaterial`] plugin from [`DecalPlugin::new`].
+    pub fn with_material_depth_bias(mut self) -> Self {
+        self.depth_bias = Some(M::with_depth_bias);
+        return self;
+    }
+}
+
+impl<M: DecalFade> DecalPlugin<M> {
+    /// Lets decals of `M` fade out at the end of their [`DecalLifetime`]. Already the case for
+    /// the [`StandardMaterial`] plugin from [`DecalPlugin::new`].
+    pub fn with_material_fade(mut self) -> Self {
+        self.fade = Some(M::with_alpha);
+        return self;
+    }
+}
+
+impl<M: DecalBake> DecalPlugin<M> {
+    /// Lets decals of `M` be baked into a [`DecalBakeTarget`]. Already the case for
+    /// the [`StandardMaterial`] plugin from [`DecalPlugin::new`].
+    pub fn with_material_bake(mut self) -> Self {
+        self.bake = Some(bake_brush::<M>);
+        return self;
+    }
+}
+
+impl<M: Material> DecalPlugin<M> {
+
+    /// Schedule decals are applied in, `PostUpdate` by default. In `PostUpdate`
+    /// decals are applied after transform propagation, in any other schedule
+    /// they use the `GlobalTransform`s of the last propagation.
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = Some(schedule.intern());
+        return self;
+    }
+
+    /// Initial settings, see [`DecalSettings`].
+    pub fn with_settings(mut self, settings: DecalSettings) -> Self {
+        self.settings = settings;
+        return self;
+    }
+
+    /// See [`DecalSettings::max_decals_per_entity`].
+    pub fn with_max_decals_per_entity(mut self, max_decals_per_entity: usize) -> Self {
+        self.settings.max_decals_per_entity = max_decals_per_entity;
+        return self;
+    }
+
+    /// Limits the decal triangles of every entity instead of the number of decals,
+    /// see [`DecalLimitUnit::Triangles`].
+    pub fn with_max_triangles_per_entity(mut self, max_triangles_per_entity: usize) -> Self {
+        self.settings.limit_unit = DecalLimitUnit::Triangles;
+        self.settings.max_triangles_per_entity = max_triangles_per_entity;
+        return self;
+    }
+
+    /// See [`DecalSettings::max_total_decals`].
+    pub fn with_decal_budget(mut self, max_total_decals: usize) -> Self {
+        self.settings.max_total_decals = Some(max_total_decals);
+        return self;
+    }
+
+    /// See [`DecalSettings::max_total_triangles`].
+    pub fn with_triangle_budget(mut self, max_total_triangles: usize) -> Self {
+        self.settings.max_total_triangles = Some(max_total_triangles);
+        return self;
+    }
+
+    /// See [`DecalSettings::remove_backfaces`].
+    pub fn with_backface_removal(mut self, remove_backfaces: bool) -> Self {
+        self.settings.remove_backfaces = remove_backfaces;
+        return self;
+    }
+
+    /// See [`DecalSettings::max_angle`].
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.settings.max_angle = Some(max_angle);
+        return self;
+    }
+
+    /// See [`DecalSettings::cast_shadows`] and [`DecalSettings::receive_shadows`].
+    pub fn with_shadows(mut self, cast_shadows: bool, receive_shadows: bool) -> Self {
+        self.settings.cast_shadows = cast_shadows;
+        self.settings.receive_shadows = receive_shadows;
+        return self;
+    }
+
+    /// See [`DecalSettings::limit_mode`].
+    pub fn with_limit_mode(mut self, limit_mode: DecalLimitMode) -> Self {
+        self.settings.limit_mode = limit_mode;
+        return self;
+    }
+
+    /// See [`DecalSettings::max_triangles_per_decal`] and [`DecalSettings::triangle_limit_policy`].
+    pub fn with_max_triangles_per_decal(mut self, max_triangles: usize, policy: TriangleLimitPolicy) -> Self {
+        self.settings.max_triangles_per_decal = Some(max_triangles);
+        self.settings.triangle_limit_policy = policy;
+        return self;
+    }
+
+    /// See [`DecalSettings::triangle_bvh`].
+    pub fn with_triangle_bvh(mut self, triangle_bvh: bool) -> Self {
+        self.settings.triangle_bvh = triangle_bvh;
+        return self;
+    }
+
+    /// See [`DecalSettings::vertex_cache_bytes`].
+    pub fn with_vertex_cache(mut self, vertex_cache_bytes: usize) -> Self {
+        self.settings.vertex_cache_bytes = vertex_cache_bytes;
+        return self;
+    }
+
+    /// See [`DecalSettings::merge_decals`].
+    pub fn with_decal_merging(mut self, merge_decals: bool) -> Self {
+        self.settings.merge_decals = merge_decals;
+        return self;
+    }
+
+    /// See [`DecalSettings::mesh_pool_size`].
+    pub fn with_mesh_pool(mut self, mesh_pool_size: usize) -> Self {
+        self.settings.mesh_pool_size = mesh_pool_size;
+        return self;
+    }
+
+    /// See [`DecalSettings::max_sprays_per_frame`].
+    pub fn with_max_sprays_per_frame(mut self, max_sprays_per_frame: usize) -> Self {
+        self.settings.max_sprays_per_frame = Some(max_sprays_per_frame);
+        return self;
+    }
+
+    /// See [`DecalSettings::frame_budget`].
+    pub fn with_frame_budget(mut self, frame_budget: Duration) -> Self {
+        self.settings.frame_budget = Some(frame_budget);
+        return self;
+    }
+
+    /// See [`DecalSettings::frame_sliced_async`].
+    pub fn with_frame_sliced_async(mut self, frame_sliced_async: bool) -> Self {
+        self.settings.frame_sliced_async = frame_sliced_async;
+        return self;
+    }
+
+    /// See [`DecalSettings::asset_usage`].
+    pub fn with_asset_usage(mut self, asset_usage: RenderAssetUsages) -> Self {
+        self.settings.asset_usage = asset_usage;
+        return self;
+    }
+}
+
+impl<M: Material> Default for DecalPlugin<M> {
+    fn default() -> Self {
+        return DecalPlugin {
+            schedule: None,
+            settings: DecalSettings::DEFAULT,
+            tint: None,
+            depth_bias: None,
+            fade: None,
+            bake: None,
+            material: PhantomData,
+        };
+    }
+}
+
+impl<M: Material> Plugin for DecalPlugin<M> {
+    fn build(&self, app: &mut App) {
+        // Everything that isn't specific to a material is only set up by the first plugin
+        if !app.world().contains_resource::<Events<DecalAppliedEvent>>() {
+            app.register_type::<Decalable>()
+                .register_type::<DecalBlocked>()
+                .register_type::<DecalBakeTarget>()
+                .register_type::<ReprojectOnMeshChange>()
+                .register_type::<DecalableScene>()
+                .register_type::<DecalableGroup>()
+                .register_type::<DecalLayers>()
+                .register_type::<DecalLimitMode>()
+                .register_type::<DecalLimitUnit>()
+                .register_type::<TriangleLimitPolicy>()
+                .register_type::<DecalOffsetMode>()
+                .register_type::<DecalOcclusion>()
+                .register_type::<DecalShape>()
+                .register_type::<SprayJitter>()
+                .register_type::<DecalAtlas>()
+                .register_type::<DecalAtlasIndex>()
+                .register_type::<Decal>()
+                .register_type::<DecalSpray>()
+                .register_type::<DecalOf>()
+                .register_type::<DecalInfo>()
+                .register_type::<DecalGroup>()
+                .register_type::<DecalLifetime>()
+                .register_type::<SprayOptions>()
+                .register_type::<DecalRecord>()
+                .register_type::<DecalAnchor>()
+                .register_type::<Vec<DecalRecord>>()
+                .register_type::<DecalSettings>();
+
+            app.add_event::<DecalAppliedEvent>()
+                .add_event::<DecalFailedEvent>()
+                .add_event::<DecalTriangleLimitEvent>()
+                .add_event::<DecalBakedEvent>()
+                .init_resource::<DecalBakeQueue>()
+                .init_resource::<DecalMeshPool>()
+                .init_resource::<DecalStats>()
+                .init_resource::<DecalSpatialIndex>()
+                .init_resource::<DecalVertexCache>()
+                .init_resource::<DecalTriangleBvhs>()
+                .init_resource::<DecalMaterialIds>();
+
+            app.register_diagnostic(Diagnostic::new(DecalDiagnostics::SPRAYS))
+                .register_diagnostic(Diagnostic::new(DecalDiagnostics::DECALS))
+                .register_diagnostic(Diagnostic::new(DecalDiagnostics::TRIANGLES))
+                .register_diagnostic(Diagnostic::new(DecalDiagnostics::APPLY_TIME).with_suffix("ms"));
+
+            app.add_systems(Last, (sync_decal_morph_weights, record_decal_diagnostics));
+
+            // Persisted and reprojected decals are updated before the sprays of the frame, with the same transforms,
+            // and expired decals make room for them
+            let schedule = self.schedule.unwrap_or(PostUpdate.intern());
+            let systems = (
+                restore_anchored_decals.run_if(resource_exists::<PersistentDecals>).after(propagate_decalable_scenes),
+                reproject_modified_targets,
+                expire_decals,
+            ).before(DecalSet::Apply);
+            if schedule == PostUpdate.intern() {
+                app.add_systems(schedule, systems.after(TransformSystem::TransformPropagate));
+            } else {
+                app.add_systems(schedule, systems);
+            }
+            app.add_systems(schedule, (
+                (propagate_decalable_scenes, update_decal_index, invalidate_vertex_cache, invalidate_triangle_bvhs).chain().before(DecalSet::Apply),
+                enforce_decal_budget.after(DecalSet::Apply),
+            ));
+
+            // Gizmos need the GizmoPlugin, which only has to be there once they're enabled
+            #[cfg(feature = "debug")]
+            {
+                app.register_type::<DecalProjectorGizmos>()
+                    .init_resource::<DecalProjectorGizmos>()
+                    .init_resource::<ProjectorGizmoLog>();
+                app.add_systems(schedule, draw_projector_gizmos.after(DecalSet::Apply).run_if(|gizmos: Res<DecalProjectorGizmos>| gizmos.enabled));
+            }
+        }
+
+        app.register_type::<ApplyingDecal<M>>()
+            .register_type::<SprayDecal<M>>();
+
+        // Settings inserted by the user before adding the plugin take precedence
+        if !app.world().contains_resource::<DecalSettings>() {
+            app.insert_resource(self.settings.clone());
+        }
+        app.add_event::<SprayDecalEvent<M>>();
+        if self.tint.is_some() || self.depth_bias.is_some() || self.fade.is_some() {
+            app.insert_resource(DecalMaterialVariants::<M> { tint: self.tint, depth_bias: self.depth_bias, fade: self.fade, variants: HashMap::new() });
+        }
+        app.insert_resource(DecalBakeBrush::<M>(self.bake));
+
+        let schedule = self.schedule.unwrap_or(PostUpdate.intern());
+        if schedule == PostUpdate.intern() {
+            // Run after transform propagation, so decals are projected with this frame's GlobalTransforms
+            app.configure_sets(schedule, DecalSet::Apply.after(TransformSystem::TransformPropagate));
+        }
+        app.add_systems(schedule, (
+            (poll_async_decals::<M>, decal_system::<M>).chain().in_set(DecalSet::Apply),
+            (vary_decal_materials::<M>, fade_decals::<M>).chain().after(DecalSet::Apply),
+            bake_decals::<M>.after(DecalSet::Apply),
+        ));
+    }
+
+    // Every other plugin is built by now, so a missing RenderPlugin means there's no rendering
+    fn finish(&self, app: &mut App) {
+        if app.is_plugin_added::<RenderPlugin>() {
+            return;
+        }
+
+        app.insert_resource(DecalHeadless);
+        if !app.world().contains_resource::<Assets<Mesh>>() {
+            app.init_asset::<Mesh>();
+        }
+        if !app.world().contains_resource::<Assets<Image>>() {
+            app.init_asset::<Image>();
+        }
+        if !app.world().contains_resource::<Assets<SkinnedMeshInverseBindposes>>() {
+            app.init_asset::<SkinnedMeshInverseBindposes>();
+        }
+        if !app.world().contains_resource::<Assets<M>>() {
+            app.init_asset::<M>();
+        }
+    }
+}
+
+/// System sets of the [`DecalPlugin`], to order your own systems against decal application.
+///
+/// # Example:
+///
+/// ```
+/// app.add_systems(PostUpdate, (
+///     spray_bullet_holes.before(DecalSet::Apply),
+///     update_paint_coverage.after(DecalSet::Apply),
+/// ));
+/// ```
+///
+/// # Note
+///
+/// By default [`DecalSet::Apply`] runs in `PostUpdate`, after transform propagation,
+/// see [`DecalPlugin::in_schedule`].
+/// Sprays queued before it, including everything queued in `Update`, are
+/// applied in the same frame. The decal entities are spawned through commands,
+/// so they are visible to systems ordered after the set, and to everything
+/// running in later frames. Sprays queued after the set are applied next frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DecalSet {
+    /// Projects pending sprays and spawns the resulting decal entities.
+    Apply,
+}
+
+/// Settings for all sprays and generated decal meshes. Inserted by the
+/// [`DecalPlugin`], changes apply to every decal sprayed afterwards.
+#[derive(Resource, Reflect, Clone)]
+#[reflect(Resource, Default)]
+pub struct DecalSettings {
+    /// Maximum number of decals on a single entity, see [`DecalSettings::limit_mode`]
+    /// for what happens once it's reached. Can be overridden per entity with
+    /// [`Decalable::with_limit`].
+    pub max_decals_per_entity: usize,
+    /// What happens to sprays reaching an entity at its decal limit. Sprays are
+    /// refused by default. Can be overridden per entity with [`Decalable::with_limit_mode`].
+    pub limit_mode: DecalLimitMode,
+    /// Whether entities limit the number of their decals or of their triangles, decals by default.
+    /// Entities with a limit of their own, from [`Decalable::with_limit`] or
+    /// [`Decalable::with_triangle_limit`], count what their limit says instead.
+    pub limit_unit: DecalLimitUnit,
+    /// Maximum number of decal triangles on a single entity, see [`DecalLimitUnit::Triangles`].
+    /// Unused unless [`DecalSettings::limit_unit`] counts triangles.
+    pub max_triangles_per_entity: usize,
+    /// Maximum number of decal entities in the whole world, on top of the limit per entity,
+    /// so decals can't pile up across a big level. Whenever sprays go beyond it, the least
+    /// recently applied decals are despawned right after [`DecalSet::Apply`], freeing their
+    /// slots and layers, until it's met again. `None` has no limit.
+    ///
+    /// # Note
+    ///
+    /// Decals are evicted in the order they were applied, sprays of the same frame in the order
+    /// they reached their targets. Reprojecting a decal keeps its place, while a merged decal moves
+    /// to the back whenever a spray joins it, and is evicted with all of its sprays. Decals of
+    /// [`DecalSettings::budget_exempt_groups`] and [`SprayOptions::budget_exempt`] sprays count toward
+    /// the budget but are never evicted, so it may stay exceeded if only those are left.
+    pub max_total_decals: Option<usize>,
+    /// Maximum number of decal triangles in the whole world, evicting decals like
+    /// [`DecalSettings::max_total_decals`]. `None` has no limit.
+    pub max_total_triangles: Option<usize>,
+    /// Groups whose decals are never evicted for [`DecalSettings::max_total_decals`] and
+    /// [`DecalSettings::max_total_triangles`], e.g. level art or restored decals.
+    pub budget_exempt_groups: Vec<DecalGroup>,
+    /// Only spray the sides of surfaces facing the projector. When false, both
+    /// sides of the mesh will be sprayed. Can be overridden per spray with
+    /// [`SprayOptions::backfaces`].
+    pub remove_backfaces: bool,
+    /// Largest angle in radians between the normal of a triangle and the direction
+    /// back to the projector, steeper triangles are not sprayed. Kept backfaces are
+    /// measured against the opposite direction. `None` only removes the backfaces,
+    /// like an angle of 90°. Can be overridden per spray with [`SprayOptions::max_angle`].
+    pub max_angle: Option<f32>,
+    /// Offset of decals from the surface in world units, to prevent Z-fighting.
+    /// Multiplied by the stacking layer of the decal, the lowest layer not taken
+    /// by another decal on the target. Can be overridden per spray with [`SprayOptions::offset`].
+    /// Unused with [`DecalOffsetMode::DepthBias`].
+    pub offset: f32,
+    /// Whether decals are kept off the surface by [`DecalSettings::offset`] or by
+    /// the depth bias of their material. Geometric by default.
+    pub offset_mode: DecalOffsetMode,
+    /// Sort overlapping decals with blended materials by their stacking layer, so they
+    /// don't swap places as the camera moves. Each sprayed material with a transparent
+    /// alpha mode gets a clone per layer with a tiny depth bias, which Bevy adds to the
+    /// sorting distance. Needs a material implementing [`DecalDepthBias`], and has no
+    /// effect with [`DecalOffsetMode::DepthBias`], which biases every material anyways.
+    pub blend_depth_bias: bool,
+    /// Copy the UVs of the target mesh into `ATTRIBUTE_UV_1` of the decal mesh,
+    /// e.g. to blend the decal with the surface's own textures. The projected
+    /// decal UVs stay in `ATTRIBUTE_UV_0`. The attribute is omitted when the
+    /// target mesh has no UVs.
+    pub copy_target_uvs: bool,
+    /// Whether decals cast shadows. Off by default, paint on a surface has no
+    /// shadow of its own and skipping them saves performance. Can be overridden
+    /// per spray with [`SprayOptions::cast_shadows`].
+    pub cast_shadows: bool,
+    /// Whether decals receive shadows, like the surface they are on. Can be
+    /// overridden per spray with [`SprayOptions::receive_shadows`].
+    pub receive_shadows: bool,
+    /// Generate `ATTRIBUTE_TANGENT` for the decal mesh, needed by materials
+    /// with a normal map. Untextured decals can leave this off.
+    pub generate_tangents: bool,
+    /// Merge duplicate vertices of the decal mesh and reuse them through the
+    /// index buffer. Saves memory on dense surfaces, at a small cost when spraying.
+    pub weld_vertices: bool,
+    /// Clip the triangles of large target meshes on the [`ComputeTaskPool`],
+    /// a few thousand triangles per task. The result is identical either way.
+    /// Without threads, e.g. on wasm32, the tasks simply run one after the other.
+    pub parallel_clipping: bool,
+    /// Maximum number of triangles of a single decal, counted after clipping, so a
+    /// huge spray on a dense mesh can't produce a huge mesh. Decals reaching it send a
+    /// [`DecalTriangleLimitEvent`]. `None` has no limit. Can be overridden per spray
+    /// with [`SprayOptions::max_triangles`].
+    pub max_triangles_per_decal: Option<usize>,
+    /// What happens to decals reaching their triangle limit.
+    pub triangle_limit_policy: TriangleLimitPolicy,
+    /// Build a bounding volume hierarchy over the triangles of a target mesh the first
+    /// time it's sprayed, and only clip the triangles near the projector. Makes small
+    /// decals on huge meshes cheap, at about 20 bytes per triangle for every sprayed mesh.
+    /// Skinned and morphed targets always clip every triangle.
+    pub triangle_bvh: bool,
+    /// Memory in bytes for keeping the world space vertices of static targets
+    /// between sprays, so spraying the same wall over and over only decodes its
+    /// vertices once. Least recently sprayed targets are dropped first, and targets
+    /// moving or changing their mesh are decoded again. 0 disables the cache.
+    pub vertex_cache_bytes: usize,
+    /// Merge static decals of the same material and group into a single mesh per target,
+    /// to save draw calls. Every spray still takes its own slot and stacking layer, and
+    /// eviction only removes its own part of the mesh. Skinned, morphed and asynchronous
+    /// decals are never merged, nor are decals with a [`DecalLifetime`].
+    ///
+    /// # Note
+    ///
+    /// [`remove_decals_in_region`] despawns a merged decal as a whole, and its
+    /// [`DecalOf`] describes the oldest spray it holds.
+    pub merge_decals: bool,
+    /// Find the targets of a spray through a uniform grid over the bounds of all
+    /// [`Decalable`] entities, instead of testing each of them. Worth it from a few
+    /// hundred targets on, turn it off for small scenes. Sprays with explicit
+    /// [`SprayOptions::targets`] don't use it either way.
+    ///
+    /// # Note
+    ///
+    /// The grid is updated right before sprays are applied, so [`spray_decal_immediate`]
+    /// sees the targets as they were at the last [`DecalSet::Apply`].
+    pub spatial_index: bool,
+    /// Number of meshes of despawned decals kept around and overwritten by new
+    /// decals, instead of adding a new mesh asset for every spray. Only decals
+    /// removed by the crate are pooled, e.g. through eviction or [`clear_decals_in_group`].
+    /// 0 disables the pool.
+    pub mesh_pool_size: usize,
+    /// Maximum number of sprays applied per frame and material. Further sprays stay
+    /// queued and are applied on the following frames, oldest first. `None` applies
+    /// every spray in the frame it was sprayed.
+    pub max_sprays_per_frame: Option<usize>,
+    /// Soft time limit for applying sprays each frame, checked after every spray,
+    /// so at least one spray is applied per frame. Queues like [`DecalSettings::max_sprays_per_frame`].
+    /// `None` has no time limit.
+    pub frame_budget: Option<Duration>,
+    /// Maximum number of sprays waiting for the frame budget, per material. Sprays
+    /// beyond it are dropped with a [`DecalFailedEvent`]. Unused without a budget.
+    pub max_queued_sprays: usize,
+    /// Project [`SprayOptions::asynchronous`] decals on the main thread a few at a time,
+    /// instead of on the [`AsyncComputeTaskPool`]: one per frame, or more while within the
+    /// [`DecalSettings::frame_budget`]. Always on for wasm32, where the task pool has no
+    /// threads to run them on, and off elsewhere by default.
+    pub frame_sliced_async: bool,
+    /// Where the generated decal meshes are kept. Render world only by default,
+    /// add [`RenderAssetUsages::MAIN_WORLD`] to read them back from `Assets<Mesh>`
+    /// after they are uploaded, e.g. for coverage calculations, colliders or exporting
+    /// them with the `export` feature.
+    pub asset_usage: RenderAssetUsages,
+}
+
+impl DecalSettings {
+    pub const DEFAULT: DecalSettings = DecalSettings {
+        max_decals_per_entity: DECAL_MAX_PER_ENTTIY,
+        limit_mode: DecalLimitMode::Refuse,
+        limit_unit: DecalLimitUnit::Decals,
+        max_triangles_per_entity: DECAL_MAX_TRIANGLES_PER_ENTITY,
+        max_total_decals: None,
+        max_total_triangles: None,
+        budget_exempt_groups: Vec::new(),
+        remove_backfaces: DECAL_REMOVE_BACKFACES,
+        max_angle: None,
+        offset: DECAL_EPSILON,
+        offset_mode: DecalOffsetMode::Geometric,
+        blend_depth_bias: true,
+        copy_target_uvs: false,
+        cast_shadows: false,
+        receive_shadows: true,
+        generate_tangents: false,
+        weld_vertices: true,
+        parallel_clipping: true,
+        max_triangles_per_decal: None,
+        triangle_limit_policy: TriangleLimitPolicy::Truncate,
+        triangle_bvh: false,
+        vertex_cache_bytes: 0,
+        merge_decals: false,
+        spatial_index: true,
+        mesh_pool_size: 0,
+        max_sprays_per_frame: None,
+        frame_budget: None,
+        max_queued_sprays: DECAL_MAX_QUEUED,
+        frame_sliced_async: cfg!(target_arch = "wasm32"),
+        asset_usage: RenderAssetUsages::RENDER_WORLD,
+    };
+}
+
+impl DecalSettings {
+    /// Checks that the offset is not negative, that every entity can hold at least one decal,
+    /// and that a spray budget lets at least one spray through per frame.
+    /// Invalid settings are clamped to the nearest valid value when spraying.
+    pub fn validate(&self) -> Result<(), InvalidDecalSettings> {
+        if self.offset.is_nan() || self.offset < 0. {
+            return Err(InvalidDecalSettings::NegativeOffset(self.offset));
+        }
+        if self.max_decals_per_entity < 1 {
+            return Err(InvalidDecalSettings::ZeroMaxDecals);
+        }
+        if self.max_sprays_per_frame == Some(0) {
+            return Err(InvalidDecalSettings::ZeroSpraysPerFrame);
+        }
+        return Ok(());
+    }
+
+    fn clamped(&self) -> DecalSettings {
+        return DecalSettings {
+            offset: if self.offset >= 0. { self.offset } else { 0. },
+            max_decals_per_entity: self.max_decals_per_entity.max(1),
+            max_sprays_per_frame: self.max_sprays_per_frame.map(|max_sprays| max_sprays.max(1)),
+            ..self.clone()
+        };
+    }
+}
+
+impl Default for DecalSettings {
+    fn default() -> Self {
+        return DecalSettings::DEFAULT;
+    }
+}
+
+/// Error returned by [`DecalSettings::validate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidDecalSettings {
+    /// [`DecalSettings::offset`] is negative or NaN.
+    NegativeOffset(f32),
+    /// [`DecalSettings::max_decals_per_entity`] is zero.
+    ZeroMaxDecals,
+    /// [`DecalSettings::max_sprays_per_frame`] is zero.
+    ZeroSpraysPerFrame,
+}
+
+impl std::fmt::Display for InvalidDecalSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            InvalidDecalSettings::NegativeOffset(offset) => write!(f, "decal offset must be >= 0, got {offset}"),
+            InvalidDecalSettings::ZeroMaxDecals => write!(f, "max decals per entity must be >= 1"),
+            InvalidDecalSettings::ZeroSpraysPerFrame => write!(f, "max sprays per frame must be >= 1"),
+        };
+    }
+}
+
+impl std::error::Error for InvalidDecalSettings {}
+
+// Triangles of a decal mesh in the local space of the decal entity, kept on the CPU for region queries
+struct DecalTriangles(Vec<[Vec3; 3]>);
+
+impl Component for DecalTriangles {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    // Keeps DecalStats::triangles up to date. Replace it by removing it first, inserting over it skips on_remove
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_add(|mut world, decal, _| {
+            let triangles = world.get::<DecalTriangles>(decal).map_or(0, |triangles| triangles.0.len());
+            if let Some(mut stats) = world.get_resource_mut::<DecalStats>() {
+                stats.triangles += triangles;
+            }
+        });
+        hooks.on_remove(|mut world, decal, _| {
+            let triangles = world.get::<DecalTriangles>(decal).map_or(0, |triangles| triangles.0.len());
+            if let Some(mut stats) = world.get_resource_mut::<DecalStats>() {
+                stats.triangles = stats.triangles.saturating_sub(triangles);
+            }
+        });
+    }
+}
+
+impl DecalTriangles {
+    fn from_mesh(mesh: &Mesh) -> Self {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return DecalTriangles(Vec::new());
+        };
+        let Some(indices) = mesh.indices() else {
+            return DecalTriangles(Vec::new());
+        };
+
+        // Merged decals use U32 indices, see combine_meshes
+        let indices: Vec<usize> = indices.iter().collect();
+        return DecalTriangles(indices.chunks_exact(3)
+            .map(|triangle| [0, 1, 2].map(|corner| Vec3::from(positions[triangle[corner]])))
+            .collect());
+    }
+
+    // Surface of the triangles once transformed, where the sprays of merged decals overlap counts twice
+    fn area(&self, transform: Mat4) -> f32 {
+        return self.0.iter()
+            .map(|triangle| triangle.map(|point| transform.transform_point3(point)))
+            .map(|[a, b, c]| (b - a).cross(c - a).length() * 0.5)
+            .sum();
+    }
+
+    fn intersects(&self, transform: &Mat4, region: &DecalRegion) -> bool {
+        let world = |triangle: &[Vec3; 3]| triangle.map(|point| transform.transform_point3(point));
+
+        return match region {
+            DecalRegion::Sphere { center, radius } => self.0.iter().any(|triangle| {
+                closest_point_on_triangle(*center, world(triangle)).distance_squared(*center) <= radius * radius
+            }),
+            DecalRegion::Box(region) => {
+                let to_region = region.compute_matrix().inverse() * *transform;
+                self.0.iter().any(|triangle| {
+                    triangle_intersects_unit_cube(triangle.map(|point| to_region.transform_point3(point)))
+                })
+            }
+        };
+    }
+}
+
+// Closest point to p on a triangle, from Real-Time Collision Detection by Christer Ericson
+fn closest_point_on_triangle(p: Vec3, [a, b, c]: [Vec3; 3]) -> Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0. && d2 <= 0. {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0. && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0. && d1 >= 0. && d3 <= 0. {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0. && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0. && d2 >= 0. && d6 <= 0. {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1. / (va + vb + vc);
+    return a + ab * (vb * denom) + ac * (vc * denom);
+}
+
+// Separating axis test of a triangle against the unit cube from -1 to 1
+fn triangle_intersects_unit_cube(triangle: [Vec3; 3]) -> bool {
+    let separated = |axis: Vec3| -> bool {
+        let projected = triangle.map(|point| axis.dot(point));
+        let radius = axis.abs().element_sum();
+        let min = projected[0].min(projected[1]).min(projected[2]);
+        let max = projected[0].max(projected[1]).max(projected[2]);
+        return min > radius || max < -radius;
+    };
+
+    let edges = [triangle[1] - triangle[0], triangle[2] - triangle[1], triangle[0] - triangle[2]];
+    let face_axes = [Vec3::X, Vec3::Y, Vec3::Z];
+
+    if face_axes.iter().any(|axis| separated(*axis)) || separated(edges[0].cross(edges[1])) {
+        return false;
+    }
+
+    for edge in edges {
+        for axis in face_axes {
+            let axis = axis.cross(edge);
+            if axis != Vec3::ZERO && separated(axis) {
+                return false;
+            }
+        }
+    }
+
+    return true;
+}
+
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
+struct ApplyingDecal<M: Material = StandardMaterial>(SprayDecal<M>);
+
+// Vertices being clipped, with their position in projector space
+type Vertex = ClipVertex<VertexAttributes>;
+type Triangle = ClipTriangle<VertexAttributes>;
+
+#[derive(Clone, Copy)]
+struct VertexAttributes {
+    normal: Vec3,           // Projector space
+    uv: Vec2,               // UV of the target mesh
+    color: Vec4,            // Vertex color of the target mesh
+    local_position: Vec3,   // Bind space of the target mesh, used for skinned decals
+    local_normal: Vec3,
+    joints: JointInfluences,
+    barycentric: Vec3,      // Position within the source triangle, used to interpolate morph targets
+}
+
+impl ClipPayload for VertexAttributes {
+    fn lerp(&self, rhs: &VertexAttributes, d: f32) -> VertexAttributes {
+        return VertexAttributes {
+            normal: self.normal.lerp(rhs.normal, d),
+            uv: self.uv.lerp(rhs.uv, d),
+            color: self.color.lerp(rhs.color, d),
+            local_position: self.local_position.lerp(rhs.local_position, d),
+            local_normal: self.local_normal.lerp(rhs.local_normal, d),
+            joints: self.joints.lerp(&rhs.joints, d),
+            barycentric: self.barycentric.lerp(rhs.barycentric, d),
+        };
+    }
+}
+
+// Up to 4 skinning influences of a vertex. All weights are zero for non-skinned meshes
+#[derive(Clone, Copy, Default)]
+struct JointInfluences {
+    indices: [u16; 4],
+    weights: [f32; 4],
+}
+
+impl JointInfluences {
+    // Blend the influences of both vertices, keeping the 4 strongest joints and renormalizing
+    fn lerp(&self, rhs: &JointInfluences, d: f32) -> JointInfluences {
+        let mut influences = [(0u16, 0f32); 8];
+        let mut count = 0;
+
+        for (joints, factor) in [(self, 1. - d), (rhs, d)] {
+            for k in 0..4 {
+                let weight = joints.weights[k] * factor;
+                if weight <= 0. {
+                    continue;
+                }
+                match influences[..count].iter_mut().find(|(index, _)| *index == joints.indices[k]) {
+                    Some(influence) => influence.1 += weight,
+                    None => {
+                        influences[count] = (joints.indices[k], weight);
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        influences[..count].sort_by(|a, b| b.1.total_cmp(&a.1));
+        let count = count.min(4);
+        let total: f32 = influences[..count].iter().map(|(_, weight)| weight).sum();
+
+        let mut result = JointInfluences::default();
+        for (k, (index, weight)) in influences.iter().take(count).enumerate() {
+            result.indices[k] = *index;
+            result.weights[k] = weight / total;
+        }
+        return result;
+    }
+
+    // Blended joint matrix, where each matrix is the joint's world transform times its inverse bindpose
+    fn skin_matrix(&self, joint_matrices: &[Mat4]) -> Mat4 {
+        let mut matrix = Mat4::ZERO;
+        for k in 0..4 {
+            if let Some(joint_matrix) = joint_matrices.get(self.indices[k] as usize) {
+                matrix += *joint_matrix * self.weights[k];
+            }
+        }
+        return matrix;
+    }
+}
+
+// Morph target deltas of a target mesh, decoded from its morph target image
+#[derive(Clone)]
+struct MorphTargets {
+    deltas: Vec<Vec<MorphAttributes>>,  // Indexed by [target][vertex]
+    weights: Vec<f32>,
+}
+
+impl MorphTargets {
+    fn from_image(image: &Image, weights: &[f32], vertex_count: usize) -> Option<MorphTargets> {
+        // Each target is one layer of the image, made of tightly packed MorphAttributes
+        let components = std::mem::size_of::<MorphAttributes>() / std::mem::size_of::<f32>();
+        let size = image.texture_descriptor.size;
+        let layer_size = (size.width * size.height) as usize;
+        let target_count = size.depth_or_array_layers as usize;
+
+        let floats: Vec<f32> = image.data.chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+
+        if layer_size < vertex_count * components || floats.len() < layer_size * target_count {
+            return None;
+        }
+
+        let deltas = (0..target_count).map(|target| {
+            (0..vertex_count).map(|vertex| {
+                let f = &floats[target * layer_size + vertex * components..];
+                MorphAttributes::new(
+                    Vec3::new(f[0], f[1], f[2]),
+                    Vec3::new(f[3], f[4], f[5]),
+                    Vec3::new(f[6], f[7], f[8]),
+                )
+            }).collect()
+        }).collect();
+
+        return Some(MorphTargets {
+            deltas,
+            weights: (0..target_count).map(|target| weights.get(target).copied().unwrap_or(0.)).collect(),
+        });
+    }
+
+    // Interpolated deltas of a target at a point within a source triangle
+    fn interpolate(&self, target: usize, triangle: [usize; 3], barycentric: Vec3) -> MorphAttributes {
+        let deltas = &self.deltas[target];
+        let [a, b, c] = [deltas[triangle[0]], deltas[triangle[1]], deltas[triangle[2]]];
+        return MorphAttributes::new(
+            a.position * barycentric.x + b.position * barycentric.y + c.position * barycentric.z,
+            a.normal * barycentric.x + b.normal * barycentric.y + c.normal * barycentric.z,
+            a.tangent * barycentric.x + b.tangent * barycentric.y + c.tangent * barycentric.z,
+        );
+    }
+
+    // Weighted sum of all target deltas of a single vertex
+    fn evaluate(&self, vertex: usize) -> (Vec3, Vec3) {
+        let mut position = Vec3::ZERO;
+        let mut normal = Vec3::ZERO;
+        for (deltas, weight) in self.deltas.iter().zip(self.weights.iter()) {
+            position += deltas[vertex].position * *weight;
+            normal += deltas[vertex].normal * *weight;
+        }
+        return (position, normal);
+    }
+}
+
+// Generated decal geometry, along with its morph targets if the target mesh has any
+struct DecalGeometry {
+    mesh: Mesh,
+    morph_targets: Option<Image>,
+    triangles: usize,
+    centroid: Vec3,     // In world space
+    truncated: bool,    // Stopped at the triangle limit, see SprayOptions::max_triangles
+}
+
+impl DecalGeometry {
+    // Truncated decals are dropped entirely, depending on DecalSettings::triangle_limit_policy
+    fn is_allowed(&self, settings: &DecalSettings) -> bool {
+        return !self.truncated || settings.triangle_limit_policy == TriangleLimitPolicy::Truncate;
+    }
+
+    // Neither skinned nor morphed, so the decal can be merged with others
+    fn is_static(&self) -> bool {
+        return self.morph_targets.is_none() && !self.mesh.contains_attribute(Mesh::ATTRIBUTE_JOINT_INDEX);
+    }
+}
+
+// Quantized vertex attributes. Vertices with equal keys are merged when welding.
+// The decal UV only differs between vertices at the same position on a seam.
+fn weld_key(vertex: &Vertex, decal_uv: Vec2) -> [i32; 10] {
+    let quantize = |value: f32| (value / DECAL_WELD_EPSILON).round() as i32;
+    return [
+        quantize(vertex.position.x),
+        quantize(vertex.position.y),
+        quantize(vertex.position.z),
+        quantize(vertex.payload.normal.x),
+        quantize(vertex.payload.normal.y),
+        quantize(vertex.payload.normal.z),
+        quantize(vertex.payload.uv.x),
+        quantize(vertex.payload.uv.y),
+        quantize(decal_uv.x),
+        quantize(decal_uv.y),
+    ];
+}
+
+// Buffers reused by apply_decal across sprays, so clipping doesn't allocate per triangle
+#[derive(Default)]
+struct DecalScratch {
+    triangles: Vec<Triangle>,
+    sources: Vec<[usize; 3]>,   // Source triangle of each new triangle
+    clip: ClipScratch,
+    gathered: Vec<u32>,         // Triangles found in a BVH
+    gathered_indices: Vec<u16>,
+}
+
+// Triangles being clipped against one plane after the other
+#[derive(Default)]
+struct ClipScratch {
+    buffers: ClipBuffers<VertexAttributes>,
+    subdivided: Vec<Triangle>,  // Triangles left to subdivide, see push_clipped
+}
+
+// Adds a clipped triangle to the decal. Curved shapes split the longest edge at its midpoint
+// until every edge is short enough, and drop the parts outside of them. The split only depends
+// on the edge, so neighboring triangles split shared edges alike and no cracks appear.
+fn push_clipped(
+    triangle: Triangle,
+    source: [usize; 3],
+    shape: DecalShape,
+    triangles: &mut Vec<Triangle>,
+    sources: &mut Vec<[usize; 3]>,
+    subdivided: &mut Vec<Triangle>,
+) {
+    if !shape.is_curved() {
+        triangles.push(triangle);
+        sources.push(source);
+        return;
+    }
+
+    subdivided.clear();
+    match shape {
+        DecalShape::Cylinder { .. } => split_seam(triangle, subdivided),
+        _ => subdivided.push(triangle),
+    }
+    while let Some(triangle) = subdivided.pop() {
+        if !shape.intersects(triangle.a.position, triangle.b.position, triangle.c.position) {
+            continue;
+        }
+
+        // Each edge along with the corner opposite to it, in winding order
+        let edges = [
+            (triangle.a, triangle.b, triangle.c),
+            (triangle.b, triangle.c, triangle.a),
+            (triangle.c, triangle.a, triangle.b),
+        ];
+        let (p, q, r) = *edges.iter()
+            .max_by(|(a, b, _), (c, d, _)| a.position.distance_squared(b.position).total_cmp(&c.position.distance_squared(d.position)))
+            .unwrap();
+        if p.position.distance_squared(q.position) <= DECAL_CURVED_EDGE * DECAL_CURVED_EDGE {
+            triangles.push(triangle);
+            sources.push(source);
+            continue;
+        }
+
+        // Interpolated in the same direction for both triangles sharing the edge
+        let midpoint = if (p.position.x, p.position.y, p.position.z) <= (q.position.x, q.position.y, q.position.z) {
+            p.lerp(q, 0.5)
+        } else {
+            q.lerp(p, 0.5)
+        };
+        subdivided.push(Triangle { a: p, b: midpoint, c: r });
+        subdivided.push(Triangle { a: midpoint, b: q, c: r });
+    }
+}
+
+// Splits a triangle crossing the seam of a cylinder, the half plane x = 0 behind the axis,
+// so each part maps the texture from one side of the seam. See DecalShape::texture_position.
+fn split_seam(triangle: Triangle, triangles: &mut Vec<Triangle>) {
+    let Triangle { a, b, c } = triangle;
+    let left = [a, b, c].map(|vertex| vertex.position.x < 0.);
+    let right = [a, b, c].iter().any(|vertex| vertex.position.x > 0.);
+    let behind = [a, b, c].iter().any(|vertex| vertex.position.z < 0.);
+    if !behind || !right || left.iter().all(|left| !*left) {
+        triangles.push(Triangle { a, b, c });
+        return;
+    }
+
+    // The corner alone on its side first, keeping the winding
+    let (a, b, c) = if left[1] == left[2] {
+        (a, b, c)
+    } else if left[0] == left[2] {
+        (b, c, a)
+    } else {
+        (c, a, b)
+    };
+
+    // Interpolated in the same direction for both triangles sharing the edge, and snapped onto the seam
+    let seam = |p: Vertex, q: Vertex| -> Vertex {
+        let (p, q) = if (p.position.x, p.position.y, p.position.z) <= (q.position.x, q.position.y, q.position.z) { (p, q) } else { (q, p) };
+        let mut vertex = p.lerp(q, p.position.x / (p.position.x - q.position.x));
+        vertex.position.x = 0.;
+        return vertex;
+    };
+    let ab = seam(a, b);
+    let ac = seam(a, c);
+    triangles.push(Triangle { a, b: ab, c: ac });
+    triangles.push(Triangle { a: b, b: c, c: ac });
+    triangles.push(Triangle { a: b, b: ac, c: ab });
+}
+
+/// Generates the decal geometry for a single mesh, without going through the ECS.
+/// Useful for editor tools, baking, or custom batching. [`DecalPlugin`] uses the
+/// same projection, so the result matches what a spray would produce.
+///
+/// The resulting mesh is in the space of the projector. To place it, parent it
+/// to the target with a local transform of `mesh_transform⁻¹ * projector`, or
+/// spawn it unparented with `projector` as its transform.
+///
+/// # Example:
+///
+/// ```
+/// let decal_mesh = project_decal(
+///     meshes.get(&wall_mesh).unwrap(),
+///     wall_global_transform,
+///     &projector,
+///     // Offset from the surface in world units
+///     0.001,
+/// );
+///
+/// if let Some(decal_mesh) = decal_mesh {
+///     commands.spawn((
+///         Mesh3d(meshes.add(decal_mesh)),
+///         MeshMaterial3d(my_material.clone()),
+///         projector,
+///     ));
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the mesh doesn't have `ATTRIBUTE_POSITION` and `ATTRIBUTE_NORMAL`
+/// as `Float32x3`, or doesn't have `U16` indices.
+///
+/// # Note
+///
+/// Returns `None` when no triangle of the mesh is inside the projection
+/// volume. Skinning and morph targets are ignored, the mesh is projected
+/// in its bind pose.
+pub fn project_decal(mesh: &Mesh, mesh_transform: &GlobalTransform, projector: &Transform, offset: f32) -> Option<Mesh> {
+    return project_decal_with(mesh, mesh_transform, projector, offset, &DecalSettings::default(), &SprayOptions::default());
+}
+
+/// Same as [`project_decal`], with explicit [`DecalSettings`] and [`SprayOptions`].
+pub fn project_decal_with(
+    mesh: &Mesh,
+    mesh_transform: &GlobalTransform,
+    projector: &Transform,
+    offset: f32,
+    settings: &DecalSettings,
+    options: &SprayOptions,
+) -> Option<Mesh> {
+    return apply_decal(mesh, &mesh_transform.compute_transform(), projector, offset, None, None, settings, options, None, None, None, None, &mut DecalScratch::default())
+        .filter(|geometry| geometry.is_allowed(settings))
+        .map(|geometry| geometry.mesh);
+}
+
+fn apply_decal(
+    mesh: &Mesh,
+    mesh_transform: &Transform,
+    decal_transform: &Transform,
+    offset: f32,
+    joint_matrices: Option<&[Mat4]>,
+    morph_targets: Option<&MorphTargets>,
+    settings: &DecalSettings,
+    options: &SprayOptions,
+    world_vertices: Option<&WorldVertices>,
+    bvh: Option<&TriangleBvh>,
+    occlusion: Option<&DepthGrid>,
+    anchor: Option<Vec3>,
+    scratch: &mut DecalScratch,
+) -> Option<DecalGeometry> {
+    let vertex_attribute = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
+    let normal_attribute = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap();
+    let indices = mesh.indices().unwrap();
+
+    let VertexAttributeValues::Float32x3(vertex_attribute) = vertex_attribute else {
+        panic!("Unexpected vertex format, expected Float32x3.");
+    };
+
+    let VertexAttributeValues::Float32x3(normal_attribute) = normal_attribute else {
+        panic!("Unexpected normal format, expected Float32x3.");
+    };
+
+    let Indices::U16(indices) = indices else {
+        panic!("Unexpected indices format, expected U16.");
+    };
+
+    // Skinned targets are projected in their current pose, and the decal is emitted in bind space
+    // so it can be skinned by the same joints. Without the joint matrices the mesh is treated as static.
+    let skin = match (
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX),
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT),
+        joint_matrices,
+    ) {
+        (
+            Some(VertexAttributeValues::Uint16x4(joint_indices)),
+            Some(VertexAttributeValues::Float32x4(joint_weights)),
+            Some(joint_matrices),
+        ) => Some((joint_indices, joint_weights, joint_matrices)),
+        _ => None,
+    };
+
+    let uv_attribute = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uv_attribute)) if settings.copy_target_uvs => Some(uv_attribute),
+        _ => None,
+    };
+
+    let color_attribute = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(color_attribute)) => Some(color_attribute),
+        _ => None,
+    };
+    
+    let axii = options.shape.clip_planes();
+    let is_inside = |p: Vec3| axii.iter().all(|axis| p.dot(*axis) <= 1.);
+
+    let remove_backfaces = !options.two_sided && options.backfaces.map_or(settings.remove_backfaces, |backfaces| !backfaces);
+    // Cosine of the steepest angle sprayed, removing backfaces is the special case of 90°
+    let min_facing = options.max_angle.or(settings.max_angle).map(|max_angle| max_angle.cos());
+    // Projector space normals are scaled by the projector size, which would skew the angle
+    let projector_scale = decal_transform.scale;
+
+    let decal_proj = decal_transform.compute_matrix().inverse();
+    // Normals are transformed by the inverse transpose, so they stay perpendicular under non-uniform scale
+    let mesh_matrix = mesh_transform.compute_matrix();
+    // Mirrored targets wind their triangles against their normals in world space, so the winding of their
+    // decals is reversed to face the way of the normals. Decals are drawn with the projector transform, which
+    // undoes the mirroring of a mirrored projector. Skinned decals are emitted in bind space, where nothing is mirrored.
+    let reverse_winding = skin.is_none() && mesh_matrix.determinant() < 0.;
+    let mesh_inverse = mesh_matrix.inverse();
+    let mesh_normal_matrix = normal_matrix(mesh_matrix);
+    let decal_normal_matrix = normal_matrix(decal_proj);
+
+    // Only static targets can be cached, see DecalVertexCache
+    let world_vertices = world_vertices.filter(|_| skin.is_none() && morph_targets.is_none());
+
+    let vertex = |index: u16, barycentric: Vec3| -> Vertex {
+        let index = index as usize;
+
+        if let Some(world_vertices) = world_vertices {
+            let world_normal = world_vertices.normals[index];
+            let world_position = world_vertices.positions[index] + world_normal * offset;
+            return Vertex::new(decal_proj.transform_point3(world_position), VertexAttributes {
+                normal: (decal_normal_matrix * world_normal).normalize_or_zero(),
+                uv: uv_attribute.map_or(Vec2::ZERO, |uv_attribute| Vec2::from(uv_attribute[index])),
+                color: color_attribute.map_or(Vec4::ONE, |color_attribute| Vec4::from(color_attribute[index])),
+                // The bind space is only used by skinned decals
+                local_position: Vec3::ZERO,
+                local_normal: Vec3::ZERO,
+                joints: JointInfluences::default(),
+                barycentric,
+            });
+        }
+
+        let base_normal = Vec3::from(normal_attribute[index]);
+        let base_position = Vec3::from(vertex_attribute[index]);
+
+        // Project against the currently morphed surface, while keeping the base pose as the output
+        let (morphed_position, morphed_normal) = match morph_targets {
+            Some(morph_targets) => {
+                let (position, normal) = morph_targets.evaluate(index);
+                (base_position + position, base_normal + normal)
+            }
+            None => (base_position, base_normal),
+        };
+
+        let (world_position, world_normal, world_to_local, joints) = match skin {
+            Some((joint_indices, joint_weights, joint_matrices)) => {
+                let joints = JointInfluences { indices: joint_indices[index], weights: joint_weights[index] };
+                let skin_matrix = joints.skin_matrix(joint_matrices);
+                (
+                    skin_matrix.transform_point3(morphed_position),
+                    (normal_matrix(skin_matrix) * morphed_normal).normalize_or_zero(),
+                    skin_matrix.inverse(),
+                    joints,
+                )
+            }
+            None => (
+                mesh_matrix.transform_point3(morphed_position),
+                (mesh_normal_matrix * morphed_normal).normalize_or_zero(),
+                mesh_inverse,
+                JointInfluences::default(),
+            ),
+        };
+
+        // The offset is a world space distance, so it doesn't depend on the scale of the target
+        let world_offset = world_normal * offset;
+        let world_position = world_position + world_offset;
+        let position = decal_proj.transform_point3(world_position);
+        let local_position = base_position + world_to_local.transform_vector3(world_offset);
+        let local_normal = if options.projector_normals {
+            // The side of the surface facing the projector, brought into bind space like a normal
+            let back = decal_transform.rotation * options.shape.back(position, projector_scale);
+            normal_matrix(world_to_local) * (back * world_normal.dot(back).signum())
+        } else {
+            base_normal
+        };
+
+        return Vertex::new(position, VertexAttributes {
+            normal: (decal_normal_matrix * world_normal).normalize_or_zero(),
+            uv: uv_attribute.map_or(Vec2::ZERO, |uv_attribute| Vec2::from(uv_attribute[index])),
+            color: color_attribute.map_or(Vec4::ONE, |color_attribute| Vec4::from(color_attribute[index])),
+            local_position,
+            local_normal,
+            joints,
+            barycentric,
+        });
+    };
+
+    let max_triangles = options.max_triangles.or(settings.max_triangles_per_decal).unwrap_or(usize::MAX);
+
+    // Projects and clips a range of the index buffer, independent of every other range.
+    // Returns true when it stopped early at max_triangles.
+    let clip = |indices: &[u16], new_triangles: &mut Vec<Triangle>, new_sources: &mut Vec<[usize; 3]>, scratch: &mut ClipScratch| -> bool {
+        for triangle in indices.chunks(3) {
+            // Counts the output, triangles split by clipping included
+            if new_triangles.len() >= max_triangles {
+                return true;
+            }
+
+            let a = vertex(triangle[0], Vec3::X);
+            let b = vertex(triangle[1], Vec3::Y);
+            let c = vertex(triangle[2], Vec3::Z);
+            let source = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+
+            let mut removed = false;
+            for axis in axii.iter() {
+                let fa = a.position.dot(*axis);
+                let fb = b.position.dot(*axis);
+                let fc = c.position.dot(*axis);
+
+                if fa > 1. && fb > 1. && fc > 1. {
+                    removed = true;
+                    break;
+                }
+            }
+            if removed || !options.shape.intersects(a.position, b.position, c.position) {
+                continue;
+            }
+
+            // Kept backfaces need no special treatment, clipping preserves the winding of the source triangle
+            if remove_backfaces || min_facing.is_some() {
+                let normal = ((a.payload.normal + b.payload.normal + c.payload.normal) / projector_scale).normalize_or_zero();
+                let facing = normal.dot(options.shape.back((a.position + b.position + c.position) / 3., projector_scale));
+                if remove_backfaces && facing < 0. {
+                    continue;
+                }
+                if min_facing.is_some_and(|min_facing| facing.abs() < min_facing) {
+                    continue;
+                }
+            }
+
+            let ClipScratch { buffers, subdivided } = &mut *scratch;
+
+            if is_inside(a.position) && is_inside(b.position) && is_inside(c.position) {
+                push_clipped(Triangle {a, b, c}, source, options.shape, new_triangles, new_sources, subdivided);
+                continue;
+            }
+
+            for triangle in clip_triangle(Triangle {a, b, c}, &axii, buffers).iter().rev() {
+                push_clipped(*triangle, source, options.shape, new_triangles, new_sources, subdivided);
+            }
+        }
+        return false;
+    };
+
+    let DecalScratch { triangles: new_triangles, sources: new_sources, clip: clip_scratch, gathered, gathered_indices } = scratch;
+    new_triangles.clear();
+    new_sources.clear();
+    let mut truncated = false;
+
+    // Only the triangles near the projector, still in index buffer order
+    let indices: &[u16] = match bvh.filter(|_| skin.is_none() && morph_targets.is_none()) {
+        Some(bvh) => {
+            let local_projector = mesh_inverse * decal_transform.compute_matrix();
+            let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+            for corner in 0..8 {
+                let corner = Vec3::new(
+                    if corner & 1 == 0 { -1. } else { 1. },
+                    if corner & 2 == 0 { -1. } else { 1. },
+                    if corner & 4 == 0 { -1. } else { 1. },
+                );
+                let corner = local_projector.transform_point3(corner);
+                min = min.min(corner);
+                max = max.max(corner);
+            }
+            // Vertices are moved by the offset before clipping, the Frobenius norm bounds its local length
+            let margin = offset.abs() * Vec3::new(
+                mesh_inverse.x_axis.truncate().length(),
+                mesh_inverse.y_axis.truncate().length(),
+                mesh_inverse.z_axis.truncate().length(),
+            ).length();
+
+            bvh.gather(min - margin, max + margin, gathered);
+            gathered_indices.clear();
+            for triangle in gathered.iter() {
+                let triangle = *triangle as usize * 3;
+                gathered_indices.extend_from_slice(&indices[triangle..triangle + 3]);
+            }
+            &gathered_indices[..]
+        }
+        None => &indices[..],
+    };
+
+    if settings.parallel_clipping && indices.len() > DECAL_PARALLEL_CHUNK * 3 {
+        // Chunks are concatenated in index buffer order, so the output is the same as clipping serially
+        let mut chunks = ComputeTaskPool::get_or_init(TaskPool::default).scope(|scope| {
+            for (chunk_index, chunk) in indices.chunks(DECAL_PARALLEL_CHUNK * 3).enumerate() {
+                let clip = &clip;
+                scope.spawn(async move {
+                    let mut new_triangles = Vec::new();
+                    let mut new_sources = Vec::new();
+                    let truncated = clip(chunk, &mut new_triangles, &mut new_sources, &mut ClipScratch::default());
+                    (chunk_index, new_triangles, new_sources, truncated)
+                });
+            }
+        });
+        chunks.sort_unstable_by_key(|(chunk_index, ..)| *chunk_index);
+
+        for (_, triangles, sources, chunk_truncated) in chunks {
+            new_triangles.extend(triangles);
+            new_sources.extend(sources);
+            truncated |= chunk_truncated;
+        }
+    } else {
+        truncated = clip(indices, new_triangles, new_sources, clip_scratch);
+    }
+
+    // Drop triangles behind the nearest surfaces
+    if let Some(occlusion) = occlusion {
+        retain_triangles(new_triangles, new_sources, |_, triangle| occlusion.is_visible(triangle));
+    }
+
+    // Drop triangles not connected to the anchor, see SprayOptions::connected
+    if let Some(anchor) = anchor {
+        let connected = connected_triangles(new_triangles, decal_proj.transform_point3(anchor), decal_transform.scale);
+        retain_triangles(new_triangles, new_sources, |index, _| connected[index]);
+    }
+
+    // Clipping a triangle can add a few at once, and chunks are limited on their own
+    if new_triangles.len() > max_triangles {
+        new_triangles.truncate(max_triangles);
+        new_sources.truncate(max_triangles);
+        truncated = true;
+    }
+
+    if new_triangles.is_empty() {
+        return None
+    }
+
+    // Area weighted centroid, computed in world space so the projector scale doesn't skew it
+    let decal_matrix = decal_transform.compute_matrix();
+    let mut area_sum = 0.0;
+    let mut weighted_sum = Vec3::ZERO;
+    for triangle in new_triangles.iter() {
+        let a = decal_matrix.transform_point3(triangle.a.position);
+        let b = decal_matrix.transform_point3(triangle.b.position);
+        let c = decal_matrix.transform_point3(triangle.c.position);
+        let area = (b - a).cross(c - a).length() * 0.5;
+        area_sum += area;
+        weighted_sum += (a + b + c) / 3.0 * area;
+    }
+    let centroid = if area_sum > 0.0 {
+        weighted_sum / area_sum
+    } else {
+        decal_transform.translation
+    };
+    let mut positions = Vec::with_capacity(4096);
+    let mut normals = Vec::with_capacity(4096);
+    let mut uvs = Vec::with_capacity(4096);
+    let mut target_uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut joint_indices = Vec::new();
+    let mut joint_weights = Vec::new();
+    let mut morph_deltas = vec![Vec::new(); morph_targets.map_or(0, |morph_targets| morph_targets.deltas.len())];
+    let mut indices = Vec::with_capacity(4096);
+    let mut index: u32 = 0;
+    let mut welded: HashMap<[i32; 10], u32> = HashMap::new();
+
+    // Fading needs vertex colors even if the target has none
+    let write_colors = color_attribute.is_some() || options.edge_fade > 0. || options.depth_fade.is_some() || options.angle_fade.is_some()
+        || options.shape.is_curved();
+
+    // Morph deltas are in the local space of the target, so they need the same transform as the output vertices
+    let local_to_decal = decal_proj * mesh_transform.compute_matrix();
+    let local_to_decal_normal = decal_normal_matrix * mesh_normal_matrix;
+
+    // The other side of a decal vertex, see SprayOptions::two_sided. Undoing the projector scale
+    // gives the direction of the world space offset, which is scaled like any position then.
+    let flip = |vertex: Vertex| -> Vertex {
+        let attributes = vertex.payload;
+        let world_normal = (attributes.normal / projector_scale).normalize_or_zero();
+        let displacement = world_normal / projector_scale * offset;
+        return Vertex::new(vertex.position - displacement * 2., VertexAttributes {
+            normal: -attributes.normal,
+            local_position: attributes.local_position - attributes.local_normal.normalize_or_zero() * offset * 2.,
+            local_normal: -attributes.local_normal,
+            ..attributes
+        });
+    };
+
+    for (triangle, source) in new_triangles.iter().zip(new_sources.iter()) {
+        let front = if reverse_winding { [triangle.a, triangle.c, triangle.b] } else { [triangle.a, triangle.b, triangle.c] };
+        let back = options.two_sided.then(|| [flip(front[0]), flip(front[2]), flip(front[1])]);
+        let sides = std::iter::once((front, false)).chain(back.map(|back| (back, true)));
+
+        let center = (triangle.a.position + triangle.b.position + triangle.c.position) / 3.;
+        for (corners, is_back) in sides {
+            // UVs always come from the projector space position
+            let corner_uvs = corners.map(|vertex| decal_uv(vertex.position, center, options));
+            let keys = settings.weld_vertices.then(|| [0, 1, 2].map(|corner| weld_key(&corners[corner], corner_uvs[corner])));
+            // Welding two corners of a sliver together would leave it without any area
+            if keys.is_some_and(|[a, b, c]| a == b || b == c || c == a) {
+                continue;
+            }
+
+            for (corner, vertex) in corners.into_iter().enumerate() {
+                let uv = corner_uvs[corner];
+                if let Some(keys) = &keys {
+                    if let Some(existing) = welded.get(&keys[corner]) {
+                        indices.push(*existing);
+                        continue;
+                    }
+                    welded.insert(keys[corner], index);
+                }
+
+                if let Some(morph_targets) = morph_targets {
+                    for (target, deltas) in morph_deltas.iter_mut().enumerate() {
+                        let mut delta = morph_targets.interpolate(target, *source, vertex.payload.barycentric);
+                        if is_back {
+                            delta.normal = -delta.normal;
+                        }
+                        deltas.push(if skin.is_some() {
+                            delta
+                        } else {
+                            MorphAttributes::new(
+                                local_to_decal.transform_vector3(delta.position),
+                                local_to_decal_normal * delta.normal,
+                                local_to_decal.transform_vector3(delta.tangent),
+                            )
+                        });
+                    }
+                }
+
+                uvs.push(uv);
+                if uv_attribute.is_some() {
+                    target_uvs.push(vertex.payload.uv);
+                }
+                if write_colors {
+                    let mut color = vertex.payload.color;
+                    color.w *= decal_fade(&vertex, projector_scale, options);
+                    colors.push(color.to_array());
+                }
+
+                if skin.is_some() {
+                    positions.push(vertex.payload.local_position);
+                    normals.push(vertex.payload.local_normal.normalize_or_zero());
+                    joint_indices.push(vertex.payload.joints.indices);
+                    joint_weights.push(vertex.payload.joints.weights);
+                } else if options.projector_normals {
+                    // The decal entity is the projector, so its normals are scaled like the projector space ones
+                    let back = options.shape.back(vertex.position, projector_scale);
+                    let facing = (vertex.payload.normal / projector_scale).dot(back).signum();
+                    positions.push(vertex.position);
+                    normals.push((back * projector_scale).normalize_or_zero() * facing);
+                } else {
+                    positions.push(vertex.position);
+                    normals.push(vertex.payload.normal.normalize_or_zero());
+                }
+
+                indices.push(index);
+                index += 1;
+            }
+        }
+    }
+
+    if indices.is_empty() {
+        return None;
+    }
+    let triangles = indices.len() / 3;
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, settings.asset_usage)
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            positions
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            uvs,
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            normals,
+        )
+        .with_inserted_indices(decal_indices(indices));
+
+    if uv_attribute.is_some() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_1, target_uvs);
+    }
+
+    if write_colors {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+
+    if skin.is_some() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_INDEX, VertexAttributeValues::Uint16x4(joint_indices));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
+    }
+
+    if settings.generate_tangents {
+        if let Err(error) = mesh.generate_tangents() {
+            warn!("Failed to generate decal tangents: {error}");
+        }
+    }
+
+    let vertex_count = index as usize;
+    let morph_targets = morph_targets.and_then(|_| {
+        MorphTargetImage::new(
+            morph_deltas.into_iter().map(|deltas| deltas.into_iter()),
+            vertex_count,
+            settings.asset_usage,
+        ).ok()
+    }).map(|image| image.0);
+
+    return Some(DecalGeometry { mesh, morph_targets, triangles, centroid, truncated })
+}
+
+// A box in world space, possibly sheared by non-uniform scale up the hierarchy
+struct WorldBox {
+    center: Vec3,
+    half_axes: [Vec3; 3],
+}
+
+fn world_box(center: Vec3, half_extents: Vec3, transform: &Mat4) -> WorldBox {
+    return WorldBox {
+        center: transform.transform_point3(center),
+        half_axes: [
+            transform.transform_vector3(Vec3::X * half_extents.x),
+            transform.transform_vector3(Vec3::Y * half_extents.y),
+            transform.transform_vector3(Vec3::Z * half_extents.z),
+        ],
+    };
+}
+
+impl WorldBox {
+    // Axis aligned bounds, as min and max corners
+    fn aabb(&self) -> (Vec3, Vec3) {
+        let extents = self.half_axes.iter().map(|half_axis| half_axis.abs()).sum::<Vec3>();
+        return (self.center - extents, self.center + extents);
+    }
+
+    // Separating axis test, using face normals so sheared boxes are handled too
+    fn intersects(&self, other: &WorldBox) -> bool {
+        let [a0, a1, a2] = self.half_axes;
+        let [b0, b1, b2] = other.half_axes;
+        let offset = other.center - self.center;
+
+        let separated = |axis: Vec3| -> bool {
+            if axis.length_squared() < f32::EPSILON {
+                return false;
+            }
+            let radius = |half_axes: &[Vec3; 3]| half_axes.iter().map(|half_axis| half_axis.dot(axis).abs()).sum::<f32>();
+            return offset.dot(axis).abs() > radius(&self.half_axes) + radius(&other.half_axes);
+        };
+
+        let faces = [a1.cross(a2), a2.cross(a0), a0.cross(a1), b1.cross(b2), b2.cross(b0), b0.cross(b1)];
+        if faces.iter().any(|axis| separated(*axis)) {
+            return false;
+        }
+
+        for a in self.half_axes {
+            for b in other.half_axes {
+                if separated(a.cross(b)) {
+                    return false;
+                }
+            }
+        }
+
+        return true;
+    }
+}
+
+// U16 indices unless there are too many vertices for them, e.g. when two sided decals double those of a large spray
+fn decal_indices(indices: Vec<u32>) -> Indices {
+    if indices.iter().all(|index| *index <= u16::MAX as u32) {
+        return Indices::U16(indices.into_iter().map(|index| index as u16).collect());
+    }
+    return Indices::U32(indices);
+}
+
+// Whether apply_decal can handle the mesh without panicking
+fn is_supported_mesh(mesh: &Mesh) -> bool {
+    return mesh.primitive_topology() == PrimitiveTopology::TriangleList
+        && matches!(mesh.attribute(Mesh::ATTRIBUTE_POSITION), Some(VertexAttributeValues::Float32x3(_)))
+        && matches!(mesh.attribute(Mesh::ATTRIBUTE_NORMAL), Some(VertexAttributeValues::Float32x3(_)))
+        && matches!(mesh.indices(), Some(Indices::U16(_)));
+}
+
+// Alpha of a projector space vertex, see SprayOptions::edge_fade, SprayOptions::depth_fade and SprayOptions::angle_fade
+fn decal_fade(vertex: &Vertex, projector_scale: Vec3, options: &SprayOptions) -> f32 {
+    let position = vertex.position;
+    let mut fade = 1.;
+    let border = options.shape.border(position);
+    if options.edge_fade > 0. {
+        fade *= (border / options.edge_fade).clamp(0., 1.);
+    } else if border < 0. {
+        // Only vertices of curved shapes lie beyond the border, as they are clipped to their bounding box
+        fade = 0.;
+    }
+    if let Some(depth_fade) = &options.depth_fade {
+        // Projector space z runs from 1 at the near end to -1 at the far end
+        let depth = (1. - position.z) * 0.5;
+        fade *= fade_out(depth, depth_fade);
+    }
+    if let Some(angle_fade) = &options.angle_fade {
+        // Same as measuring the world space normal against the projector, undoing the projector scale
+        let back = options.shape.back(position, projector_scale);
+        let facing = (vertex.payload.normal / projector_scale).normalize_or_zero().dot(back).abs();
+        let angle = facing.min(1.).acos();
+        fade *= fade_out(angle, angle_fade);
+    }
+    return fade;
+}
+
+// 1 before the range, falling linearly to 0 at its end
+fn fade_out(value: f32, range: &Range<f32>) -> f32 {
+    if range.end > range.start {
+        return ((range.end - value) / (range.end - range.start)).clamp(0., 1.);
+    }
+    return if value < range.start { 1. } else { 0. };
+}
+
+// Map a projector space position to the decal texture
+fn decal_uv(position: Vec3, center: Vec3, options: &SprayOptions) -> Vec2 {
+    // Rotating the texture counter-clockwise means rotating the sampling position clockwise
+    let position = Vec2::from_angle(-options.uv_rotation).rotate(options.shape.texture_position(position, center));
+    let mut uv = Vec2::new(position.x*0.5+0.5, position.y*0.5+0.5);
+
+    if options.flip_x {
+        uv.x = 1. - uv.x;
+    }
+    if options.flip_y {
+        uv.y = 1. - uv.y;
+    }
+
+    return match options.uv_rect {
+        Some(rect) => rect.min + uv * rect.size(),
+        None => uv,
+    };
+}
+
+// Whether the entity has all the components required by the spray, and none of the forbidden ones
+fn matches_component_filter(
+    entity: Entity,
+    options: &SprayOptions,
+    entities: &Entities,
+    archetypes: &Archetypes,
+    components: &Components,
+) -> bool {
+    if options.with_components.is_empty() && options.without_components.is_empty() {
+        return true;
+    }
+
+    let Some(location) = entities.get(entity) else {
+        return false;
+    };
+    let archetype = &archetypes[location.archetype_id];
+    let has_component = |type_id: &TypeId| {
+        components.get_id(*type_id).is_some_and(|component_id| archetype.contains(component_id))
+    };
+
+    return options.with_components.iter().all(has_component)
+        && !options.without_components.iter().any(has_component);
+}
+
+// Inverse transpose of the linear part of a matrix, used to transform normals
+fn normal_matrix(matrix: Mat4) -> Mat3 {
+    return Mat3::from_mat4(matrix).inverse().transpose();
+}
+
+// World space joint matrices of a skin, premultiplied by their inverse bindposes.
+// Returns None when the bindposes aren't loaded yet or a joint is missing.
+fn joint_matrices(
+    skinned_mesh: &SkinnedMesh,
+    inverse_bindposes: &Assets<SkinnedMeshInverseBindposes>,
+    joints: &Query<&GlobalTransform>,
+) -> Option<Vec<Mat4>> {
+    let inverse_bindposes = inverse_bindposes.get(&skinned_mesh.inverse_bindposes)?;
+    return skinned_mesh.joints.iter()
+        .zip(inverse_bindposes.iter())
+        .map(|(joint, inverse_bindpose)| {
+            joints.get(*joint).ok().map(|transform| transform.compute_matrix() * *inverse_bindpose)
+        })
+        .collect();
+}
+
+
+// Everything needed to apply a single spray, shared by decal_system and spray_decal_immediate
+#[derive(SystemParam)]
+struct DecalApplication<'w, 's> {
+    commands: Commands<'w, 's>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    images: ResMut<'w, Assets<Image>>,
+    inverse_bindposes: Res<'w, Assets<SkinnedMeshInverseBindposes>>,
+    applied: EventWriter<'w, DecalAppliedEvent>,
+    failed: EventWriter<'w, DecalFailedEvent>,
+    triangle_limits: EventWriter<'w, DecalTriangleLimitEvent>,
+    models: Query<'w, 's, (Entity, &'static Mesh3d, &'static GlobalTransform, &'static mut Decalable, Option<&'static DecalLayers>, Option<&'static SkinnedMesh>, Option<&'static MeshMorphWeights>, Option<&'static Aabb>), Without<DecalBlocked>>,
+    joints: Query<'w, 's, &'static GlobalTransform>,
+    entities: &'w Entities,
+    archetypes: &'w Archetypes,
+    components: &'w Components,
+    merged: Query<'w, 's, (&'static mut DecalMerge, &'static Transform, &'static Mesh3d)>,
+    decal_meshes: Query<'w, 's, &'static Mesh3d, With<Decal>>,
+    mesh_pool: ResMut<'w, DecalMeshPool>,
+    stats: ResMut<'w, DecalStats>,
+    index: Res<'w, DecalSpatialIndex>,
+    vertex_cache: ResMut<'w, DecalVertexCache>,
+    triangle_bvhs: ResMut<'w, DecalTriangleBvhs>,
+    restored_layers: Query<'w, 's, &'static RestoredLayer>,
+    sources: Query<'w, 's, &'static DecalSource>,
+    bake_targets: Query<'w, 's, &'static DecalBakeTarget>,
+    bake_queue: ResMut<'w, DecalBakeQueue>,
+    parents: Query<'w, 's, &'static Parent>,
+    groups: Query<'w, 's, &'static mut DecalableGroup>,
+    frame: Option<Res<'w, FrameCount>>,
+    headless: Option<Res<'w, DecalHeadless>>,
+    warned_failures: Local<'s, HashSet<DecalFailureReason>>,
+    scratch: Local<'s, DecalScratch>,
+}
+
+impl DecalApplication<'_, '_> {
+    // Applies the spray to every matching target, despawns the spray entity and returns the spawned decals
+    fn apply_spray<M: Material>(&mut self, decal_entity: Entity, decal: &SprayDecal<M>, settings: &DecalSettings) -> Vec<Entity> {
+        return self.apply_sprays(&[(decal_entity, decal)], settings).pop().unwrap_or_default();
+    }
+
+    // Applies all sprays target by target, so every target mesh is looked up and its skin and morph
+    // targets are decoded only once. Sprays reach each target in order, so counts and offsets
+    // advance per decal exactly like applying them one by one. Returns the decals of each spray.
+    fn apply_sprays<M: Material>(&mut self, sprays: &[(Entity, &SprayDecal<M>)], settings: &DecalSettings) -> Vec<Vec<Entity>> {
+        self.stats.sprays += sprays.len();
+
+        let all_models: Vec<Entity> = if !settings.spatial_index && sprays.iter().any(|(_, decal)| decal.options.targets.is_none()) {
+            self.models.iter().map(|(entity, ..)| entity).collect()
+        } else {
+            Vec::new()
+        };
+
+        // Entities that may receive each decal, either the explicit targets or every Decalable near the projector
+        let candidates: Vec<Vec<Entity>> = sprays.iter()
+            .map(|(_, decal)| {
+                let mut targets = match &decal.options.targets {
+                    Some(targets) => targets.clone(),
+                    None if settings.spatial_index => {
+                        let (min, max) = world_box(Vec3::ZERO, Vec3::ONE, &decal.transform.compute_matrix()).aabb();
+                        self.index.query(min, max)
+                    }
+                    None => all_models.clone(),
+                };
+                targets.sort_unstable();
+                targets.dedup();
+                targets
+            })
+            .collect();
+
+        // Nearest surfaces of sprays with occlusion, over every target they may reach
+        let occlusion: Vec<Option<DepthGrid>> = sprays.iter().zip(candidates.iter())
+            .map(|((_, decal), candidates)| {
+                let mut grid = DepthGrid::new(&decal.options.occlusion?, &decal.transform);
+                let projector_inverse = decal.transform.compute_matrix().inverse();
+                for candidate in candidates.iter().filter(|candidate| !decal.options.excluded.contains(candidate)) {
+                    let Ok((_, mesh, global_transform, ..)) = self.models.get(*candidate) else {
+                        continue;
+                    };
+                    if let Some(mesh) = self.meshes.get(mesh) {
+                        grid.rasterize(mesh, projector_inverse * global_transform.compute_matrix());
+                    }
+                }
+                return Some(grid);
+            })
+            .collect();
+
+        // Target and anchor of connected sprays, see SprayOptions::connected
+        let anchors: Vec<Option<(Entity, Vec3)>> = sprays.iter().zip(candidates.iter())
+            .map(|((_, decal), candidates)| {
+                if !decal.options.connected {
+                    return None;
+                }
+                let candidates: Vec<(Entity, &Mesh, Mat4)> = candidates.iter()
+                    .filter(|candidate| !decal.options.excluded.contains(candidate))
+                    .filter_map(|candidate| {
+                        let (entity, mesh, global_transform, ..) = self.models.get(*candidate).ok()?;
+                        return Some((entity, self.meshes.get(mesh)?, global_transform.compute_matrix()));
+                    })
+                    .collect();
+                return find_anchor(&candidates, &decal.transform, decal.options.anchor);
+            })
+            .collect();
+
+        let shared_layers = self.shared_layers(sprays, &candidates, &anchors);
+        let refused_groups = self.refused_groups(sprays, &candidates, &anchors, settings);
+        let mut group_decals: Vec<(usize, Entity, Entity, Entity)> = Vec::new();
+
+        let mut targets: Vec<Entity> = candidates.iter().flatten().copied().collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        let mut outcomes: Vec<SprayOutcome> = sprays.iter().map(|_| SprayOutcome::default()).collect();
+
+        for target in targets {
+            // Members of a group leave the limit to it, see record_group_sprays
+            let group = if self.bake_targets.contains(target) { None } else { self.group_of(target) };
+            let Ok((model_entity, model_mesh, global_transform, mut decalable, layers, skinned_mesh, morph_weights, aabb)) = self.models.get_mut(target) else {
+                continue;
+            };
+            let layers = layers.copied().unwrap_or_default();
+            let bake_target = self.bake_targets.get(model_entity).ok();
+
+            // The bounds of skinned and morphed meshes don't cover their current pose
+            let bounds = aabb.filter(|_| skinned_mesh.is_none() && morph_weights.is_none())
+                .map(|aabb| world_box(Vec3::from(aabb.center), Vec3::from(aabb.half_extents), &global_transform.compute_matrix()));
+
+            // GlobalTransform already includes the local transform of the model
+            let mesh_transform = global_transform.compute_transform();
+            let mesh = self.meshes.get(model_mesh);
+            let mut decoded: Option<(Option<Vec<Mat4>>, Option<MorphTargets>)> = None;
+            let limit = if group.is_some() { usize::MAX } else { decalable.max_decals.unwrap_or(settings.max_decals_per_entity) };
+            let limit_mode = decalable.limit_mode.unwrap_or(settings.limit_mode);
+            let triangle_limit = if group.is_some() { None } else { decalable.triangle_limit(settings) };
+            let mut slots = decalable.decals.clone();
+            let mut evicted = Vec::new();
+            let mut geometries = Vec::new();
+            let mut pending = Vec::new();
+            let mut merged_in_batch: Vec<(Entity, MergeKey)> = Vec::new();
+
+            for (index, (_, decal)) in sprays.iter().enumerate() {
+                if candidates[index].binary_search(&model_entity).is_err() || decal.options.excluded.contains(&model_entity) {
+                    continue;
+                }
+
+                if !layers.intersects(&decal.options.layers) {
+                    continue;
+                }
+
+                // Connected sprays only reach the target nearest to their anchor
+                if decal.options.connected && anchors[index].map(|(target, _)| target) != Some(model_entity) {
+                    continue;
+                }
+
+                if !matches_component_filter(model_entity, &decal.options, self.entities, self.archetypes, self.components) {
+                    continue;
+                }
+
+                // Baked decals take no room on the target
+                let full = bake_target.is_none() && match triangle_limit {
+                    Some(max_triangles) => slot_triangles(&slots) >= max_triangles,
+                    None => slots.len() >= limit,
+                };
+                if (full && limit_mode == DecalLimitMode::Refuse) || group.is_some_and(|group| refused_groups[index].contains(&group)) {
+                    outcomes[index].full = true;
+                    continue;
+                }
+
+                // Skip far away targets before touching any vertex data
+                if let Some(bounds) = &bounds {
+                    if !bounds.intersects(&world_box(Vec3::ZERO, Vec3::ONE, &decal.transform.compute_matrix())) {
+                        continue;
+                    }
+                }
+
+                let Some(mesh) = mesh else {
+                    outcomes[index].mesh_unavailable = true;
+                    continue;
+                };
+
+                if !is_supported_mesh(mesh) {
+                    outcomes[index].unsupported_mesh = true;
+                    continue;
+                }
+
+                // Drawn into the texture of the target instead of spawning a decal, see DecalBakeTarget
+                if let Some(bake_target) = bake_target {
+                    if !matches!(mesh.attribute(Mesh::ATTRIBUTE_UV_0), Some(VertexAttributeValues::Float32x2(_))) {
+                        outcomes[index].unsupported_mesh = true;
+                        continue;
+                    }
+
+                    let bake_settings = DecalSettings { copy_target_uvs: true, ..settings.clone() };
+                    let bake_options = SprayOptions { two_sided: false, ..decal.options.clone() };
+                    let bvh = if settings.triangle_bvh { self.triangle_bvhs.get(model_mesh.id(), mesh) } else { None };
+                    let Some(geometry) = apply_decal(mesh, &mesh_transform, &decal.transform, 0., None, None, &bake_settings, &bake_options, None, bvh.as_deref(), occlusion[index].as_ref(), anchors[index].map(|(_, anchor)| anchor), &mut self.scratch) else {
+                        continue;
+                    };
+                    if geometry.truncated {
+                        report_triangle_limit(&mut self.triangle_limits, SprayId(sprays[index].0), model_entity, &decal.options, settings);
+                        if !geometry.is_allowed(settings) {
+                            outcomes[index].too_many_triangles = true;
+                            continue;
+                        }
+                    }
+
+                    if let Some(triangles) = bake_triangles(&geometry.mesh) {
+                        self.bake_queue.0.push(PendingBake {
+                            spray: SprayId(sprays[index].0),
+                            target: model_entity,
+                            texture: bake_target.texture.clone(),
+                            material: decal.material.clone().untyped(),
+                            triangles,
+                        });
+                        outcomes[index].baked = true;
+                    }
+                    continue;
+                }
+
+                let (joint_matrices, morph_targets) = decoded.get_or_insert_with(|| (
+                    skinned_mesh.and_then(|skinned_mesh| joint_matrices(skinned_mesh, &self.inverse_bindposes, &self.joints)),
+                    mesh.morph_targets()
+                        .and_then(|image| self.images.get(image))
+                        .and_then(|image| MorphTargets::from_image(
+                            image,
+                            morph_weights.map(|weights| weights.weights()).unwrap_or_default(),
+                            mesh.count_vertices(),
+                        )),
+                ));
+
+                // Oldest decals that have to make room, only evicted once the new decal actually hits.
+                // How many triangles have to make room is only known once the decal is projected.
+                let evict = match triangle_limit {
+                    Some(_) => 0,
+                    None => (slots.len() + 1).saturating_sub(limit).min(slots.len()),
+                };
+                let layer = match (self.restored_layers.get(sprays[index].0), shared_layers[index]) {
+                    (Ok(restored), _) => restored.0,
+                    // Unless a decal of the same batch that wasn't expected to get there took it in the meantime
+                    (Err(_), Some(shared)) => (shared..).find(|layer| slots[evict..].iter().all(|slot| slot.layer != *layer)).unwrap(),
+                    (Err(_), None) => free_layer(&slots[evict..]),
+                };
+
+                let offset = match settings.offset_mode {
+                    DecalOffsetMode::Geometric => layer as f32 * decal.options.offset.unwrap_or(settings.offset),
+                    // The material is biased instead, see vary_decal_materials
+                    DecalOffsetMode::DepthBias { .. } => 0.,
+                };
+
+                let bvh = if settings.triangle_bvh && joint_matrices.is_none() && morph_targets.is_none() {
+                    self.triangle_bvhs.get(model_mesh.id(), mesh)
+                } else {
+                    None
+                };
+
+                if decal.options.asynchronous {
+                    // Reserve the slot and layer now, so sprays in the meantime stack on top
+                    evicted.extend(slots.drain(..evict).map(|slot| slot.decal));
+                    let applied_decal = self.commands.spawn_empty().id();
+                    slots.push(DecalSlot { decal: applied_decal, layer, triangles: 0 });
+
+                    // Snapshot everything the projection needs, the task can't access the world
+                    let projection = DecalProjection {
+                        mesh: mesh.clone(),
+                        mesh_transform,
+                        projector: decal.transform,
+                        offset,
+                        joint_matrices: joint_matrices.clone(),
+                        morph_targets: morph_targets.clone(),
+                        settings: settings.clone(),
+                        options: decal.options.clone(),
+                        bvh: bvh.clone(),
+                        occlusion: occlusion[index].clone(),
+                        anchor: anchors[index].map(|(_, anchor)| anchor),
+                    };
+
+                    self.commands.entity(applied_decal).insert(PendingDecal {
+                        projection: PendingProjection::start(projection, settings),
+                        target: model_entity,
+                        projected_from: *global_transform,
+                        spray: SprayId(sprays[index].0),
+                        layer,
+                        decal: (*decal).clone(),
+                        morph_target_names: mesh.morph_target_names().map(|names| names.to_vec()),
+                        morph_weights: morph_targets.as_ref().map(|morph_targets| morph_targets.weights.clone()),
+                    });
+                    pending.push(applied_decal);
+                    outcomes[index].decals.push(applied_decal);
+                    if let Some(group) = group {
+                        group_decals.push((index, group, model_entity, applied_decal));
+                    }
+                    continue;
+                }
+
+                // Static targets reuse their world space vertices across sprays, see DecalSettings::vertex_cache_bytes
+                let world_vertices = if settings.vertex_cache_bytes > 0 && joint_matrices.is_none() && morph_targets.is_none() {
+                    self.vertex_cache.get(model_mesh.id(), model_entity, mesh, mesh_transform.compute_matrix(), settings.vertex_cache_bytes)
+                } else {
+                    None
+                };
+
+                if let Some(geometry) = apply_decal(mesh, &mesh_transform, &decal.transform, offset, joint_matrices.as_deref(), morph_targets.as_ref(), settings, &decal.options, world_vertices, bvh.as_deref(), occlusion[index].as_ref(), anchors[index].map(|(_, anchor)| anchor), &mut self.scratch) {
+                    if geometry.truncated {
+                        report_triangle_limit(&mut self.triangle_limits, SprayId(sprays[index].0), model_entity, &decal.options, settings);
+                        if !geometry.is_allowed(settings) {
+                            outcomes[index].too_many_triangles = true;
+                            continue;
+                        }
+                    }
+
+                    let evict = match triangle_limit {
+                        Some(max_triangles) => match triangle_eviction(&slots, geometry.triangles, max_triangles, limit_mode) {
+                            Some(evict) => evict,
+                            None => {
+                                outcomes[index].full = true;
+                                continue;
+                            }
+                        },
+                        None => evict,
+                    };
+                    evicted.extend(slots.drain(..evict).map(|slot| slot.decal));
+
+                    // Static decals join the newest decal of the same kind on the target, see DecalSettings::merge_decals
+                    let merge = settings.merge_decals && geometry.is_static() && decal.options.lifetime.is_none() && group.is_none();
+                    let key = MergeKey::new(decal, &geometry.mesh, settings);
+                    let merge_into = slots.iter().rev()
+                        .map(|slot| slot.decal)
+                        .find(|candidate| merge && (
+                            merged_in_batch.contains(&(*candidate, key))
+                                || self.merged.get(*candidate).is_ok_and(|(merged, ..)| merged.key == key)
+                        ));
+
+                    let applied_decal = merge_into.unwrap_or_else(|| self.commands.spawn_empty().id());
+                    if merge && merge_into.is_none() {
+                        merged_in_batch.push((applied_decal, key));
+                    }
+                    slots.push(DecalSlot { decal: applied_decal, layer, triangles: geometry.triangles });
+                    geometries.push((index, applied_decal, layer, geometry));
+                }
+            }
+
+            if geometries.is_empty() && pending.is_empty() {
+                continue;
+            }
+
+            let morph_target_names = mesh.and_then(|mesh| mesh.morph_target_names()).map(|names| names.to_vec());
+            let target_morph_weights = decoded.and_then(|(_, morph_targets)| morph_targets).map(|morph_targets| morph_targets.weights);
+
+            decalable.decals = slots;
+            let global_transform = *global_transform;
+            let skinned_mesh = skinned_mesh.cloned();
+
+            // Merged decals are rebuilt once per target, including those only losing parts to eviction
+            let mut merges: Vec<(Entity, Vec<(usize, usize, DecalGeometry)>)> = Vec::new();
+            for decal in evicted.iter() {
+                if self.merged.contains(*decal) && !merges.iter().any(|(merged, _)| merged == decal) {
+                    merges.push((*decal, Vec::new()));
+                }
+            }
+
+            for (index, applied_decal, layer, geometry) in geometries {
+                if settings.merge_decals && geometry.is_static() && sprays[index].1.options.lifetime.is_none() && group.is_none() {
+                    match merges.iter_mut().find(|(merged, _)| *merged == applied_decal) {
+                        Some((_, parts)) => parts.push((index, layer, geometry)),
+                        None => merges.push((applied_decal, vec![(index, layer, geometry)])),
+                    }
+                    continue;
+                }
+
+                // Replaced by a later spray of the same batch, despawned below
+                if evicted.contains(&applied_decal) {
+                    continue;
+                }
+
+                let (decal_entity, decal) = sprays[index];
+                self.spawn_decal(DecalSpawn {
+                    decal: applied_decal,
+                    target: model_entity,
+                    projected_from: global_transform,
+                    current: global_transform,
+                    skinned_mesh: skinned_mesh.clone(),
+                    spray: SprayId(decal_entity),
+                    spray_decal: decal,
+                    layer,
+                    geometry,
+                    morph_target_names: morph_target_names.clone(),
+                    morph_weights: target_morph_weights.clone(),
+                    settings,
+                });
+                outcomes[index].decals.push(applied_decal);
+                if let Some(group) = group {
+                    group_decals.push((index, group, model_entity, applied_decal));
+                }
+            }
+
+            let merged_decals: Vec<Entity> = merges.iter().map(|(decal, _)| *decal).collect();
+            for (decal, parts) in merges {
+                let evicted_parts = evicted.iter().filter(|evicted| **evicted == decal).count();
+                for index in self.merge_decal(decal, model_entity, global_transform, evicted_parts, parts, sprays, settings) {
+                    outcomes[index].decals.push(decal);
+                }
+            }
+
+            for decal in evicted {
+                // Merged decals only lose the evicted parts, see merge_decal
+                if merged_decals.contains(&decal) {
+                    continue;
+                }
+
+                // Evicting a pending decal drops its task, which cancels it
+                if pending.contains(&decal) {
+                    for outcome in outcomes.iter_mut() {
+                        outcome.decals.retain(|applied| *applied != decal);
+                    }
+                }
+                self.despawn_decal(decal, settings);
+            }
+        }
+
+        // Sprays past the limit of a group only make room once they hit, like on a single target
+        let evicted = self.record_group_sprays(sprays, group_decals, settings);
+        for outcome in outcomes.iter_mut() {
+            outcome.decals.retain(|decal| !evicted.contains(decal));
+        }
+
+        for ((decal_entity, _), outcome) in sprays.iter().zip(outcomes.iter()) {
+            if outcome.decals.is_empty() && !outcome.baked {
+                self.fail(*decal_entity, outcome.failure_reason());
+            }
+
+            self.commands.entity(*decal_entity).despawn();
+        }
+
+        return outcomes.into_iter().map(|outcome| outcome.decals).collect();
+    }
+
+    // Sprays that may reach several targets take the same layer on all of them, the lowest one free on
+    // every one, so the parts of the decal on each target are coplanar and line up along the seams.
+    // Layers are reserved in spray order, the same order the decals stack on the targets in apply_sprays.
+    fn shared_layers<M: Material>(&self, sprays: &[(Entity, &SprayDecal<M>)], candidates: &[Vec<Entity>], anchors: &[Option<(Entity, Vec3)>]) -> Vec<Option<usize>> {
+        let mut taken: HashMap<Entity, Vec<usize>> = HashMap::new();
+        let mut shared_layers = Vec::with_capacity(sprays.len());
+
+        for (index, (_, decal)) in sprays.iter().enumerate() {
+            let reached: Vec<Entity> = candidates[index].iter().copied()
+                .filter(|candidate| self.may_reach(decal, *candidate, anchors[index]))
+                .collect();
+            for target in reached.iter() {
+                taken.entry(*target).or_insert_with(|| self.models.get(*target).unwrap().3.decals.iter().map(|slot| slot.layer).collect());
+            }
+
+            let layer = (1..).find(|layer| reached.iter().all(|target| !taken[target].contains(layer))).unwrap();
+            for target in reached.iter() {
+                taken.get_mut(target).unwrap().push(layer);
+            }
+            shared_layers.push(Some(layer).filter(|_| reached.len() > 1));
+        }
+        return shared_layers;
+    }
+
+    // The same checks as apply_sprays before projecting, so the decal may or may not hit the candidate
+    fn may_reach<M: Material>(&self, decal: &SprayDecal<M>, candidate: Entity, anchor: Option<(Entity, Vec3)>) -> bool {
+        let Ok((entity, mesh, global_transform, _, layers, skinned_mesh, morph_weights, aabb)) = self.models.get(candidate) else {
+            return false;
+        };
+        if decal.options.excluded.contains(&entity) || !layers.copied().unwrap_or_default().intersects(&decal.options.layers) {
+            return false;
+        }
+        if decal.options.connected && anchor.map(|(target, _)| target) != Some(entity) {
+            return false;
+        }
+        if self.bake_targets.contains(entity) || !matches_component_filter(entity, &decal.options, self.entities, self.archetypes, self.components) {
+            return false;
+        }
+        if let Some(aabb) = aabb.filter(|_| skinned_mesh.is_none() && morph_weights.is_none()) {
+            let bounds = world_box(Vec3::from(aabb.center), Vec3::from(aabb.half_extents), &global_transform.compute_matrix());
+            if !bounds.intersects(&world_box(Vec3::ZERO, Vec3::ONE, &decal.transform.compute_matrix())) {
+                return false;
+            }
+        }
+        return self.meshes.get(mesh).is_some_and(is_supported_mesh);
+    }
+
+    // The nearest DecalableGroup the target belongs to, the target itself included
+    fn group_of(&self, target: Entity) -> Option<Entity> {
+        return std::iter::once(target)
+            .chain(self.parents.iter_ancestors(target))
+            .find(|entity| self.groups.contains(*entity));
+    }
+
+    // Groups refusing each spray, see DecalableGroup. Sprays are let in by the sprays already on the group
+    // and the earlier sprays of the batch that may reach it, each counting once whichever meshes it hits.
+    fn refused_groups<M: Material>(&mut self, sprays: &[(Entity, &SprayDecal<M>)], candidates: &[Vec<Entity>], anchors: &[Option<(Entity, Vec3)>], settings: &DecalSettings) -> Vec<Vec<Entity>> {
+        let mut counts: HashMap<Entity, usize> = HashMap::new();
+        let mut refused_groups = Vec::with_capacity(sprays.len());
+
+        for (index, (_, decal)) in sprays.iter().enumerate() {
+            let mut reached: Vec<Entity> = candidates[index].iter()
+                .filter(|candidate| self.may_reach(decal, **candidate, anchors[index]))
+                .filter_map(|candidate| self.group_of(*candidate))
+                .collect();
+            reached.sort_unstable();
+            reached.dedup();
+
+            let mut refused = Vec::new();
+            for group_entity in reached {
+                let mut group = self.groups.get_mut(group_entity).unwrap();
+                let count = counts.entry(group_entity).or_insert_with(|| {
+                    group.prune(self.entities);
+                    group.count()
+                });
+                let limit = group.max_decals.unwrap_or(settings.max_decals_per_entity);
+                if *count >= limit && group.limit_mode.unwrap_or(settings.limit_mode) == DecalLimitMode::Refuse {
+                    refused.push(group_entity);
+                } else {
+                    *count += 1;
+                }
+            }
+            refused_groups.push(refused);
+        }
+        return refused_groups;
+    }
+
+    // Adds the decals of the batch to their groups in spray order, and despawns the decals of the
+    // oldest sprays on groups past their limit. Returns the evicted decals.
+    fn record_group_sprays<M: Material>(&mut self, sprays: &[(Entity, &SprayDecal<M>)], mut group_decals: Vec<(usize, Entity, Entity, Entity)>, settings: &DecalSettings) -> Vec<Entity> {
+        group_decals.sort_by_key(|(index, ..)| *index);
+        let mut touched: Vec<Entity> = Vec::new();
+        for (index, group_entity, target, decal) in group_decals {
+            let Ok(mut group) = self.groups.get_mut(group_entity) else {
+                continue;
+            };
+            let spray = SprayId(sprays[index].0);
+            match group.sprays.iter_mut().find(|group_spray| group_spray.spray == spray) {
+                Some(group_spray) => group_spray.decals.push((decal, target)),
+                None => group.sprays.push(GroupSpray { spray, decals: vec![(decal, target)] }),
+            }
+            if !touched.contains(&group_entity) {
+                touched.push(group_entity);
+            }
+        }
+
+        let mut evicted = Vec::new();
+        for group_entity in touched {
+            let mut group = self.groups.get_mut(group_entity).unwrap();
+            let limit = group.max_decals.unwrap_or(settings.max_decals_per_entity);
+            let excess = group.sprays.len().saturating_sub(limit);
+            evicted.extend(group.sprays.drain(..excess).flat_map(|group_spray| group_spray.decals));
+        }
+        for (decal, target) in evicted.iter().copied() {
+            // Pending decals aren't Decals yet, so their slots aren't freed by despawning them
+            self.commands.queue(move |world: &mut World| {
+                if let Some(mut decalable) = world.get_mut::<Decalable>(target) {
+                    decalable.remove_decal(decal);
+                }
+            });
+            self.despawn_decal(decal, settings);
+        }
+        return evicted.into_iter().map(|(decal, _)| decal).collect();
+    }
+
+    // Reports a spray without any decal, the spray entity is left to the caller
+    fn fail(&mut self, decal_entity: Entity, reason: DecalFailureReason) {
+        if self.warned_failures.insert(reason) {
+            warn!("A decal spray didn't result in any decal: {reason}. Further sprays failing for this reason are not logged.");
+        }
+        self.failed.send(DecalFailedEvent { spray: SprayId(decal_entity), reason });
+    }
+}
+
+impl DecalApplication<'_, '_> {
+    // Turns a projected geometry into the decal entity, reserved beforehand with spawn_empty
+    fn spawn_decal<M: Material>(&mut self, spawn: DecalSpawn<M>) {
+        let DecalSpawn { decal, target, projected_from, current, skinned_mesh, spray, spray_decal, layer, geometry, morph_target_names, morph_weights, settings } = spawn;
+        let mut mesh = geometry.mesh;
+
+        // Rigidly attached decals leave skinning behind, see SprayOptions::attach_to_joint
+        let joint = match (&skinned_mesh, &geometry.morph_targets) {
+            (Some(skinned_mesh), None) if spray_decal.options.attach_to_joint => self.attach_to_joint(&mut mesh, skinned_mesh),
+            _ => None,
+        };
+
+        // Skinned decals are emitted in the bind space of the target and deformed by its joints
+        let skinned = mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_some();
+
+        // Morphed decals deform with the weights of the target, see sync_decal_morph_weights
+        let decal_morph_weights = geometry.morph_targets.map(|image| {
+            mesh.set_morph_targets(self.images.add(image));
+            if let Some(names) = morph_target_names {
+                mesh.set_morph_target_names(names);
+            }
+            MeshMorphWeights::new(morph_weights.unwrap()).unwrap()
+        });
+
+        let triangles = DecalTriangles::from_mesh(&mesh);
+
+        // Inverse matrices to make it work with Bevy's transform propagation, relative to
+        // where the target was when projected, so the decal follows it if it moved since
+        let transform = if skinned || joint.is_some() {
+            Transform::IDENTITY
+        } else {
+            Transform::from_matrix(projected_from.compute_matrix().inverse() * spray_decal.transform.compute_matrix())
+        };
+
+        let (parent, parent_transform) = match joint {
+            Some((joint, joint_transform)) => (joint, joint_transform),
+            None => (target, current),
+        };
+
+        // Propagation already ran this frame, so start out at the final world transform
+        let global_transform = parent_transform.mul_transform(transform);
+        let info = DecalInfo::new(&mesh, &triangles, global_transform.compute_matrix(), spray_decal.transform, self.frame_count());
+
+        let mesh = self.add_mesh(mesh);
+        self.commands.entity(decal).insert((
+            Mesh3d(mesh),
+            MeshMaterial3d(spray_decal.material.clone()),
+            transform,
+            global_transform,
+            Decal,
+            DecalSpray(spray),
+            DecalOf { target, spray, layer },
+            DecalSource::new(spray_decal, &projected_from),
+            spray_decal.options.group,
+            triangles,
+            info,
+        ));
+
+        self.insert_visuals(decal, &spray_decal.options, settings);
+
+        if let Some(tint) = spray_decal.options.tint {
+            self.commands.entity(decal).insert(DecalTintColor::new(tint));
+        }
+
+        if let Some(lifetime) = spray_decal.options.lifetime {
+            self.commands.entity(decal).insert(lifetime);
+        }
+
+        if spray_decal.options.budget_exempt {
+            self.commands.entity(decal).insert(DecalBudgetExempt);
+        }
+
+        if skinned {
+            self.commands.entity(decal).insert(skinned_mesh.unwrap());
+        }
+
+        if let Some(decal_morph_weights) = decal_morph_weights {
+            self.commands.entity(decal).insert(decal_morph_weights);
+        }
+
+        self.commands.entity(parent).add_child(decal);
+        self.notify_applied(spray, target, decal, geometry.triangles, geometry.centroid);
+    }
+
+    // Projects every decal of a target again with the spray it came from, replacing the decal
+    // meshes in place, see ReprojectOnMeshChange
+    fn reproject_decals(&mut self, target: Entity, settings: &DecalSettings) {
+        let Ok((_, model_mesh, global_transform, decalable, _, skinned_mesh, morph_weights, _)) = self.models.get(target) else {
+            return;
+        };
+        let Some(mesh) = self.meshes.get(model_mesh).filter(|mesh| is_supported_mesh(mesh)) else {
+            return;
+        };
+
+        let slots = decalable.decals.clone();
+        let global_transform = *global_transform;
+        let mesh_transform = global_transform.compute_transform();
+        let target_matrix = global_transform.compute_matrix();
+        let skinned_mesh = skinned_mesh.cloned();
+        let joint_matrices = skinned_mesh.as_ref().and_then(|skinned_mesh| joint_matrices(skinned_mesh, &self.inverse_bindposes, &self.joints));
+        let morph_targets = mesh.morph_targets()
+            .and_then(|image| self.images.get(image))
+            .and_then(|image| MorphTargets::from_image(image, morph_weights.map(|weights| weights.weights()).unwrap_or_default(), mesh.count_vertices()));
+        let morph_target_names = mesh.morph_target_names().map(|names| names.to_vec());
+
+        let scratch = &mut self.scratch;
+        let mut project = |source: &DecalSource, layer: usize| -> Option<DecalGeometry> {
+            let projector = global_transform.mul_transform(source.transform).compute_transform();
+            let offset = match settings.offset_mode {
+                DecalOffsetMode::Geometric => layer as f32 * source.options.offset.unwrap_or(settings.offset),
+                DecalOffsetMode::DepthBias { .. } => 0.,
+            };
+            let occlusion = source.options.occlusion.as_ref().map(|occlusion| {
+                let mut grid = DepthGrid::new(occlusion, &projector);
+                grid.rasterize(mesh, projector.compute_matrix().inverse() * target_matrix);
+                grid
+            });
+            let anchor = if source.options.connected {
+                find_anchor(&[(target, mesh, target_matrix)], &projector, source.options.anchor).map(|(_, anchor)| anchor)
+            } else {
+                None
+            };
+            return apply_decal(mesh, &mesh_transform, &projector, offset, joint_matrices.as_deref(), morph_targets.as_ref(), settings, &source.options, None, None, occlusion.as_ref(), anchor, &mut *scratch)
+                .filter(|geometry| geometry.is_allowed(settings));
+        };
+
+        let mut decals: Vec<(Entity, bool, Option<DecalGeometry>)> = Vec::new();
+        let mut merged: Vec<(Entity, Vec<Option<DecalGeometry>>)> = Vec::new();
+        for slot in slots.iter() {
+            if let Ok(source) = self.sources.get(slot.decal) {
+                decals.push((slot.decal, source.options.attach_to_joint, project(source, slot.layer)));
+            } else if let Ok((merge, ..)) = self.merged.get(slot.decal) {
+                // A merged decal takes one slot per spray
+                if !merged.iter().any(|(decal, _)| *decal == slot.decal) {
+                    merged.push((slot.decal, merge.parts.iter().map(|part| project(&part.source, part.layer)).collect()));
+                }
+            }
+        }
+
+        // Triangles of every reprojected decal, or of each part of a merged one
+        let mut reprojected: Vec<(Entity, Vec<usize>)> = Vec::new();
+
+        for (decal, attach_to_joint, geometry) in decals {
+            let decal_mesh = self.decal_meshes.get(decal).ok().map(|decal_mesh| decal_mesh.id());
+            let (Some(geometry), Some(decal_mesh)) = (geometry, decal_mesh) else {
+                self.despawn_decal(decal, settings);
+                continue;
+            };
+            reprojected.push((decal, vec![geometry.triangles]));
+            let mut mesh = geometry.mesh;
+
+            if let Some(image) = geometry.morph_targets {
+                mesh.set_morph_targets(self.images.add(image));
+                if let Some(names) = morph_target_names.clone() {
+                    mesh.set_morph_target_names(names);
+                }
+            }
+
+            // The joint influencing the decal most may have changed along with the mesh
+            let joint = match &skinned_mesh {
+                Some(skinned_mesh) if attach_to_joint && mesh.morph_targets().is_none() => self.attach_to_joint(&mut mesh, skinned_mesh),
+                _ => None,
+            };
+            match joint {
+                Some((joint, _)) => {
+                    self.commands.entity(decal).remove::<SkinnedMesh>().insert(Transform::IDENTITY);
+                    self.commands.entity(joint).add_child(decal);
+                }
+                None if mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_some() => {
+                    self.commands.entity(decal).insert((skinned_mesh.clone().unwrap(), Transform::IDENTITY));
+                    self.commands.entity(target).add_child(decal);
+                }
+                None => {}
+            }
+
+            self.replace_decal_mesh(decal, decal_mesh, mesh);
+        }
+
+        for (decal, geometries) in merged {
+            if geometries.iter().all(Option::is_none) {
+                self.despawn_decal(decal, settings);
+                continue;
+            }
+            let Ok((mut merge, transform, decal_mesh)) = self.merged.get_mut(decal) else {
+                continue;
+            };
+            let (decal_mesh, transform) = (decal_mesh.id(), *transform);
+
+            // Sprays without any triangle left keep their slot until they are evicted
+            reprojected.push((decal, geometries.iter().map(|geometry| geometry.as_ref().map_or(0, |geometry| geometry.triangles)).collect()));
+            for (part, geometry) in merge.parts.iter_mut().zip(geometries) {
+                part.mesh = match geometry {
+                    Some(geometry) => {
+                        let mut mesh = geometry.mesh;
+                        transform_decal_mesh(&mut mesh, transform.compute_matrix().inverse() * part.source.transform.compute_matrix());
+                        mesh
+                    }
+                    None => Mesh::new(PrimitiveTopology::TriangleList, settings.asset_usage),
+                };
+            }
+
+            let mesh = combine_meshes(&merge.parts, settings.asset_usage);
+            self.replace_decal_mesh(decal, decal_mesh, mesh);
+        }
+
+        // The parts of a merged decal are in the order of its slots
+        if let Ok((_, _, _, mut decalable, ..)) = self.models.get_mut(target) {
+            for (decal, triangles) in reprojected {
+                for (slot, triangles) in decalable.decals.iter_mut().filter(|slot| slot.decal == decal).zip(triangles) {
+                    slot.triangles = triangles;
+                }
+            }
+        }
+    }
+
+    // Overwrites the mesh asset of a decal, along with the bounds and triangles derived from it
+    fn replace_decal_mesh(&mut self, decal: Entity, decal_mesh: AssetId<Mesh>, mesh: Mesh) {
+        // Bevy only computes the bounds of new meshes
+        if let Some(aabb) = mesh.compute_aabb() {
+            self.commands.entity(decal).insert(aabb);
+        }
+        // The joints query holds the transform of every entity, decals included
+        let to_world = self.joints.get(decal).map_or(Mat4::IDENTITY, |transform| transform.compute_matrix());
+        let triangles = DecalTriangles::from_mesh(&mesh);
+        self.refresh_info(decal, &mesh, &triangles, to_world);
+        self.commands.entity(decal)
+            .remove::<DecalTriangles>()
+            .insert(triangles);
+        self.meshes.insert(decal_mesh, mesh);
+    }
+
+    // Moves a skinned decal mesh into the bind space of the joint with the most weight over its
+    // vertices, and drops its skinning. Returns the joint and its transform, or None if there is none.
+    fn attach_to_joint(&self, mesh: &mut Mesh, skinned_mesh: &SkinnedMesh) -> Option<(Entity, GlobalTransform)> {
+        let (Some(VertexAttributeValues::Uint16x4(joint_indices)), Some(VertexAttributeValues::Float32x4(joint_weights))) =
+            (mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX), mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)) else {
+            return None;
+        };
+
+        let mut totals: HashMap<u16, f32> = HashMap::new();
+        for (indices, weights) in joint_indices.iter().zip(joint_weights.iter()) {
+            for k in 0..4 {
+                if weights[k] > 0. {
+                    *totals.entry(indices[k]).or_insert(0.) += weights[k];
+                }
+            }
+        }
+        // Ties go to the lowest joint index, so the choice doesn't depend on the iteration order
+        let (joint_index, _) = totals.into_iter().max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?;
+
+        let joint = *skinned_mesh.joints.get(joint_index as usize)?;
+        let inverse_bindpose = *self.inverse_bindposes.get(&skinned_mesh.inverse_bindposes)?.get(joint_index as usize)?;
+        let joint_transform = *self.joints.get(joint).ok()?;
+
+        transform_decal_mesh(mesh, inverse_bindpose);
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_INDEX);
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT);
+        return Some((joint, joint_transform));
+    }
+
+    // Components only needed to render the decal, left out without rendering, see DecalPlugin.
+    // Visibility is required by Mesh3d either way.
+    fn insert_visuals(&mut self, decal: Entity, options: &SprayOptions, settings: &DecalSettings) {
+        if self.headless.is_some() {
+            return;
+        }
+        insert_decal_shadows(&mut self.commands.entity(decal), options, settings);
+    }
+
+    // Like the free function despawn_decal, for decals evicted while applying sprays
+    fn despawn_decal(&mut self, decal: Entity, settings: &DecalSettings) {
+        if let Ok(mesh) = self.decal_meshes.get(decal) {
+            self.mesh_pool.recycle(mesh.0.clone(), settings.mesh_pool_size);
+        }
+        self.commands.entity(decal).despawn_recursive();
+    }
+
+    // Adds a decal mesh, reusing a pooled one if possible, see DecalSettings::mesh_pool_size
+    fn add_mesh(&mut self, mesh: Mesh) -> Handle<Mesh> {
+        if let Some(pooled) = self.mesh_pool.take() {
+            self.meshes.insert(pooled.id(), mesh);
+            return pooled;
+        }
+        return self.meshes.add(mesh);
+    }
+
+    fn frame_count(&self) -> u32 {
+        return self.frame.as_ref().map_or(0, |frame| frame.0);
+    }
+
+    // Recomputes the statistics of a rebuilt decal, keeping its projector and frame
+    fn refresh_info(&mut self, decal: Entity, mesh: &Mesh, triangles: &DecalTriangles, to_world: Mat4) {
+        let refreshed = DecalInfo::new(mesh, triangles, to_world, Transform::IDENTITY, 0);
+        self.commands.entity(decal).queue(move |mut entity: EntityWorldMut| {
+            if let Some(mut info) = entity.get_mut::<DecalInfo>() {
+                *info = DecalInfo { projector: info.projector, frame_applied: info.frame_applied, ..refreshed };
+            }
+        });
+    }
+
+    fn notify_applied(&mut self, spray: SprayId, target: Entity, decal: Entity, triangles: usize, centroid: Vec3) {
+        self.stats.applied += 1;
+        self.commands.entity(decal).try_insert(DecalOrder(self.stats.applied));
+        self.commands.trigger_targets(OnDecalApplied { spray, decal, triangles, centroid }, target);
+        self.applied.send(DecalAppliedEvent { spray, target, decal, triangles, centroid });
+    }
+
+    // Rebuilds a merged decal after dropping its evicted parts, the oldest ones, and appending the new ones.
+    // Returns the sprays whose new parts made it into the decal.
+    fn merge_decal<M: Material>(
+        &mut self,
+        decal: Entity,
+        target: Entity,
+        target_transform: GlobalTransform,
+        evicted_parts: usize,
+        new_parts: Vec<(usize, usize, DecalGeometry)>,
+        sprays: &[(Entity, &SprayDecal<M>)],
+        settings: &DecalSettings,
+    ) -> Vec<usize> {
+        let existing = self.merged.get_mut(decal).ok()
+            .map(|(mut merged, transform, mesh)| (std::mem::take(&mut merged.parts), *transform, mesh.0.clone()));
+        let (mut parts, transform, mesh_handle) = match existing {
+            Some((parts, transform, mesh_handle)) => (parts, transform, Some(mesh_handle)),
+            // A new merged decal sits at the projector of its first spray, like any other decal
+            None => (Vec::new(), Transform::from_matrix(target_transform.compute_matrix().inverse() * sprays[new_parts[0].0].1.transform.compute_matrix()), None),
+        };
+
+        // New parts are moved from the space of their own projector into the one of the merged decal
+        let existing_parts = parts.len();
+        let to_decal = transform.compute_matrix().inverse() * target_transform.compute_matrix().inverse();
+        let mut applied = Vec::new();
+        for (index, layer, geometry) in new_parts {
+            let (decal_entity, spray_decal) = sprays[index];
+            let mut mesh = geometry.mesh;
+            transform_decal_mesh(&mut mesh, to_decal * spray_decal.transform.compute_matrix());
+            parts.push(MergedPart { spray: SprayId(decal_entity), layer, mesh, source: DecalSource::new(spray_decal, &target_transform) });
+            applied.push((index, geometry.triangles, geometry.centroid));
+        }
+
+        // New parts are only evicted by later sprays of the same batch
+        let removed = evicted_parts.min(parts.len());
+        parts.drain(..removed);
+        let applied: Vec<(usize, usize, Vec3)> = applied.into_iter().skip(removed.saturating_sub(existing_parts)).collect();
+
+        if parts.is_empty() {
+            self.despawn_decal(decal, settings);
+            return Vec::new();
+        }
+
+        let mesh = combine_meshes(&parts, settings.asset_usage);
+        let triangles = DecalTriangles::from_mesh(&mesh);
+        let (spray, layer) = (parts[0].spray, parts[0].layer);
+        let to_world = target_transform.mul_transform(transform).compute_matrix();
+        let info = applied.last().map(|(index, ..)| DecalInfo::new(&mesh, &triangles, to_world, sprays[*index].1.transform, self.frame_count()));
+
+        match mesh_handle {
+            Some(mesh_handle) => {
+                // Bevy only computes the bounds of new meshes
+                if let Some(aabb) = mesh.compute_aabb() {
+                    self.commands.entity(decal).insert(aabb);
+                }
+                match info {
+                    Some(info) => {
+                        self.commands.entity(decal).insert(info);
+                    }
+                    // Only lost parts to eviction
+                    None => self.refresh_info(decal, &mesh, &triangles, to_world),
+                }
+                self.meshes.insert(mesh_handle.id(), mesh);
+                self.commands.entity(decal)
+                    .remove::<DecalTriangles>()
+                    .insert((DecalSpray(spray), DecalOf { target, spray, layer }, triangles));
+                self.merged.get_mut(decal).unwrap().0.parts = parts;
+            }
+            None => {
+                let spray_decal = sprays[applied[0].0].1;
+                let key = MergeKey::new(spray_decal, &parts[0].mesh, settings);
+                let mesh = self.add_mesh(mesh);
+                self.commands.entity(decal).insert((
+                    Mesh3d(mesh),
+                    MeshMaterial3d(spray_decal.material.clone()),
+                    transform,
+                    target_transform.mul_transform(transform),
+                    Decal,
+                    DecalSpray(spray),
+                    DecalOf { target, spray, layer },
+                    spray_decal.options.group,
+                    triangles,
+                    info.unwrap(),
+                    DecalMerge { key, parts },
+                ));
+                self.insert_visuals(decal, &spray_decal.options, settings);
+                if let Some(tint) = spray_decal.options.tint {
+                    self.commands.entity(decal).insert(DecalTintColor::new(tint));
+                }
+                if spray_decal.options.budget_exempt {
+                    self.commands.entity(decal).insert(DecalBudgetExempt);
+                }
+                self.commands.entity(target).add_child(decal);
+            }
+        }
+
+        for (index, triangles, centroid) in applied.iter() {
+            self.notify_applied(SprayId(sprays[*index].0), target, decal, *triangles, *centroid);
+        }
+        return applied.into_iter().map(|(index, ..)| index).collect();
+    }
+}
+
+// Attributes a merged decal can hold, as generated by apply_decal for static targets
+const MERGED_ATTRIBUTES: [MeshVertexAttribute; 6] = [
+    Mesh::ATTRIBUTE_POSITION,
+    Mesh::ATTRIBUTE_NORMAL,
+    Mesh::ATTRIBUTE_UV_0,
+    Mesh::ATTRIBUTE_UV_1,
+    Mesh::ATTRIBUTE_COLOR,
+    Mesh::ATTRIBUTE_TANGENT,
+];
+
+// Static decals of the same material and group, sharing a single mesh on their target.
+// See DecalSettings::merge_decals
+#[derive(Component)]
+struct DecalMerge {
+    key: MergeKey,
+    parts: Vec<MergedPart>,     // Oldest first, like the slots of the target
+}
+
+// One spray of a merged decal, in the local space of the merged decal entity
+struct MergedPart {
+    spray: SprayId,
+    layer: usize,
+    mesh: Mesh,
+    source: DecalSource,
+}
+
+// Decals can only be merged if they render the same, and their meshes have the same attributes
+#[derive(Clone, Copy, PartialEq)]
+struct MergeKey {
+    material: UntypedAssetId,
+    group: DecalGroup,
+    attributes: u8,     // Bit mask of MERGED_ATTRIBUTES
+    shadows: (bool, bool),
+    tint: Option<[u8; 4]>,
+    budget_exempt: bool,
+}
+
+impl MergeKey {
+    fn new<M: Material>(decal: &SprayDecal<M>, mesh: &Mesh, settings: &DecalSettings) -> Self {
+        let attributes = MERGED_ATTRIBUTES.iter().enumerate()
+            .filter(|(_, attribute)| mesh.contains_attribute(attribute.id))
+            .fold(0, |mask, (bit, _)| mask | 1 << bit);
+
+        return MergeKey {
+            material: decal.material.id().untyped(),
+            group: decal.options.group,
+            attributes,
+            shadows: decal_shadows(&decal.options, settings),
+            tint: decal.options.tint.map(|tint| DecalTintColor::new(tint).0),
+            budget_exempt: decal.options.budget_exempt,
+        };
+    }
+}
+
+// Whether a decal casts and receives shadows
+fn decal_shadows(options: &SprayOptions, settings: &DecalSettings) -> (bool, bool) {
+    return (
+        options.cast_shadows.unwrap_or(settings.cast_shadows),
+        options.receive_shadows.unwrap_or(settings.receive_shadows),
+    );
+}
+
+fn insert_decal_shadows(decal: &mut EntityCommands, options: &SprayOptions, settings: &DecalSettings) {
+    let (cast_shadows, receive_shadows) = decal_shadows(options, settings);
+    if !cast_shadows {
+        decal.insert(NotShadowCaster);
+    }
+    if !receive_shadows {
+        decal.insert(NotShadowReceiver);
+    }
+}
+
+// Transforms the vertices of a decal mesh, normals and tangents included
+fn transform_decal_mesh(mesh: &mut Mesh, matrix: Mat4) {
+    if let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION) {
+        for position in positions.iter_mut() {
+            *position = matrix.transform_point3(Vec3::from(*position)).to_array();
+        }
+    }
+
+    let normal_matrix = normal_matrix(matrix);
+    if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL) {
+        for normal in normals.iter_mut() {
+            *normal = (normal_matrix * Vec3::from(*normal)).normalize_or_zero().to_array();
+        }
+    }
+
+    // Mirroring flips the handedness of the tangent frame and the winding of the triangles
+    let mirrored = matrix.determinant() < 0.;
+    if let Some(VertexAttributeValues::Float32x4(tangents)) = mesh.attribute_mut(Mesh::ATTRIBUTE_TANGENT) {
+        for tangent in tangents.iter_mut() {
+            let direction = matrix.transform_vector3(Vec3::new(tangent[0], tangent[1], tangent[2])).normalize_or_zero();
+            *tangent = direction.extend(if mirrored { -tangent[3] } else { tangent[3] }).to_array();
+        }
+    }
+
+    if mirrored {
+        match mesh.indices_mut() {
+            Some(Indices::U16(indices)) => indices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+            Some(Indices::U32(indices)) => indices.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+            None => {}
+        }
+    }
+}
+
+// Concatenates the meshes of the parts, with U32 indices since the total can exceed U16
+fn combine_meshes(parts: &[MergedPart], asset_usage: RenderAssetUsages) -> Mesh {
+    let mut combined = Mesh::new(PrimitiveTopology::TriangleList, asset_usage);
+
+    for attribute in MERGED_ATTRIBUTES {
+        let mut values: Option<VertexAttributeValues> = None;
+        for part in parts {
+            let Some(part_values) = part.mesh.attribute(attribute.id) else {
+                continue;
+            };
+            match (&mut values, part_values) {
+                (None, part_values) => values = Some(part_values.clone()),
+                (Some(VertexAttributeValues::Float32x2(values)), VertexAttributeValues::Float32x2(part_values)) => values.extend_from_slice(part_values),
+                (Some(VertexAttributeValues::Float32x3(values)), VertexAttributeValues::Float32x3(part_values)) => values.extend_from_slice(part_values),
+                (Some(VertexAttributeValues::Float32x4(values)), VertexAttributeValues::Float32x4(part_values)) => values.extend_from_slice(part_values),
+                _ => {}
+            }
+        }
+        if let Some(values) = values {
+            combined.insert_attribute(attribute, values);
+        }
+    }
+
+    let mut indices = Vec::new();
+    let mut first_vertex = 0;
+    for part in parts {
+        if let Some(part_indices) = part.mesh.indices() {
+            indices.extend(part_indices.iter().map(|index| first_vertex + index as u32));
+        }
+        first_vertex += part.mesh.count_vertices() as u32;
+    }
+    combined.insert_indices(Indices::U32(indices));
+
+    return combined;
+}
+
+// Everything needed to spawn a decal once its geometry is projected
+struct DecalSpawn<'a, M: Material> {
+    decal: Entity,
+    target: Entity,
+    projected_from: GlobalTransform,    // Transform of the target the geometry was projected with
+    current: GlobalTransform,           // Transform of the target this frame
+    skinned_mesh: Option<SkinnedMesh>,
+    spray: SprayId,
+    spray_decal: &'a SprayDecal<M>,
+    layer: usize,
+    geometry: DecalGeometry,
+    morph_target_names: Option<Vec<String>>,
+    morph_weights: Option<Vec<f32>>,
+    settings: &'a DecalSettings,
+}
+
+// Tint of a decal, see SprayOptions::tint
+#[derive(Component, Clone, Copy)]
+struct DecalTintColor([u8; 4]);
+
+impl DecalTintColor {
+    // Quantized to 8 bit sRGB, so similar colors share their material
+    fn new(tint: Color) -> Self {
+        return DecalTintColor(tint.to_srgba().to_u8_array());
+    }
+
+    fn color(&self) -> Color {
+        return Color::Srgba(Srgba::from_u8_array(self.0));
+    }
+}
+
+// Clones of sprayed materials with a tint, the alpha of a fade and the depth bias of a stacking layer,
+// see SprayOptions::tint, DecalLifetime::fade, DecalOffsetMode::DepthBias and DecalSettings::blend_depth_bias
+#[derive(Resource)]
+struct DecalMaterialVariants<M: Material> {
+    tint: Option<fn(&M, Color) -> M>,
+    depth_bias: Option<fn(&M, f32) -> M>,
+    fade: Option<fn(&M, f32) -> M>,
+    variants: HashMap<(AssetId<M>, Option<[u8; 4]>, u8, u32), Handle<M>>,   // By material, tint, 8 bit alpha and bits of the bias
+}
+
+impl<M: Material> DecalMaterialVariants<M> {
+    // The variant of `base`, cloned the first time it's asked for. Steps without a function are skipped.
+    fn variant(&mut self, materials: &mut Assets<M>, base: AssetId<M>, tint: Option<DecalTintColor>, alpha: u8, depth_bias: f32) -> Option<Handle<M>> {
+        // Keyed by the bias too, the settings may change at any time
+        let key = (base, tint.map(|tint| tint.0), alpha, depth_bias.to_bits());
+        if let Some(handle) = self.variants.get(&key) {
+            return Some(handle.clone());
+        }
+
+        let base_material = materials.get(base)?;
+        let mut variant = None;
+        if let (Some(tint), Some(tint_fn)) = (tint, self.tint) {
+            variant = Some(tint_fn(base_material, tint.color()));
+        }
+        if let Some(fade_fn) = self.fade.filter(|_| alpha != u8::MAX) {
+            variant = Some(fade_fn(variant.as_ref().unwrap_or(base_material), alpha as f32 / u8::MAX as f32));
+        }
+        if let Some(depth_bias_fn) = self.depth_bias.filter(|_| depth_bias != 0.) {
+            variant = Some(depth_bias_fn(variant.as_ref().unwrap_or(base_material), depth_bias));
+        }
+        let handle = materials.add(variant?);
+        self.variants.insert(key, handle.clone());
+        return Some(handle);
+    }
+}
+
+// Depth bias of a stacking layer, see DecalOffsetMode::DepthBias and DecalSettings::blend_depth_bias
+fn layer_depth_bias<M: Material>(settings: &DecalSettings, material: &M, layer: usize) -> f32 {
+    let per_layer = match settings.offset_mode {
+        DecalOffsetMode::DepthBias { per_layer } => per_layer,
+        DecalOffsetMode::Geometric if settings.blend_depth_bias => match material.alpha_mode() {
+            AlphaMode::Blend | AlphaMode::Premultiplied | AlphaMode::Add | AlphaMode::Multiply => DECAL_BLEND_BIAS,
+            _ => 0.,
+        },
+        DecalOffsetMode::Geometric => 0.,
+    };
+    return per_layer * layer as f32;
+}
+
+// Swaps the material of new decals for the variant with their tint and the bias of their layer
+fn vary_decal_materials<M: Material>(
+    settings: Res<DecalSettings>,
+    mut variants: Option<ResMut<DecalMaterialVariants<M>>>,
+    mut materials: ResMut<Assets<M>>,
+    mut decals: Query<(&DecalOf, Option<&DecalTintColor>, &mut MeshMaterial3d<M>), Added<DecalOf>>,
+    mut warned: Local<bool>,
+) {
+    if let Some(variants) = variants.as_mut() {
+        // Variants no decal uses anymore, only the cache holds on to them
+        variants.variants.retain(|_, handle| !matches!(handle, Handle::Strong(strong) if Arc::strong_count(strong) == 1));
+    }
+
+    for (decal_of, tint, mut material) in decals.iter_mut() {
+        let Some(base) = materials.get(&*material) else {
+            continue;
+        };
+        let depth_bias = layer_depth_bias(&settings, base, decal_of.layer);
+        if depth_bias == 0. && tint.is_none() {
+            continue;
+        }
+
+        // Blend sorting is best effort, the others change how decals look
+        let tint_fn = variants.as_ref().and_then(|variants| variants.tint);
+        let depth_bias_fn = variants.as_ref().and_then(|variants| variants.depth_bias);
+        let unsupported_bias = depth_bias_fn.is_none() && matches!(settings.offset_mode, DecalOffsetMode::DepthBias { .. });
+        if (tint.is_some() && tint_fn.is_none() || unsupported_bias) && !*warned {
+            warn!(
+                "Tinted decals need DecalPlugin::with_material_tint and DecalOffsetMode::DepthBias needs DecalPlugin::with_material_depth_bias, {} decals are sprayed without them.",
+                std::any::type_name::<M>(),
+            );
+            *warned = true;
+        }
+        let tint = tint.filter(|_| tint_fn.is_some()).copied();
+        let depth_bias = if depth_bias_fn.is_some() { depth_bias } else { 0. };
+        let Some(variants) = variants.as_mut().filter(|_| depth_bias != 0. || tint.is_some()) else {
+            continue;
+        };
+
+        if let Some(handle) = variants.variant(&mut materials, material.id(), tint, u8::MAX, depth_bias) {
+            material.0 = handle;
+        }
+    }
+}
+
+// Swaps the material of decals at the end of their lifetime for variants with less and less alpha.
+// Fading decals of the same material and layer share their variant for each 8 bit alpha step.
+fn fade_decals<M: Material>(
+    settings: Res<DecalSettings>,
+    mut variants: Option<ResMut<DecalMaterialVariants<M>>>,
+    mut materials: ResMut<Assets<M>>,
+    mut decals: Query<(&DecalLifetime, &DecalAge, &DecalOf, &DecalSource, Option<&DecalTintColor>, &mut MeshMaterial3d<M>)>,
+    mut warned: Local<bool>,
+) {
+    for (lifetime, age, decal_of, source, tint, mut material) in decals.iter_mut() {
+        let remaining = lifetime.duration.saturating_sub(age.0);
+        if remaining >= lifetime.fade {
+            continue;
+        }
+
+        let Some(variants) = variants.as_mut().filter(|variants| variants.fade.is_some()) else {
+            if !*warned {
+                warn!("Fading decals need DecalPlugin::with_material_fade, {} decals disappear at the end of their lifetime instead.", std::any::type_name::<M>());
+                *warned = true;
+            }
+            continue;
+        };
+        let Some(base) = source.material.try_typed::<M>().ok().filter(|base| materials.contains(*base)) else {
+            continue;
+        };
+
+        let alpha = (remaining.as_secs_f32() / lifetime.fade.as_secs_f32() * u8::MAX as f32).round() as u8;
+        let tint = tint.copied().filter(|_| variants.tint.is_some());
+        let depth_bias = if variants.depth_bias.is_some() { layer_depth_bias(&settings, materials.get(base).unwrap(), decal_of.layer) } else { 0. };
+        if let Some(handle) = variants.variant(&mut materials, base, tint, alpha, depth_bias) {
+            if material.0 != handle {
+                material.0 = handle;
+            }
+        }
+    }
+}
+
+// A decal projected asynchronously, see SprayOptions::asynchronous.
+// Lives on the reserved decal entity, so evicting or despawning it cancels the projection.
+#[derive(Component)]
+struct PendingDecal<M: Material> {
+    projection: PendingProjection,
+    target: Entity,
+    projected_from: GlobalTransform,
+    spray: SprayId,
+    layer: usize,
+    decal: SprayDecal<M>,
+    morph_target_names: Option<Vec<String>>,
+    morph_weights: Option<Vec<f32>>,
+}
+
+// Everything projecting an asynchronous decal needs, snapshotted when spraying
+struct DecalProjection {
+    mesh: Mesh,
+    mesh_transform: Transform,
+    projector: Transform,
+    offset: f32,
+    joint_matrices: Option<Vec<Mat4>>,
+    morph_targets: Option<MorphTargets>,
+    settings: DecalSettings,
+    options: SprayOptions,
+    bvh: Option<Arc<TriangleBvh>>,
+    occlusion: Option<DepthGrid>,
+    anchor: Option<Vec3>,
+}
+
+impl DecalProjection {
+    fn project(&self) -> Option<DecalGeometry> {
+        return apply_decal(
+            &self.mesh,
+            &self.mesh_transform,
+            &self.projector,
+            self.offset,
+            self.joint_matrices.as_deref(),
+            self.morph_targets.as_ref(),
+            &self.settings,
+            &self.options,
+            None,
+            self.bvh.as_deref(),
+            self.occlusion.as_ref(),
+            self.anchor,
+            &mut DecalScratch::default(),
+        );
+    }
+}
+
+// Where an asynchronous decal is projected, see DecalSettings::frame_sliced_async
+enum PendingProjection {
+    #[cfg(not(target_arch = "wasm32"))]
+    Task(Task<Option<DecalGeometry>>),
+    Sliced(Box<DecalProjection>),
+}
+
+impl PendingProjection {
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn start(projection: DecalProjection, settings: &DecalSettings) -> Self {
+        // The single threaded task pool of wasm32 can't hand back results, and waiting on
+        // them there would block the only thread
+        #[cfg(not(target_arch = "wasm32"))]
+        if !settings.frame_sliced_async {
+            return PendingProjection::Task(AsyncComputeTaskPool::get_or_init(TaskPool::default).spawn(async move {
+                return projection.project();
+            }));
+        }
+        return PendingProjection::Sliced(Box::new(projection));
+    }
+}
+
+// Reprojects the decals of marked targets whose mesh asset was modified, see ReprojectOnMeshChange
+fn reproject_modified_targets(
+    mut application: DecalApplication,
+    settings: Res<DecalSettings>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    targets: Query<(Entity, &Mesh3d), With<ReprojectOnMeshChange>>,
+) {
+    let modified: HashSet<AssetId<Mesh>> = mesh_events.read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    let settings = settings.clamped();
+    for (target, mesh) in targets.iter() {
+        if modified.contains(&mesh.id()) {
+            application.reproject_decals(target, &settings);
+        }
+    }
+}
+
+// Spawns the asynchronous decals whose projection finished
+fn poll_async_decals<M: Material>(
+    mut application: DecalApplication,
+    settings: Res<DecalSettings>,
+    mut pending: Query<(Entity, &mut PendingDecal<M>)>,
+) {
+    let start = Instant::now();
+    let mut sliced = 0;
+    for (entity, mut pending) in pending.iter_mut() {
+        let geometry = match &mut pending.projection {
+            #[cfg(not(target_arch = "wasm32"))]
+            PendingProjection::Task(task) => match block_on(future::poll_once(task)) {
+                Some(geometry) => geometry,
+                None => continue,
+            },
+            PendingProjection::Sliced(projection) => {
+                // At least one per frame, like sprays within the frame budget
+                if sliced > 0 && settings.frame_budget.is_none_or(|budget| start.elapsed() >= budget) {
+                    continue;
+                }
+                sliced += 1;
+                projection.project()
+            }
+        };
+        application.commands.entity(entity).remove::<PendingDecal<M>>();
+
+        let geometry = geometry.filter(|geometry| {
+            if geometry.truncated {
+                report_triangle_limit(&mut application.triangle_limits, pending.spray, pending.target, &pending.decal.options, &settings);
+            }
+            geometry.is_allowed(&settings)
+        });
+
+        let target = application.models.get(pending.target)
+            .map(|(_, _, global_transform, _, _, skinned_mesh, ..)| (*global_transform, skinned_mesh.cloned()));
+
+        match (geometry, target) {
+            (Some(geometry), Ok((current, skinned_mesh))) => {
+                // The slot was reserved before its triangles were known
+                if let Ok((_, _, _, mut decalable, ..)) = application.models.get_mut(pending.target) {
+                    if let Some(slot) = decalable.decals.iter_mut().find(|slot| slot.decal == entity) {
+                        slot.triangles = geometry.triangles;
+                    }
+                }
+                let pending = &*pending;
+                application.spawn_decal(DecalSpawn {
+                    decal: entity,
+                    target: pending.target,
+                    projected_from: pending.projected_from,
+                    current,
+                    skinned_mesh,
+                    spray: pending.spray,
+                    spray_decal: &pending.decal,
+                    layer: pending.layer,
+                    geometry,
+                    morph_target_names: pending.morph_target_names.clone(),
+                    morph_weights: pending.morph_weights.clone(),
+                    settings: &settings,
+                });
+            }
+            _ => {
+                // Empty projection, or the target can't take decals anymore. Frees the
+                // reserved slot, as the placeholder isn't a Decal yet and has no on_remove hook.
+                let target = pending.target;
+                application.commands.queue(move |world: &mut World| {
+                    if let Some(mut decalable) = world.get_mut::<Decalable>(target) {
+                        decalable.remove_decal(entity);
+                    }
+                });
+                application.commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+// Warns about a decal that reached its triangle limit. Not a method, as the target mesh is still borrowed then
+fn report_triangle_limit(
+    events: &mut EventWriter<DecalTriangleLimitEvent>,
+    spray: SprayId,
+    target: Entity,
+    options: &SprayOptions,
+    settings: &DecalSettings,
+) {
+    let max_triangles = options.max_triangles.or(settings.max_triangles_per_decal).unwrap_or(usize::MAX);
+    let policy = settings.triangle_limit_policy;
+    warn!("A decal on {target} reached its limit of {max_triangles} triangles and was {}, consider a smaller decal or a less dense mesh.", match policy {
+        TriangleLimitPolicy::Truncate => "cut off",
+        TriangleLimitPolicy::Drop => "dropped",
+    });
+    events.send(DecalTriangleLimitEvent { spray, target, max_triangles, policy });
+}
+
+// What happened to a single spray while applying a batch
+#[derive(Default)]
+struct SprayOutcome {
+    decals: Vec<Entity>,
+    baked: bool,    // Drawn into a DecalBakeTarget without any decal entity
+    full: bool,
+    mesh_unavailable: bool,
+    unsupported_mesh: bool,
+    too_many_triangles: bool,
+}
+
+impl SprayOutcome {
+    fn failure_reason(&self) -> DecalFailureReason {
+        return if self.full {
+            DecalFailureReason::AllTargetsFull
+        } else if self.mesh_unavailable {
+            DecalFailureReason::MeshUnavailable
+        } else if self.unsupported_mesh {
+            DecalFailureReason::UnsupportedMesh
+        } else if self.too_many_triangles {
+            DecalFailureReason::TooManyTriangles
+        } else {
+            DecalFailureReason::NoTargets
+        };
+    }
+}
+
+fn decal_system<M: Material>(
+    mut application: DecalApplication,
+    settings: Res<DecalSettings>,
+    mut events: EventReader<SprayDecalEvent<M>>,
+    decals: Query<(Entity, &ApplyingDecal<M>), Added<ApplyingDecal<M>>>,
+    mut queue: Local<VecDeque<(Entity, SprayDecal<M>)>>,
+    mut waiting: Local<Vec<(Entity, SprayDecal<M>)>>,
+    layouts: Option<Res<Assets<TextureAtlasLayout>>>,
+    asset_server: Option<Res<AssetServer>>,
+    mut warned_invalid_settings: Local<bool>,
+    #[cfg(feature = "debug")] projector_gizmos: Res<DecalProjectorGizmos>,
+    #[cfg(feature = "debug")] mut projector_log: ResMut<ProjectorGizmoLog>,
+) {
+    let start = Instant::now();
+
+    // Settings are read every frame, so changes at runtime apply to the following sprays
+    let settings = match settings.validate() {
+        Ok(()) => {
+            *warned_invalid_settings = false;
+            settings.clone()
+        }
+        Err(error) => {
+            if !*warned_invalid_settings {
+                warn!("Invalid DecalSettings, clamping to valid values: {error}");
+                *warned_invalid_settings = true;
+            }
+            settings.clamped()
+        }
+    };
+
+    // Events get a placeholder entity as their SprayId, so both paths behave exactly the same
+    let event_sprays: Vec<(Entity, SprayDecal<M>)> = events.read()
+        .map(|event| (
+            application.commands.spawn_empty().id(),
+            SprayDecal { material: event.material.clone(), transform: event.transform, options: event.options.clone() },
+        ))
+        .collect();
+
+    // Sprays waiting for the layout of their atlas were sprayed earlier, so they go first once it's loaded
+    let sprays = std::mem::take(&mut *waiting).into_iter()
+        .chain(decals.iter().map(|(entity, decal)| (entity, decal.0.clone())))
+        .chain(event_sprays);
+
+    // Sprays despawned while queued or waiting are cancelled
+    queue.retain(|(entity, _)| application.entities.contains(*entity));
+
+    let budgeted = settings.max_sprays_per_frame.is_some() || settings.frame_budget.is_some();
+    for (entity, mut decal) in sprays {
+        if !application.entities.contains(entity) {
+            continue;
+        }
+        match resolve_atlas(&mut decal.options, layouts.as_deref(), asset_server.as_deref()) {
+            AtlasLayoutState::Ready => {}
+            AtlasLayoutState::Loading => {
+                waiting.push((entity, decal));
+                continue;
+            }
+            AtlasLayoutState::Failed => {
+                application.fail(entity, DecalFailureReason::AtlasUnavailable);
+                application.commands.entity(entity).despawn();
+                continue;
+            }
+        }
+
+        if budgeted && queue.len() >= settings.max_queued_sprays {
+            application.fail(entity, DecalFailureReason::QueueFull);
+            application.commands.entity(entity).despawn();
+            continue;
+        }
+        queue.push_back((entity, decal));
+    }
+
+    let mut remaining = settings.max_sprays_per_frame.unwrap_or(usize::MAX);
+    while !queue.is_empty() && remaining > 0 {
+        // The time budget is checked between sprays, so those are applied one by one. Otherwise batched,
+        // so targets hit by several sprays in the same frame are only prepared once.
+        let count = if settings.frame_budget.is_some() { 1 } else { queue.len().min(remaining) };
+        let batch: Vec<(Entity, SprayDecal<M>)> = queue.drain(..count).collect();
+        let sprays: Vec<(Entity, &SprayDecal<M>)> = batch.iter().map(|(entity, decal)| (*entity, decal)).collect();
+        application.apply_sprays(&sprays, &settings);
+
+        #[cfg(feature = "debug")]
+        if projector_gizmos.enabled {
+            for (_, decal) in batch.iter() {
+                projector_log.applied(decal.transform, decal.options.shape, projector_gizmos.frames);
+            }
+        }
+
+        remaining -= count;
+        if settings.frame_budget.is_some_and(|frame_budget| start.elapsed() >= frame_budget) {
+            break;
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    if projector_gizmos.enabled {
+        for (_, decal) in queue.iter().chain(waiting.iter()) {
+            projector_log.queued(decal.transform, decal.options.shape);
+        }
+    } else {
+        projector_log.clear();
+    }
+
+    application.stats.apply_time += start.elapsed();
+}
+
+/// Paths of the decal [`Diagnostic`]s registered by the [`DecalPlugin`], printed
+/// by the `LogDiagnosticsPlugin` along with the others.
+///
+/// # Example:
+///
+/// ```
+/// fn show_decal_count(diagnostics: Res<DiagnosticsStore>) {
+///     if let Some(decals) = diagnostics.get(&DecalDiagnostics::DECALS).and_then(|decals| decals.value()) {
+///         info!("{decals} decals");
+///     }
+/// }
+/// ```
+pub struct DecalDiagnostics;
+
+impl DecalDiagnostics {
+    /// Sprays applied this frame, over all materials.
+    pub const SPRAYS: DiagnosticPath = DiagnosticPath::const_new("decal/sprays");
+    /// Decal entities alive.
+    pub const DECALS: DiagnosticPath = DiagnosticPath::const_new("decal/decals");
+    /// Triangles of all decals alive.
+    pub const TRIANGLES: DiagnosticPath = DiagnosticPath::const_new("decal/triangles");
+    /// Time spent applying sprays this frame, in milliseconds.
+    pub const APPLY_TIME: DiagnosticPath = DiagnosticPath::const_new("decal/apply_time");
+}
+
+// Running totals behind DecalDiagnostics. Decals and triangles are kept up to date by the
+// hooks of Decal and DecalTriangles, the per frame values are reset after being recorded.
+#[derive(Resource, Default)]
+struct DecalStats {
+    sprays: usize,
+    decals: usize,
+    triangles: usize,
+    apply_time: Duration,
+    applied: u64,   // Decals applied to so far, never reset, see DecalOrder
+}
+
+fn record_decal_diagnostics(mut stats: ResMut<DecalStats>, mut diagnostics: Diagnostics) {
+    diagnostics.add_measurement(&DecalDiagnostics::SPRAYS, || stats.sprays as f64);
+    diagnostics.add_measurement(&DecalDiagnostics::DECALS, || stats.decals as f64);
+    diagnostics.add_measurement(&DecalDiagnostics::TRIANGLES, || stats.triangles as f64);
+    diagnostics.add_measurement(&DecalDiagnostics::APPLY_TIME, || stats.apply_time.as_secs_f64() * 1000.);
+
+    stats.sprays = 0;
+    stats.apply_time = Duration::ZERO;
+}
+
+// Removes the triangles and their sources that keep rejects, keeping the order of the rest
+fn retain_triangles(triangles: &mut Vec<Triangle>, sources: &mut Vec<[usize; 3]>, mut keep: impl FnMut(usize, &Triangle) -> bool) {
+    let mut kept = 0;
+    for index in 0..triangles.len() {
+        if keep(index, &triangles[index]) {
+            triangles.swap(kept, index);
+            sources.swap(kept, index);
+            kept += 1;
+        }
+    }
+    triangles.truncate(kept);
+    sources.truncate(kept);
+}
+
+// Calls f with the corners of every triangle of the mesh, transformed by the matrix
+fn for_each_triangle(mesh: &Mesh, matrix: Mat4, mut f: impl FnMut(Vec3, Vec3, Vec3)) {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return;
+    };
+    let Some(indices) = mesh.indices() else {
+        return;
+    };
+
+    let indices: Vec<usize> = indices.iter().collect();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|corner| matrix.transform_point3(Vec3::from(positions[triangle[corner]])));
+        f(a, b, c);
+    }
+}
+
+// The target a connected spray reaches and the point it's anchored to, see SprayOptions::anchor.
+// Targets are in their current pose, skinned and morphed targets in their bind pose.
+fn find_anchor(candidates: &[(Entity, &Mesh, Mat4)], projector: &Transform, anchor: Option<Vec3>) -> Option<(Entity, Vec3)> {
+    if anchor.is_none() {
+        // First hit along the center line, from the near to the far end of the projector
+        let origin = projector.transform_point(Vec3::Z);
+        let direction = projector.transform_point(Vec3::NEG_Z) - origin;
+        let mut nearest: Option<(Entity, f32)> = None;
+        for (entity, mesh, matrix) in candidates.iter() {
+            for_each_triangle(mesh, *matrix, |a, b, c| {
+                if let Some(t) = ray_triangle(origin, direction, a, b, c) {
+                    if t <= 1. && nearest.is_none_or(|(_, nearest)| t < nearest) {
+                        nearest = Some((*entity, t));
+                    }
+                }
+            });
+        }
+        if let Some((entity, t)) = nearest {
+            return Some((entity, origin + direction * t));
+        }
+    }
+
+    // Surface nearest to the anchor, or to the center if the center line missed
+    let anchor = anchor.unwrap_or(projector.translation);
+    let mut nearest: Option<(Entity, f32)> = None;
+    for (entity, mesh, matrix) in candidates.iter() {
+        for_each_triangle(mesh, *matrix, |a, b, c| {
+            let distance = closest_point_on_triangle(anchor, [a, b, c]).distance_squared(anchor);
+            if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+                nearest = Some((*entity, distance));
+            }
+        });
+    }
+    return nearest.map(|(entity, _)| (entity, anchor));
+}
+
+// Distance along the ray to the triangle in multiples of direction, hitting either side
+fn ray_triangle(origin: Vec3, direction: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let to_origin = origin - a;
+    let u = to_origin.dot(p) / determinant;
+    let q = to_origin.cross(ab);
+    let v = direction.dot(q) / determinant;
+    if u < 0. || v < 0. || u + v > 1. {
+        return None;
+    }
+
+    let t = ac.dot(q) / determinant;
+    return (t >= 0.).then_some(t);
+}
+
+// Which clipped triangles are connected to the one nearest to the projector space anchor,
+// by a flood fill over vertices closer than DECAL_CONNECT_EPSILON. Distances are measured
+// with the projector scale applied, so the epsilon is in world units.
+fn connected_triangles(triangles: &[Triangle], anchor: Vec3, projector_scale: Vec3) -> Vec<bool> {
+    let mut connected = vec![false; triangles.len()];
+    let corners = |triangle: &Triangle| [triangle.a.position, triangle.b.position, triangle.c.position].map(|corner| corner * projector_scale);
+    let cell = |position: Vec3| (position / DECAL_CONNECT_EPSILON).floor().as_ivec3();
+
+    let anchor = anchor * projector_scale;
+    let Some(start) = (0..triangles.len()).min_by(|a, b| {
+        let [a0, a1, a2] = corners(&triangles[*a]);
+        let [b0, b1, b2] = corners(&triangles[*b]);
+        return closest_point_on_triangle(anchor, [a0, a1, a2]).distance_squared(anchor)
+            .total_cmp(&closest_point_on_triangle(anchor, [b0, b1, b2]).distance_squared(anchor));
+    }) else {
+        return connected;
+    };
+
+    // Positional hash of every corner, so triangles clipped independently find their neighbors
+    let mut vertices: HashMap<IVec3, Vec<(Vec3, usize)>> = HashMap::new();
+    for (index, triangle) in triangles.iter().enumerate() {
+        for corner in corners(triangle) {
+            vertices.entry(cell(corner)).or_default().push((corner, index));
+        }
+    }
+
+    let mut queue = VecDeque::from([start]);
+    connected[start] = true;
+    while let Some(index) = queue.pop_front() {
+        for corner in corners(&triangles[index]) {
+            // Corners within the epsilon may lie in any neighboring cell
+            let center = cell(corner);
+            for offset in (0..27).map(|i| IVec3::new(i % 3 - 1, i / 3 % 3 - 1, i / 9 - 1)) {
+                let Some(neighbors) = vertices.get(&(center + offset)) else {
+                    continue;
+                };
+                for (position, neighbor) in neighbors.iter() {
+                    if !connected[*neighbor] && position.distance_squared(corner) <= DECAL_CONNECT_EPSILON * DECAL_CONNECT_EPSILON {
+                        connected[*neighbor] = true;
+                        queue.push_back(*neighbor);
+                    }
+                }
+            }
+        }
+    }
+    return connected;
+}
+
+// Depth of the surface nearest to the projector per cell of a grid over the projection,
+// see SprayOptions::occlusion. Depths are projector space z, which grows toward the projector.
+#[derive(Clone)]
+struct DepthGrid {
+    resolution: usize,
+    depths: Vec<f32>,
+    tolerance: f32,     // Projector space
+}
+
+impl DepthGrid {
+    fn new(occlusion: &DecalOcclusion, projector: &Transform) -> Self {
+        let resolution = occlusion.resolution.max(1);
+        return DepthGrid {
+            resolution,
+            depths: vec![f32::NEG_INFINITY; resolution * resolution],
+            tolerance: occlusion.tolerance / projector.scale.z.abs().max(f32::EPSILON),
+        };
+    }
+
+    // Adds every triangle of the mesh, given the matrix from its local space to projector space
+    fn rasterize(&mut self, mesh: &Mesh, mesh_to_projector: Mat4) {
+        let (resolution, depths) = (self.resolution, &mut self.depths);
+        for_each_triangle(mesh, mesh_to_projector, |a, b, c| {
+            DepthGrid::samples(resolution, a, b, c, |cell, depth| {
+                if (-1. ..=1.).contains(&depth) {
+                    depths[cell] = depths[cell].max(depth);
+                }
+            });
+        });
+    }
+
+    // Whether any part of the triangle is within the tolerance of the nearest surface
+    fn is_visible(&self, triangle: &Triangle) -> bool {
+        let mut visible = false;
+        DepthGrid::samples(self.resolution, triangle.a.position, triangle.b.position, triangle.c.position, |cell, depth| {
+            visible |= depth >= self.depths[cell] - self.tolerance;
+        });
+        return visible;
+    }
+
+    // Calls f with the cell and the depth at every cell center covered by the triangle,
+    // and at its centroid, so small triangles are sampled at least once
+    fn samples(resolution: usize, a: Vec3, b: Vec3, c: Vec3, mut f: impl FnMut(usize, f32)) {
+        let (min, max) = (a.min(b).min(c), a.max(b).max(c));
+        if max.x < -1. || min.x > 1. || max.y < -1. || min.y > 1. {
+            return;
+        }
+
+        let cell_of = |value: f32| (((value * 0.5 + 0.5) * resolution as f32).floor() as i64).clamp(0, resolution as i64 - 1) as usize;
+        let centroid = (a + b + c) / 3.;
+        if centroid.x.abs() <= 1. && centroid.y.abs() <= 1. {
+            f(cell_of(centroid.y) * resolution + cell_of(centroid.x), centroid.z);
+        }
+
+        let (a2, b2, c2) = (a.truncate(), b.truncate(), c.truncate());
+        let area = (b2 - a2).perp_dot(c2 - a2);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        for y in cell_of(min.y)..=cell_of(max.y) {
+            for x in cell_of(min.x)..=cell_of(max.x) {
+                let center = (Vec2::new(x as f32, y as f32) + 0.5) / resolution as f32 * 2. - 1.;
+                let weight_b = (center - a2).perp_dot(c2 - a2) / area;
+                let weight_c = (b2 - a2).perp_dot(center - a2) / area;
+                let weight_a = 1. - weight_b - weight_c;
+                if weight_a >= 0. && weight_b >= 0. && weight_c >= 0. {
+                    f(y * resolution + x, weight_a * a.z + weight_b * b.z + weight_c * c.z);
+                }
+            }
+        }
+    }
+}
+
+// World space positions and normals of a static target
+struct WorldVertices {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+}
+
+impl WorldVertices {
+    fn new(mesh: &Mesh, mesh_matrix: Mat4) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return None;
+        };
+        let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) else {
+            return None;
+        };
+
+        let normal_matrix = normal_matrix(mesh_matrix);
+        return Some(WorldVertices {
+            positions: positions.iter().map(|position| mesh_matrix.transform_point3(Vec3::from(*position))).collect(),
+            normals: normals.iter().map(|normal| (normal_matrix * Vec3::from(*normal)).normalize_or_zero()).collect(),
+        });
+    }
+
+    fn bytes(&self) -> usize {
+        return (self.positions.len() + self.normals.len()) * std::mem::size_of::<Vec3>();
+    }
+}
+
+struct CachedVertices {
+    vertices: WorldVertices,
+    mesh_matrix: Mat4,  // Transform of the target the vertices were computed with
+    last_used: u64,
+}
+
+// World space vertices of recently sprayed static targets, see DecalSettings::vertex_cache_bytes
+#[derive(Resource, Default)]
+struct DecalVertexCache {
+    entries: HashMap<(AssetId<Mesh>, Entity), CachedVertices>,
+    bytes: usize,
+    tick: u64,
+}
+
+impl DecalVertexCache {
+    // Cached vertices of the target, decoded first if missing or stale.
+    // None if they don't fit into max_bytes at all.
+    fn get(&mut self, mesh_id: AssetId<Mesh>, entity: Entity, mesh: &Mesh, mesh_matrix: Mat4, max_bytes: usize) -> Option<&WorldVertices> {
+        self.tick += 1;
+        let tick = self.tick;
+        let key = (mesh_id, entity);
+
+        let fresh = self.entries.get(&key).is_some_and(|entry| entry.mesh_matrix.abs_diff_eq(mesh_matrix, DECAL_CACHE_EPSILON));
+        if !fresh {
+            self.remove(&key);
+            let vertices = WorldVertices::new(mesh, mesh_matrix)?;
+            let bytes = vertices.bytes();
+            if bytes > max_bytes {
+                return None;
+            }
+
+            // Least recently used first
+            while self.bytes + bytes > max_bytes {
+                let Some(oldest) = self.entries.iter().min_by_key(|(_, entry)| entry.last_used).map(|(key, _)| *key) else {
+                    break;
+                };
+                self.remove(&oldest);
+            }
+
+            self.bytes += bytes;
+            self.entries.insert(key, CachedVertices { vertices, mesh_matrix, last_used: tick });
+        }
+
+        let entry = self.entries.get_mut(&key)?;
+        entry.last_used = tick;
+        return Some(&entry.vertices);
+    }
+
+    fn remove(&mut self, key: &(AssetId<Mesh>, Entity)) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.bytes -= entry.vertices.bytes();
+        }
+    }
+
+    fn retain(&mut self, keep: impl Fn(&(AssetId<Mesh>, Entity)) -> bool) {
+        let removed: Vec<(AssetId<Mesh>, Entity)> = self.entries.keys().filter(|key| !keep(key)).copied().collect();
+        for key in removed.iter() {
+            self.remove(key);
+        }
+    }
+}
+
+// Drops cached vertices of changed meshes and of targets that stopped being Decalable
+fn invalidate_vertex_cache(
+    mut cache: ResMut<DecalVertexCache>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut removed: RemovedComponents<Decalable>,
+) {
+    if cache.entries.is_empty() {
+        mesh_events.clear();
+        removed.clear();
+        return;
+    }
+
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            cache.retain(|(mesh_id, _)| mesh_id != id);
+        }
+    }
+    for entity in removed.read() {
+        cache.retain(|(_, cached)| *cached != entity);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    first: u32,     // First triangle of a leaf, or the second child of an inner node
+    count: u32,     // Triangles of a leaf, 0 for inner nodes whose first child follows them
+}
+
+// Bounding volume hierarchy over the triangles of a mesh in its local space, see DecalSettings::triangle_bvh
+struct TriangleBvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<u32>,
+}
+
+impl TriangleBvh {
+    fn new(mesh: &Mesh) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return None;
+        };
+        let Some(Indices::U16(indices)) = mesh.indices() else {
+            return None;
+        };
+
+        let bounds: Vec<(Vec3, Vec3)> = indices.chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|corner| Vec3::from(positions[triangle[corner] as usize]));
+                (a.min(b).min(c), a.max(b).max(c))
+            })
+            .collect();
+
+        let mut bvh = TriangleBvh {
+            nodes: Vec::with_capacity(bounds.len() / DECAL_BVH_LEAF * 2 + 1),
+            triangles: (0..bounds.len() as u32).collect(),
+        };
+        if !bounds.is_empty() {
+            bvh.build(&bounds, 0, bounds.len());
+        }
+        return Some(bvh);
+    }
+
+    // Adds the node for triangles[start..end] and its children, splitting at the median along the longest axis
+    fn build(&mut self, bounds: &[(Vec3, Vec3)], start: usize, end: usize) {
+        let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+        for triangle in self.triangles[start..end].iter() {
+            let (triangle_min, triangle_max) = bounds[*triangle as usize];
+            min = min.min(triangle_min);
+            max = max.max(triangle_max);
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(BvhNode { min, max, first: start as u32, count: (end - start) as u32 });
+        if end - start <= DECAL_BVH_LEAF {
+            return;
+        }
+
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        let center = |triangle: &u32| {
+            let (triangle_min, triangle_max) = bounds[*triangle as usize];
+            return triangle_min[axis] + triangle_max[axis];
+        };
+        let middle = (start + end) / 2;
+        self.triangles[start..end].select_nth_unstable_by(middle - start, |a, b| center(a).total_cmp(&center(b)));
+
+        self.build(bounds, start, middle);
+        self.nodes[node].first = self.nodes.len() as u32;
+        self.nodes[node].count = 0;
+        self.build(bounds, middle, end);
+    }
+
+    // Triangles whose bounds overlap the box, in index buffer order
+    fn gather(&self, min: Vec3, max: Vec3, triangles: &mut Vec<u32>) {
+        triangles.clear();
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let BvhNode { min: node_min, max: node_max, first, count } = self.nodes[node];
+            if node_min.cmpgt(max).any() || node_max.cmplt(min).any() {
+                continue;
+            }
+
+            if count > 0 {
+                triangles.extend_from_slice(&self.triangles[first as usize..(first + count) as usize]);
+            } else {
+                stack.push(first as usize);
+                stack.push(node + 1);
+            }
+        }
+        triangles.sort_unstable();
+    }
+}
+
+// Triangle BVHs by mesh asset, built the first time a mesh is sprayed
+#[derive(Resource, Default)]
+struct DecalTriangleBvhs {
+    bvhs: HashMap<AssetId<Mesh>, Arc<TriangleBvh>>,
+}
+
+impl DecalTriangleBvhs {
+    fn get(&mut self, mesh_id: AssetId<Mesh>, mesh: &Mesh) -> Option<Arc<TriangleBvh>> {
+        if let Some(bvh) = self.bvhs.get(&mesh_id) {
+            return Some(bvh.clone());
+        }
+
+        let bvh = Arc::new(TriangleBvh::new(mesh)?);
+        self.bvhs.insert(mesh_id, bvh.clone());
+        return Some(bvh);
+    }
+}
+
+// Drops the BVHs of changed and removed meshes
+fn invalidate_triangle_bvhs(mut bvhs: ResMut<DecalTriangleBvhs>, mut mesh_events: EventReader<AssetEvent<Mesh>>) {
+    for event in mesh_events.read() {
+        if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+            bvhs.bvhs.remove(id);
+        }
+    }
+}
+
+// Uniform grid over the world bounds of Decalable entities, see DecalSettings::spatial_index
+#[derive(Resource, Default)]
+struct DecalSpatialIndex {
+    cells: HashMap<IVec3, Vec<Entity>>,
+    ranges: HashMap<Entity, (IVec3, IVec3)>,    // Cells covered by each entity in the grid, inclusive
+    unbounded: HashSet<Entity>,                 // Candidates for every spray
+}
+
+impl DecalSpatialIndex {
+    fn cell(point: Vec3) -> IVec3 {
+        return (point / DECAL_INDEX_CELL).floor().as_ivec3();
+    }
+
+    fn cell_count(min: IVec3, max: IVec3) -> i64 {
+        let size = (max - min + IVec3::ONE).as_i64vec3();
+        return size.x * size.y * size.z;
+    }
+
+    fn cells_in(min: IVec3, max: IVec3) -> impl Iterator<Item = IVec3> {
+        return (min.x..=max.x).flat_map(move |x| (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| IVec3::new(x, y, z))));
+    }
+
+    // Entities without bounds, or spanning too many cells, are kept out of the grid
+    fn insert(&mut self, entity: Entity, bounds: Option<(Vec3, Vec3)>) {
+        self.remove(entity);
+
+        let Some((min, max)) = bounds.map(|(min, max)| (Self::cell(min), Self::cell(max))) else {
+            self.unbounded.insert(entity);
+            return;
+        };
+        if Self::cell_count(min, max) > DECAL_INDEX_MAX_CELLS {
+            self.unbounded.insert(entity);
+            return;
+        }
+
+        for cell in Self::cells_in(min, max) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.ranges.insert(entity, (min, max));
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        self.unbounded.remove(&entity);
+        let Some((min, max)) = self.ranges.remove(&entity) else {
+            return;
+        };
+        for cell in Self::cells_in(min, max) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    // Candidates overlapping the world space box, possibly with duplicates
+    fn query(&self, min: Vec3, max: Vec3) -> Vec<Entity> {
+        let (min, max) = (Self::cell(min), Self::cell(max));
+        let mut entities: Vec<Entity> = self.unbounded.iter().copied().collect();
+
+        // Huge projectors walk the occupied cells instead of every cell they cover
+        if Self::cell_count(min, max) > self.cells.len() as i64 {
+            for (cell, cell_entities) in self.cells.iter() {
+                if cell.cmpge(min).all() && cell.cmple(max).all() {
+                    entities.extend_from_slice(cell_entities);
+                }
+            }
+        } else {
+            for cell in Self::cells_in(min, max) {
+                if let Some(cell_entities) = self.cells.get(&cell) {
+                    entities.extend_from_slice(cell_entities);
+                }
+            }
+        }
+        return entities;
+    }
+}
+
+// Keeps the spatial index up to date with moved, new and removed Decalables
+fn update_decal_index(
+    mut index: ResMut<DecalSpatialIndex>,
+    changed: Query<
+        (Entity, &GlobalTransform, Option<&Aabb>, Has<SkinnedMesh>, Has<MeshMorphWeights>),
+        (With<Decalable>, Or<(Added<Decalable>, Changed<GlobalTransform>, Changed<Aabb>)>),
+    >,
+    mut removed: RemovedComponents<Decalable>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+
+    for (entity, global_transform, aabb, skinned, morphed) in changed.iter() {
+        // The bounds of skinned and morphed meshes don't cover their current pose
+        let bounds = aabb.filter(|_| !skinned && !morphed)
+            .map(|aabb| world_box(Vec3::from(aabb.center), Vec3::from(aabb.half_extents), &global_transform.compute_matrix()).aabb());
+        index.insert(entity, bounds);
+    }
+}
+
+// Insert Decalable on the meshes of DecalableScenes, and remove it for scenes that lost the marker
+fn propagate_decalable_scenes(
+    mut commands: Commands,
+    added_scenes: Query<Entity, Or<(Added<DecalableScene>, Added<DecalableGroup>)>>,
+    mut removed_scenes: RemovedComponents<DecalableScene>,
+    mut removed_groups: RemovedComponents<DecalableGroup>,
+    scenes: Query<(), Or<(With<DecalableScene>, With<DecalableGroup>)>>,
+    new_meshes: Query<Entity, (Or<(Added<Mesh3d>, Changed<Parent>)>, Without<Decalable>, Without<Decal>)>,
+    meshes: Query<(), (With<Mesh3d>, Without<Decalable>, Without<Decal>)>,
+    propagated: Query<(), With<PropagatedDecalable>>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+) {
+    let mut make_decalable = |entity: Entity| {
+        commands.entity(entity).insert((Decalable::default(), PropagatedDecalable));
+    };
+
+    for scene in added_scenes.iter() {
+        children.iter_descendants(scene)
+            .filter(|entity| meshes.contains(*entity))
+            .for_each(&mut make_decalable);
+    }
+
+    // Meshes spawned into a scene after it was marked
+    for mesh in new_meshes.iter() {
+        if parents.iter_ancestors(mesh).any(|ancestor| scenes.contains(ancestor)) {
+            make_decalable(mesh);
+        }
+    }
+
+    // Unless the entity is still marked by the other one
+    for scene in removed_scenes.read().chain(removed_groups.read()).filter(|scene| !scenes.contains(*scene)) {
+        for entity in children.iter_descendants(scene).filter(|entity| propagated.contains(*entity)) {
+            if let Some(mut entity) = commands.get_entity(entity) {
+                entity.remove::<(Decalable, PropagatedDecalable)>();
+            }
+        }
+    }
+}
+
+// Copy the current morph weights of each target onto its decals, after animation has updated them
+fn sync_decal_morph_weights(
+    mut decals: Query<(&Parent, &mut MeshMorphWeights), With<Decal>>,
+    models: Query<&MeshMorphWeights, Without<Decal>>,
+) {
+    for (parent, mut decal_weights) in decals.iter_mut() {
+        let Ok(model_weights) = models.get(parent.get()) else {
+            continue;
+        };
+        for (decal_weight, model_weight) in decal_weights.weights_mut().iter_mut().zip(model_weights.weights()) {
+            *decal_weight = *model_weight;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bounds of a 2 meter quad facing up, as Bevy computes them
+    fn quad_bounds(translation: Vec3) -> WorldBox {
+        return world_box(Vec3::ZERO, Vec3::new(1., 0., 1.), &Transform::from_translation(translation).compute_matrix());
+    }
+
+    #[test]
+    fn distant_targets_are_culled() {
+        let projector = world_box(Vec3::ZERO, Vec3::ONE, &projector_transform(Vec3::Y, Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2), Vec2::ONE, 0.0..2.).compute_matrix());
+        let (near, far) = (Entity::from_raw(1), Entity::from_raw(2));
+        let near_bounds = quad_bounds(Vec3::ZERO);
+        let far_bounds = quad_bounds(Vec3::new(500., 0., -300.));
+
+        let mut index = DecalSpatialIndex::default();
+        index.insert(near, Some(near_bounds.aabb()));
+        index.insert(far, Some(far_bounds.aabb()));
+        let (min, max) = projector.aabb();
+        let mut candidates = index.query(min, max);
+        candidates.dedup();
+        assert_eq!(candidates, vec![near], "only the target under the projector is a candidate");
+
+        // The targets left to the oriented box test without the spatial index
+        assert!(near_bounds.intersects(&projector));
+        assert!(!far_bounds.intersects(&projector));
+    }
+
+    #[test]
+    fn rotated_projectors_are_culled_by_their_oriented_box() {
+        // Their axis aligned bounds overlap, but a thin projector rotated by 45° misses the quad
+        let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4) * Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+        let projector = world_box(Vec3::ZERO, Vec3::ONE, &projector_transform(Vec3::new(1.8, 1., 1.8), rotation, Vec2::new(4., 0.2), 0.0..2.).compute_matrix());
+        let near_bounds = quad_bounds(Vec3::ZERO);
+
+        let (min, max) = projector.aabb();
+        let (quad_min, quad_max) = near_bounds.aabb();
+        assert!(min.cmple(quad_max).all() && max.cmpge(quad_min).all());
+        assert!(!near_bounds.intersects(&projector));
+    }
 }
@@ -0,0 +1,91 @@
+use bevy::asset::load_internal_asset;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+
+use crate::DecalPlugin;
+
+const DECAL_MATERIAL_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(0x5d1c_73a2_9e40_4b8f_a6c1_0f3e_d2b7_9a41);
+
+/// A [`StandardMaterial`] that fades out toward the edges of the decal,
+/// instead of cutting the texture off where the projection ends.
+pub type DecalMaterial = ExtendedMaterial<StandardMaterial, DecalMaterialExtension>;
+
+/// Fading settings of the [`DecalMaterial`].
+///
+/// # Note
+///
+/// The edge fade assumes the decal texture spans the whole projector, so it
+/// doesn't line up with [`crate::SprayOptions::uv_rect`] or a UV rotation.
+/// The depth fade uses the transform of the decal entity and has no effect on skinned decals.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct DecalMaterialExtension {
+    /// Width of the fade toward the borders of the decal, in UV units.
+    /// 0 disables it, 0.5 fades all the way to the center.
+    #[uniform(100)]
+    pub edge_fade: f32,
+    /// Fraction of the projection depth over which the decal fades out toward
+    /// the far end of the projector. 0 disables it.
+    #[uniform(100)]
+    pub depth_fade: f32,
+}
+
+impl Default for DecalMaterialExtension {
+    fn default() -> Self {
+        return DecalMaterialExtension {
+            edge_fade: 0.1,
+            depth_fade: 0.,
+        };
+    }
+}
+
+impl MaterialExtension for DecalMaterialExtension {
+    fn fragment_shader() -> ShaderRef {
+        return DECAL_MATERIAL_SHADER_HANDLE.into();
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        return DECAL_MATERIAL_SHADER_HANDLE.into();
+    }
+}
+
+/// A blended [`DecalMaterial`] with the default fading for `base_color_texture`.
+///
+/// # Example:
+///
+/// ```
+/// let splatter = materials.add(decal_material(assets.load("splatter1.png")));
+/// spray_decal(&mut commands, splatter, my_transform);
+/// ```
+pub fn decal_material(base_color_texture: Handle<Image>) -> DecalMaterial {
+    return DecalMaterial {
+        base: StandardMaterial {
+            base_color_texture: Some(base_color_texture),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        },
+        extension: DecalMaterialExtension::default(),
+    };
+}
+
+/// Adds the [`DecalMaterial`] along with a [`DecalPlugin`] for it.
+/// Add it next to the regular [`DecalPlugin`], which is still needed for [`StandardMaterial`] decals.
+///
+/// # Example:
+///
+/// ```
+/// app.add_plugins((DecalPlugin, DecalMaterialPlugin));
+/// ```
+pub struct DecalMaterialPlugin;
+
+impl Plugin for DecalMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, DECAL_MATERIAL_SHADER_HANDLE, "decal_material.wgsl", Shader::from_wgsl);
+
+        app.register_type::<DecalMaterialExtension>()
+            .add_plugins((
+                MaterialPlugin::<DecalMaterial>::default(),
+                DecalPlugin::<DecalMaterial>::default().with_material_tint().with_material_depth_bias().with_material_fade().with_material_bake(),
+            ));
+    }
+}
@@ -0,0 +1,109 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy::picking::events::{Click, Pointer};
+use bevy::picking::pointer::PointerButton;
+
+use crate::{decal_transform_from_hit, Decalable, SprayDecal};
+
+/// What a click stamps onto a [`Decalable`], see [`DecalPickingPlugin`]. Used as a resource
+/// for every target, and as a component to override it on single targets.
+///
+/// # Example:
+///
+/// ```
+/// commands.insert_resource(ClickToStamp::new(stamp.clone(), Vec2::splat(0.5)));
+///
+/// // The wanted poster sticks to the notice board only
+/// commands.spawn((Mesh3d(board), MeshMaterial3d(cork), Decalable::default(), ClickToStamp::new(poster.clone(), Vec2::new(0.6, 0.8))));
+/// ```
+#[derive(Resource, Component, Clone)]
+pub struct ClickToStamp<M: Material = StandardMaterial> {
+    pub material: Handle<M>,
+    /// Width and height of the stamp in world units.
+    pub size: Vec2,
+    /// Depth of the projection, reaching half of it in front of and behind the hit point.
+    pub depth: f32,
+    /// Button that stamps.
+    pub button: PointerButton,
+}
+
+impl<M: Material> ClickToStamp<M> {
+    pub fn new(material: Handle<M>, size: Vec2) -> Self {
+        return ClickToStamp {
+            material,
+            size,
+            depth: size.min_element() * 0.5,
+            button: PointerButton::Primary,
+        };
+    }
+}
+
+/// Stamps decals onto [`Decalable`] entities clicked with `bevy_picking`, using the
+/// hit position and normal of the click. Clicked entities without [`Decalable`] are
+/// ignored, as are clicks while there's neither a [`ClickToStamp`] resource nor a
+/// component on the clicked entity. Only the clicked entity gets the decal.
+///
+/// # Example:
+///
+/// ```
+/// app.add_plugins((DefaultPlugins, MeshPickingPlugin, DecalPlugin, DecalPickingPlugin));
+/// ```
+///
+/// # Note
+///
+/// Needs a picking backend reporting normals, like the `MeshPickingPlugin`, otherwise
+/// stamps face the camera.
+pub struct DecalPickingPlugin<M: Material = StandardMaterial> {
+    material: PhantomData<M>,
+}
+
+/// The default [`DecalPickingPlugin`], stamping [`StandardMaterial`] decals, so
+/// `app.add_plugins(DecalPickingPlugin)` works like it does for the [`crate::DecalPlugin`].
+#[allow(non_upper_case_globals)]
+pub const DecalPickingPlugin: DecalPickingPlugin = DecalPickingPlugin { material: PhantomData };
+
+impl<M: Material> Default for DecalPickingPlugin<M> {
+    fn default() -> Self {
+        return DecalPickingPlugin { material: PhantomData };
+    }
+}
+
+impl<M: Material> Plugin for DecalPickingPlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, stamp_clicks::<M>);
+    }
+}
+
+fn stamp_clicks<M: Material>(
+    mut commands: Commands,
+    mut clicks: EventReader<Pointer<Click>>,
+    default_stamp: Option<Res<ClickToStamp<M>>>,
+    stamps: Query<&ClickToStamp<M>>,
+    decalables: Query<(), With<Decalable>>,
+    cameras: Query<&GlobalTransform, With<Camera>>,
+) {
+    for click in clicks.read() {
+        if !decalables.contains(click.target) {
+            continue;
+        }
+        let Some(stamp) = stamps.get(click.target).ok().or(default_stamp.as_deref()) else {
+            continue;
+        };
+        if click.event.button != stamp.button {
+            continue;
+        }
+        let Some(position) = click.event.hit.position else {
+            continue;
+        };
+
+        // Without a normal from the backend, face the camera
+        let normal = click.event.hit.normal.or_else(|| {
+            cameras.get(click.event.hit.camera).ok().map(|camera| *camera.back())
+        }).unwrap_or(Vec3::Y);
+
+        SprayDecal::new(stamp.material.clone(), decal_transform_from_hit(position, normal, stamp.size, stamp.depth, 0.))
+            .with_targets(&[click.target])
+            .spray(&mut commands);
+    }
+}
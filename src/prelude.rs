@@ -1,6 +1,105 @@
 pub use crate::{
     spray_decal,
+    spray_decal_with_options,
+    spray_decals,
+    spray_decal_sized,
+    spray_decal_filtered,
+    spray_decal_immediate,
+    projector_transform,
+    perspective_projector,
+    decal_transform_from_hit,
+    project_decal,
+    project_decal_with,
+    SprayOptions,
+    SprayDecal,
+    SprayJitter,
+    DecalAtlas,
+    DecalAtlasIndex,
+    SprayDecalEvent,
+    DecalAppliedEvent,
+    OnDecalApplied,
+    DecalFailedEvent,
+    DecalFailureReason,
+    DecalTriangleLimitEvent,
+    TriangleLimitPolicy,
+    DecalOffsetMode,
+    DecalShape,
+    DecalDepthBias,
+    DecalTint,
+    DecalFade,
+    DecalCommandsExt,
     DecalPlugin,
+    DecalSet,
+    DecalSettings,
+    DecalDiagnostics,
+    InvalidDecalSettings,
     Decalable,
-    Decal
-};
\ No newline at end of file
+    DecalLimitMode,
+    DecalLimitUnit,
+    DecalBlocked,
+    ReprojectOnMeshChange,
+    DecalableScene,
+    DecalableGroup,
+    DecalLayers,
+    Decal,
+    DecalSpray,
+    DecalOf,
+    DecalInfo,
+    Decals,
+    DecalGroup,
+    DecalLifetime,
+    clear_decals_in_group,
+    remove_decals_in_region,
+    DecalRegion,
+    DecalOcclusion,
+    SprayId,
+    DecalRecord,
+    DecalMaterialIds,
+    collect_decal_records,
+    restore_decal_records,
+    DecalAnchor,
+    PersistentDecals,
+    persist_anchored_decals,
+};
+
+pub use crate::bake::{
+    DecalBakeTarget,
+    DecalBake,
+    DecalBakedEvent,
+};
+
+#[cfg(feature = "decal_material")]
+pub use crate::material::{
+    decal_material,
+    DecalMaterial,
+    DecalMaterialExtension,
+    DecalMaterialPlugin,
+};
+
+#[cfg(feature = "rapier")]
+pub use crate::rapier::{
+    spray_decal_raycast,
+    DecalRaycastHit,
+};
+
+#[cfg(feature = "picking")]
+pub use crate::picking::{
+    ClickToStamp,
+    DecalPickingPlugin,
+};
+
+#[cfg(feature = "export")]
+pub use crate::export::{
+    export_decals,
+    DecalExportSelection,
+    DecalExportSpace,
+    DecalExportFormat,
+    DecalExportError,
+};
+
+#[cfg(feature = "debug")]
+pub use crate::debug::{
+    DecalProjectorGizmos,
+    DecalDebugPlugin,
+    DecalDebugOverlay,
+};
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{QueryFilter, RapierContext, RayIntersection};
+
+use crate::{decal_transform_from_hit, spray_decal, SprayId};
+
+/// What a [`spray_decal_raycast`] hit, for gameplay reacting to the same shot.
+#[derive(Clone, Copy, Debug)]
+pub struct DecalRaycastHit {
+    /// The collider entity that was hit.
+    pub entity: Entity,
+    /// Hit point, surface normal and time of impact of the ray.
+    pub intersection: RayIntersection,
+    /// The spray at the hit, see [`crate::DecalSpray`].
+    pub spray: SprayId,
+}
+
+/// Casts a ray with bevy_rapier and sprays a decal of `size` onto the surface it hits,
+/// reaching `depth / 2` in front of and behind the hit point, see [`decal_transform_from_hit`].
+/// `max_toi` is the maximum distance in multiples of `ray_dir`. Returns `None` and sprays
+/// nothing when the ray misses.
+///
+/// # Example:
+///
+/// ```
+/// let filter = QueryFilter::default().exclude_collider(player);
+/// if let Some(hit) = spray_decal_raycast(&mut commands, rapier_context.single(), gun.translation, *gun.forward(), 50., filter, bullet_hole.clone(), Vec2::splat(0.2), 0.1) {
+///     commands.entity(hit.entity).insert(Damaged);
+/// }
+/// ```
+///
+/// # Note
+///
+/// The hit collider doesn't need to be the entity with the mesh, the projection sprays every
+/// [`crate::Decalable`] around the hit point. Colliders of meshes without [`crate::Decalable`]
+/// still stop the ray.
+pub fn spray_decal_raycast<M: Material>(
+    commands: &mut Commands,
+    rapier_context: &RapierContext,
+    ray_origin: Vec3,
+    ray_dir: Vec3,
+    max_toi: f32,
+    filter: QueryFilter,
+    material: Handle<M>,
+    size: Vec2,
+    depth: f32,
+) -> Option<DecalRaycastHit> {
+    let Some((entity, intersection)) = rapier_context.cast_ray_and_get_normal(ray_origin, ray_dir, max_toi, true, filter) else {
+        debug!("Decal raycast from {ray_origin} toward {ray_dir} didn't hit anything, nothing is sprayed.");
+        return None;
+    };
+
+    let spray = spray_decal(commands, material, decal_transform_from_hit(intersection.point, intersection.normal, size, depth, 0.));
+    return Some(DecalRaycastHit { entity, intersection, spray });
+}
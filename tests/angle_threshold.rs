@@ -0,0 +1,27 @@
+// The angle threshold: a projector wider than a box, aimed at one of its faces, also covers the
+// four side faces, which are only left out with a maximum angle.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn maximum_angle_leaves_out_side_faces() {
+    let cube = cube(1.);
+    let projector = projector_transform(Vec3::new(0., 0., 2.), Quat::IDENTITY, Vec2::splat(2.), 0.0..3.);
+
+    let everything = project_decal_with(&cube, &GlobalTransform::IDENTITY, &projector, 0., &DecalSettings::default(), &SprayOptions::default())
+        .expect("the projector covers the box");
+    assert!(triangles(&everything) > 2, "the side faces are perpendicular, so they are sprayed");
+
+    let options = SprayOptions { max_angle: Some(80_f32.to_radians()), ..default() };
+    let facing = project_decal_with(&cube, &GlobalTransform::IDENTITY, &projector, 0., &DecalSettings::default(), &options)
+        .expect("the projector covers the box");
+    assert_eq!(triangles(&facing), 2, "only the face aimed at is sprayed");
+}
+
+fn triangles(mesh: &Mesh) -> usize {
+    return mesh.indices().map_or(0, |indices| indices.len() / 3);
+}
@@ -0,0 +1,40 @@
+// Decal meshes are only kept in the render world by default, to save memory, and stay readable
+// in the main world when requested, e.g. to compute the painted area or generate colliders.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn decal_meshes_are_render_only_by_default() {
+    let mut app = headless_app(DecalPlugin);
+    let mesh = spray_quad(&mut app);
+    assert_eq!(mesh.asset_usage, RenderAssetUsages::RENDER_WORLD);
+}
+
+#[test]
+fn main_world_decal_meshes_stay_readable() {
+    let mut app = headless_app(DecalPlugin::new().with_asset_usage(RenderAssetUsages::default()));
+    let mesh = spray_quad(&mut app);
+    assert!(mesh.asset_usage.contains(RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD));
+    assert!(mesh.attribute(Mesh::ATTRIBUTE_POSITION).is_some_and(|positions| !positions.is_empty()), "the vertices are still there");
+    assert!(mesh.indices().is_some_and(|indices| !indices.is_empty()), "the triangles are still there");
+}
+
+// The decal mesh of a spray onto a quad, a frame after it was applied
+fn spray_quad(app: &mut App) -> Mesh {
+    let quad = add_mesh(app, quad(2.));
+    let material = add_material(app);
+    let target = app.world_mut().spawn((Mesh3d(quad), Decalable::default())).id();
+    app.update();
+
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::ZERO, 1.));
+    app.update();
+    app.update();
+
+    let decal = app.world().get::<Decalable>(target).unwrap().decals().next().expect("the spray hits the quad");
+    return app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).expect("the decal mesh is an asset").clone();
+}
@@ -0,0 +1,99 @@
+// Atlas sprays: decals sample only from the cell of a non-square atlas they were sprayed with,
+// wait for layouts that aren't loaded yet, and random cells follow their seed.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const COLUMNS: u32 = 4;
+const ROWS: u32 = 2;
+
+#[test]
+fn decals_sample_their_cell() {
+    let (mut app, material, layout) = atlas_app();
+
+    // The last cell is the bottom right one
+    let last = SprayDecal::new(material, spot(-0.6)).with_atlas(layout, 7).spray(&mut app.world_mut().commands());
+    app.update();
+    let (min, max) = uv_bounds(&mut app, last);
+    assert!(min.cmpge(Vec2::new(0.75, 0.5) - 1e-4).all() && max.cmple(Vec2::ONE + 1e-4).all(), "the decal samples only from its cell, {min} to {max}");
+    assert!((max - min).abs_diff_eq(Vec2::new(0.25, 0.5), 1e-3), "the decal covers the whole cell");
+}
+
+#[test]
+fn sprays_wait_for_their_layout() {
+    let (mut app, material, _) = atlas_app();
+
+    let deferred_layout: Handle<TextureAtlasLayout> = app.world().resource::<Assets<TextureAtlasLayout>>().reserve_handle();
+    let deferred = SprayDecal::new(material, spot(-0.2)).with_atlas(deferred_layout.clone(), 1).spray(&mut app.world_mut().commands());
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(decal(&mut app, deferred).is_none(), "nothing is applied before the layout is loaded");
+
+    app.world_mut().resource_mut::<Assets<TextureAtlasLayout>>()
+        .insert(&deferred_layout, TextureAtlasLayout::from_grid(UVec2::new(64, 32), COLUMNS, ROWS, None, None));
+    app.update();
+    let (min, max) = uv_bounds(&mut app, deferred);
+    assert!(min.cmpge(Vec2::new(0.25, 0.) - 1e-4).all() && max.cmple(Vec2::new(0.5, 0.5) + 1e-4).all(), "the waiting spray uses its cell once loaded");
+}
+
+#[test]
+fn random_cells_follow_their_seed() {
+    let (mut app, material, layout) = atlas_app();
+
+    let random: Vec<SprayId> = [42, 42, 7]
+        .into_iter()
+        .enumerate()
+        .map(|(index, seed)| SprayDecal::new(material.clone(), spot(0.2 + index as f32 * 0.3))
+            .with_random_atlas_cell(layout.clone(), seed)
+            .spray(&mut app.world_mut().commands()))
+        .collect();
+    app.update();
+    let cells: Vec<UVec2> = random.iter().map(|spray| {
+        let (min, max) = uv_bounds(&mut app, *spray);
+        let cell = (min * Vec2::new(COLUMNS as f32, ROWS as f32) + 1e-3).floor();
+        assert!(max.cmple((cell + 1.) / Vec2::new(COLUMNS as f32, ROWS as f32) + 1e-4).all(), "random sprays stay inside one cell");
+        return cell.as_uvec2();
+    }).collect();
+    assert_eq!(cells[0], cells[1], "the same seed picks the same cell");
+}
+
+// An app with a floor to spray, and an atlas of 64x32 pixel cells, so 256x64
+fn atlas_app() -> (App, Handle<StandardMaterial>, Handle<TextureAtlasLayout>) {
+    let mut app = minimal_app();
+    app.add_plugins(DecalPlugin).init_asset::<TextureAtlasLayout>();
+    finish(&mut app);
+
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let layout = app.world_mut().resource_mut::<Assets<TextureAtlasLayout>>()
+        .add(TextureAtlasLayout::from_grid(UVec2::new(64, 32), COLUMNS, ROWS, None, None));
+    app.world_mut().spawn((Mesh3d(quad), Decalable::with_limit(64)));
+    app.update();
+    return (app, material, layout);
+}
+
+fn spot(x: f32) -> Transform {
+    return spray_down(Vec3::new(x, 0., 0.), 0.3);
+}
+
+fn decal(app: &mut App, spray: SprayId) -> Option<Entity> {
+    return app.world_mut().query_filtered::<(Entity, &DecalSpray), With<Decal>>()
+        .iter(app.world())
+        .find(|(_, decal_spray)| decal_spray.0 == spray)
+        .map(|(entity, _)| entity);
+}
+
+// Bounds of the UVs of the decal of a spray
+fn uv_bounds(app: &mut App, spray: SprayId) -> (Vec2, Vec2) {
+    let decal = decal(app, spray).expect("the spray got a decal");
+    let mesh = app.world().get::<Mesh3d>(decal).unwrap();
+    let Some(VertexAttributeValues::Float32x2(uvs)) = app.world().resource::<Assets<Mesh>>().get(mesh).unwrap().attribute(Mesh::ATTRIBUTE_UV_0) else {
+        panic!("decals have UVs");
+    };
+    return uvs.iter().fold((Vec2::MAX, Vec2::MIN), |(min, max), uv| (min.min(Vec2::from(*uv)), max.max(Vec2::from(*uv))));
+}
@@ -0,0 +1,39 @@
+// Kept backfaces: a spray going through a cube also coats its bottom, lit like the bottom
+// itself, and leaves it alone when backfaces are removed.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn kept_backfaces_face_out_of_the_surface() {
+    // Deep enough to reach through the whole cube, narrower than its faces
+    let projector = spray_down(Vec3::ZERO, 0.5);
+    let keep = SprayOptions { backfaces: Some(true), ..default() };
+    let decal = project_decal_with(&cube(1.), &GlobalTransform::IDENTITY, &projector, 0., &DecalSettings::default(), &keep)
+        .expect("the projector covers the cube");
+
+    let (mut top, mut bottom) = (0., 0.);
+    for (corners, normal, _) in world_triangles(&decal, &projector) {
+        let winding = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        assert!(winding.dot(normal) > 0., "triangles wind the way their normals face");
+        let area = winding.length() * 0.5;
+        if corners.iter().all(|corner| (corner.y - 0.5).abs() < 0.001) {
+            assert!(normal.dot(Vec3::Y) > 0.99, "the decal on the top faces up");
+            top += area;
+        } else if corners.iter().all(|corner| (corner.y + 0.5).abs() < 0.001) {
+            assert!(normal.dot(Vec3::NEG_Y) > 0.99, "the decal on the bottom faces down, away from the projector");
+            bottom += area;
+        } else {
+            panic!("{corners:?} lies on neither the top nor the bottom");
+        }
+    }
+    assert!((top - 0.25_f32).abs() < 0.001 && (bottom - 0.25_f32).abs() < 0.001, "both sides get the whole decal, {top} and {bottom}");
+
+    let remove = SprayOptions { backfaces: Some(false), ..default() };
+    let decal = project_decal_with(&cube(1.), &GlobalTransform::IDENTITY, &projector, 0., &DecalSettings::default(), &remove)
+        .expect("the projector covers the cube");
+    assert!(world_triangles(&decal, &projector).iter().all(|(_, normal, _)| normal.dot(Vec3::Y) > 0.99), "only the top is sprayed");
+}
@@ -0,0 +1,73 @@
+// Decals baked into a texture: paint sprayed onto an arena floor ends up in the texels under it,
+// blended over the paint from before, without spawning decals or touching the mesh.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SIZE: u32 = 64;
+
+#[test]
+fn sprays_are_baked_into_the_texture() {
+    let mut app = headless_app(DecalPlugin);
+    // UVs across the whole texture
+    let floor_mesh = add_mesh(&mut app, quad(2.).with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.], [0., 1.], [1., 1.], [1., 0.]]));
+    let paint = app.world_mut().resource_mut::<Assets<Image>>().add(DecalBakeTarget::image(SIZE, SIZE));
+    let floor = app.world_mut().spawn((Mesh3d(floor_mesh.clone()), DecalBakeTarget::new(paint.clone()))).id();
+    app.update();
+
+    let mut materials = |material: StandardMaterial| app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(material);
+    let red = materials(StandardMaterial { base_color: Color::LinearRgba(LinearRgba::RED), ..default() });
+    let green = materials(StandardMaterial { base_color: Color::LinearRgba(LinearRgba::GREEN.with_alpha(0.5)), ..default() });
+
+    // A 1 meter splat in the middle of the 2 meter floor covers the middle quarter of the texture
+    let splat = SprayDecal::new(red, spray_down(Vec3::ZERO, 1.)).spray(&mut app.world_mut().commands());
+    app.update();
+    let baked: Vec<DecalBakedEvent> = current_events(&app);
+    assert_eq!(baked.len(), 1);
+    assert_eq!((baked[0].spray, baked[0].target), (splat, floor));
+    assert_eq!(baked[0].texels, (SIZE as usize / 2).pow(2), "every texel under the splat is painted once");
+    assert!(close(texel(&app, &paint, SIZE / 2, SIZE / 2), LinearRgba::RED, 0.01));
+    assert_eq!(texel(&app, &paint, 4, 4).alpha, 0., "texels outside of the splat are left as they were");
+
+    // Nothing is spawned and the floor mesh stays as it is
+    assert_eq!(app.world_mut().query_filtered::<(), With<Decal>>().iter(app.world()).count(), 0);
+    assert_eq!(app.world().get::<Decalable>(floor).unwrap().count(), 0);
+    assert_eq!(app.world().resource::<Assets<Mesh>>().get(&floor_mesh).unwrap().count_vertices(), 4);
+
+    // Translucent paint is blended over the paint below
+    SprayDecal::new(green, spray_down(Vec3::ZERO, 1.)).spray(&mut app.world_mut().commands());
+    app.update();
+    let mixed = texel(&app, &paint, SIZE / 2, SIZE / 2);
+    assert!(close(mixed, LinearRgba::new(0.5, 0.5, 0., 1.), 0.02), "red below translucent green is {mixed:?}");
+
+    // Textured brushes are sampled at the decal UVs, here only the opaque half of the texture leaves paint
+    let brush = app.world_mut().resource_mut::<Assets<Image>>().add(Image::new(
+        Extent3d { width: 2, height: 1, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        vec![0, 0, 255, 255, 0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD,
+    ));
+    let stencil = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial { base_color_texture: Some(brush), ..default() });
+    SprayDecal::new(stencil, spray_down(Vec3::new(0.75, 0., 0.75), 0.5)).spray(&mut app.world_mut().commands());
+    app.update();
+    let blue = (SIZE * 3 / 4..SIZE)
+        .flat_map(|y| (SIZE * 3 / 4..SIZE).map(move |x| (x, y)))
+        .filter(|(x, y)| texel(&app, &paint, *x, *y).blue > 0.5)
+        .count();
+    assert_eq!(blue, (SIZE as usize / 4).pow(2) / 2, "half of the stencil is transparent");
+}
+
+fn texel(app: &App, image: &Handle<Image>, x: u32, y: u32) -> LinearRgba {
+    return app.world().resource::<Assets<Image>>().get(image).unwrap().get_color_at(x, y).unwrap().to_linear();
+}
+
+fn close(color: LinearRgba, expected: LinearRgba, tolerance: f32) -> bool {
+    let components = |color: LinearRgba| Vec4::new(color.red, color.green, color.blue, color.alpha);
+    return components(color).abs_diff_eq(components(expected), tolerance);
+}
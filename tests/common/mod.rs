@@ -0,0 +1,92 @@
+// Helpers shared by the integration tests, each test file only uses some of them
+#![allow(dead_code)]
+
+use bevy::app::Plugins;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+
+// An app with the decal plugin, without window or rendering, updated by hand
+pub fn headless_app<M>(decal_plugin: impl Plugins<M>) -> App {
+    let mut app = minimal_app();
+    app.add_plugins(decal_plugin);
+    finish(&mut app);
+    return app;
+}
+
+// The plugins the decal plugin needs, for tests inserting their own resources before adding it
+pub fn minimal_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin, HierarchyPlugin));
+    return app;
+}
+
+// Done by App::run, pending plugins only finish here when updating by hand
+pub fn finish(app: &mut App) {
+    app.finish();
+    app.cleanup();
+}
+
+// A square of `size` meters facing up, with the U16 indices decals need
+pub fn quad(size: f32) -> Mesh {
+    let half = size * 0.5;
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[-half, 0., -half], [-half, 0., half], [half, 0., half], [half, 0., -half]])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 1., 0.]; 4])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+}
+
+// A cube of `size` meters centered on the origin, with the U16 indices decals need
+pub fn cube(size: f32) -> Mesh {
+    let mut mesh = Mesh::from(Cuboid::from_length(size));
+    let indices: Vec<u16> = mesh.indices().unwrap().iter().map(|index| index as u16).collect();
+    mesh.insert_indices(Indices::U16(indices));
+    return mesh;
+}
+
+pub fn add_mesh(app: &mut App, mesh: Mesh) -> Handle<Mesh> {
+    return app.world_mut().resource_mut::<Assets<Mesh>>().add(mesh);
+}
+
+pub fn add_material(app: &mut App) -> Handle<StandardMaterial> {
+    return app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+}
+
+// Projector looking straight down onto the quads, from a meter above `center`
+pub fn spray_down(center: Vec3, size: f32) -> Transform {
+    return projector_transform(center + Vec3::Y, Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2), Vec2::splat(size), 0.0..2.);
+}
+
+// Events sent during the last update
+pub fn current_events<E: Event + Clone>(app: &App) -> Vec<E> {
+    return app.world().resource::<Events<E>>().iter_current_update_events().cloned().collect();
+}
+
+// World space corners, normal and UVs of each triangle of a decal mesh spawned at the projector
+pub fn world_triangles(mesh: &Mesh, projector: &Transform) -> Vec<([Vec3; 3], Vec3, [Vec2; 3])> {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) else {
+        panic!("decals have normals");
+    };
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        panic!("decals have UVs");
+    };
+    let matrix = projector.compute_matrix();
+    let normal_matrix = Mat3::from_mat4(matrix).inverse().transpose();
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+
+    return indices.chunks_exact(3)
+        .map(|triangle| {
+            let triangle: [usize; 3] = triangle.try_into().unwrap();
+            return (
+                triangle.map(|index| matrix.transform_point3(Vec3::from(positions[index]))),
+                (normal_matrix * Vec3::from(normals[triangle[0]])).normalize(),
+                triangle.map(|index| Vec2::from(uvs[index])),
+            );
+        })
+        .collect();
+}
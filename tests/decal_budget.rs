@@ -0,0 +1,70 @@
+// Soak test of the global decal budget: thousands of sprays across a level of floor tiles end up
+// with exactly the budget of decals alive, the newest ones, and exempt level art sprayed
+// afterwards outlives the decals sprayed after it.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 10_000;
+const SPRAYS_PER_FRAME: usize = 100;
+const BUDGET: usize = 500;
+const TILES: usize = 16;
+const LEVEL_ART: DecalGroup = DecalGroup(1);
+
+#[test]
+fn budget_keeps_newest_decals() {
+    let mut app = headless_app(DecalPlugin::new().with_settings(DecalSettings {
+        max_total_decals: Some(BUDGET),
+        budget_exempt_groups: vec![LEVEL_ART],
+        ..default()
+    }));
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let tiles: Vec<Entity> = (0..TILES)
+        .map(|tile| app.world_mut().spawn((Mesh3d(quad.clone()), Transform::from_xyz(tile as f32 * 3., 0., 0.), Decalable::with_limit(BUDGET * 2))).id())
+        .collect();
+    app.update();
+
+    let mut sprays = Vec::with_capacity(SPRAYS);
+    while sprays.len() < SPRAYS {
+        for _ in 0..SPRAYS_PER_FRAME {
+            let tile = sprays.len() % TILES;
+            let spot = (sprays.len() / TILES) % 9;
+            let center = Vec3::new(tile as f32 * 3. + (spot % 3) as f32 * 0.5 - 0.5, 0., (spot / 3) as f32 * 0.5 - 0.5);
+            sprays.push(spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(center, 0.3)));
+        }
+        app.update();
+
+        let alive = app.world_mut().query_filtered::<(), With<Decal>>().iter(app.world()).count();
+        assert!(alive <= BUDGET, "the budget is enforced every frame, {alive} decals are alive");
+    }
+
+    let alive = alive_sprays(&mut app);
+    let on_tiles: usize = tiles.iter().map(|tile| app.world().get::<Decalable>(*tile).unwrap().count()).sum();
+    assert_eq!(alive.len(), BUDGET);
+    assert_eq!(on_tiles, BUDGET, "evicted decals free their slots");
+    assert!(sprays[SPRAYS - BUDGET..].iter().all(|spray| alive.contains(spray)), "the newest decals are kept");
+
+    // Exempt level art counts toward the budget, but the decals after it are evicted first
+    let art = SprayDecal::new(material.clone(), spray_down(Vec3::ZERO, 1.))
+        .with_group(LEVEL_ART)
+        .spray(&mut app.world_mut().commands());
+    app.update();
+    for _ in 0..BUDGET {
+        sprays.push(spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::new(3., 0., 0.), 0.3)));
+    }
+    app.update();
+
+    let alive = alive_sprays(&mut app);
+    assert_eq!(alive.len(), BUDGET);
+    assert!(alive.contains(&art), "exempt decals are never evicted");
+    assert!(sprays[sprays.len() - (BUDGET - 1)..].iter().all(|spray| alive.contains(spray)));
+}
+
+fn alive_sprays(app: &mut App) -> HashSet<SprayId> {
+    return app.world_mut().query_filtered::<&DecalSpray, With<Decal>>().iter(app.world()).map(|spray| spray.0).collect();
+}
@@ -0,0 +1,83 @@
+// Round trip of the exporter: a decal sprayed onto a quad is written to OBJ and glTF, and both
+// files are parsed again to check the triangle count, UV and position bounds.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const QUAD_POSITION: Vec3 = Vec3::new(3., 0., 0.);
+
+#[test]
+fn obj_round_trip() {
+    let (mut app, target, triangles) = sprayed_quad();
+    let obj = export_decals(app.world_mut(), DecalExportSelection::Target(target), DecalExportSpace::Local, DecalExportFormat::Obj).unwrap();
+    let (obj_triangles, uvs, positions) = parse_obj(&String::from_utf8(obj).unwrap());
+    assert_eq!(obj_triangles, triangles, "every triangle is exported");
+    assert!(uvs.min.abs_diff_eq(Vec2::ZERO, 0.001) && uvs.max.abs_diff_eq(Vec2::ONE, 0.001), "the UVs span the texture: {uvs:?}");
+    assert!(positions.min.abs_diff_eq(Vec2::splat(-0.5), 0.001), "local space is relative to the quad: {positions:?}");
+}
+
+#[test]
+fn glb_round_trip() {
+    let (mut app, _, triangles) = sprayed_quad();
+    let glb = export_decals(app.world_mut(), DecalExportSelection::Group(DecalGroup::default()), DecalExportSpace::World, DecalExportFormat::Glb).unwrap();
+    let (document, buffers, _) = gltf::import_slice(&glb).expect("the exporter writes valid glTF");
+    let mut gltf_triangles = 0;
+    let (mut uvs, mut positions) = (Rect::EMPTY, Rect::EMPTY);
+    for gltf_mesh in document.meshes() {
+        assert_eq!(gltf_mesh.name(), Some("paint"), "objects are named after their material");
+        for primitive in gltf_mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            gltf_triangles += reader.read_indices().unwrap().into_u32().count() / 3;
+            for uv in reader.read_tex_coords(0).unwrap().into_f32() {
+                uvs = uvs.union_point(Vec2::from(uv));
+            }
+            for position in reader.read_positions().unwrap() {
+                positions = positions.union_point(Vec3::from(position).xz());
+            }
+        }
+    }
+    assert_eq!(gltf_triangles, triangles, "every triangle is exported");
+    assert!(uvs.min.abs_diff_eq(Vec2::ZERO, 0.001) && uvs.max.abs_diff_eq(Vec2::ONE, 0.001), "the UVs span the texture: {uvs:?}");
+    assert!(positions.min.abs_diff_eq(QUAD_POSITION.xz() - 0.5, 0.001), "world space includes the quad's position: {positions:?}");
+}
+
+// An app with a decal on a quad, along with the quad and the triangle count of the decal
+fn sprayed_quad() -> (App, Entity, usize) {
+    // The exporter reads the decal meshes back from the main world
+    let mut app = headless_app(DecalPlugin::new().with_asset_usage(RenderAssetUsages::default()));
+    let quad = add_mesh(&mut app, quad(2.));
+    let paint = add_material(&mut app);
+    app.world_mut().resource_mut::<DecalMaterialIds>().register("paint", paint.clone());
+    let target = app.world_mut().spawn((Mesh3d(quad), Transform::from_translation(QUAD_POSITION), Decalable::default())).id();
+    app.update();
+
+    SprayDecal::new(paint, spray_down(QUAD_POSITION, 1.)).spray(&mut app.world_mut().commands());
+    app.update();
+
+    let decal = app.world().get::<Decalable>(target).unwrap().decals().next().expect("the spray hits the quad");
+    let mesh = app.world().get::<Mesh3d>(decal).unwrap();
+    let triangles = app.world().resource::<Assets<Mesh>>().get(mesh).unwrap().indices().unwrap().len() / 3;
+    return (app, target, triangles);
+}
+
+// Triangle count, UV bounds in Bevy's convention and XZ bounds of the positions of an OBJ file
+fn parse_obj(obj: &str) -> (usize, Rect, Rect) {
+    let (mut triangles, mut uvs, mut positions) = (0, Rect::EMPTY, Rect::EMPTY);
+    for line in obj.lines() {
+        let values: Vec<f32> = line.split_whitespace().skip(1).filter_map(|word| word.parse().ok()).collect();
+        match line.split_whitespace().next() {
+            Some("f") => {
+                assert_eq!(line.split_whitespace().count(), 4, "faces are triangles");
+                triangles += 1;
+            }
+            Some("vt") => uvs = uvs.union_point(Vec2::new(values[0], 1. - values[1])),
+            Some("v") => positions = positions.union_point(Vec2::new(values[0], values[2])),
+            _ => {}
+        }
+    }
+    return (triangles, uvs, positions);
+}
@@ -0,0 +1,48 @@
+// Statistics attached to every decal: a decal covering a whole 1x1 quad covers an area of 1,
+// half of it covers 0.5, and areas are in world units on scaled targets too.
+
+mod common;
+
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn decal_info_measures_world_area() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(1.));
+    let material = add_material(&mut app);
+    let mut spawn_quad = |transform: Transform| app.world_mut().spawn((Mesh3d(quad.clone()), transform, Decalable::default())).id();
+    let tile = spawn_quad(Transform::default());
+    let half_covered = spawn_quad(Transform::from_xyz(5., 0., 0.));
+    let scaled = spawn_quad(Transform::from_xyz(10., 0., 0.).with_scale(Vec3::splat(2.)));
+    app.update();
+
+    let covering = spray_down(Vec3::ZERO, 2.);
+    let frame = app.world().resource::<FrameCount>().0;
+    spray_decal(&mut app.world_mut().commands(), material.clone(), covering);
+    // Only the half of the quad right of its center
+    let down = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    spray_decal(&mut app.world_mut().commands(), material.clone(), projector_transform(Vec3::new(5.5, 1., 0.), down, Vec2::new(1., 2.), 0.0..2.));
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::new(10., 0., 0.), 4.));
+    app.update();
+
+    let info = |target: Entity| -> DecalInfo {
+        let decal = app.world().get::<Decalable>(target).unwrap().decals().next().unwrap();
+        return *app.world().get::<DecalInfo>(decal).unwrap();
+    };
+
+    let full = info(tile);
+    assert!((full.area - 1.).abs() < 1e-4, "a decal covering the whole quad covers its area, not {}", full.area);
+    assert_eq!(full.triangles, 2);
+    assert!(full.vertices >= 4);
+    assert_eq!(full.projector, covering);
+    assert_eq!(full.frame_applied, frame);
+
+    let half = info(half_covered);
+    assert!((half.area - 0.5).abs() < 1e-4, "half of the quad covers {}", half.area);
+
+    let large = info(scaled);
+    assert!((large.area - 4.).abs() < 1e-3, "the area is in world units, not {}", large.area);
+}
@@ -0,0 +1,73 @@
+// Decal lifetimes: a footprint fades out at the end of its lifetime, pauses along with the virtual
+// clock, and is despawned once it's over, freeing its slot on the floor.
+
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const FRAME: Duration = Duration::from_millis(100);
+const LIFETIME: Duration = Duration::from_secs(1);
+const FADE: Duration = Duration::from_millis(500);
+
+#[test]
+fn decals_fade_and_expire() {
+    let mut app = headless_app(DecalPlugin);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+    let floor = app.world_mut().spawn((Mesh3d(quad), Decalable::default())).id();
+    app.update();
+
+    let footprint = SprayDecal::new(material.clone(), spray_down(Vec3::new(-0.5, 0., 0.), 0.4))
+        .with_lifetime(LIFETIME, FADE)
+        .spray(&mut app.world_mut().commands());
+    let paint = SprayDecal::new(material.clone(), spray_down(Vec3::new(0.5, 0., 0.), 0.4))
+        .spray(&mut app.world_mut().commands());
+    app.update();
+
+    let decals: Vec<Entity> = app.world().get::<Decalable>(floor).unwrap().decals().collect();
+    let find = |spray: SprayId| decals.iter().copied().find(|decal| app.world().get::<DecalSpray>(*decal) == Some(&DecalSpray(spray))).unwrap();
+    let (footprint, paint) = (find(footprint), find(paint));
+    assert_eq!(app.world().get::<Decalable>(floor).unwrap().count(), 2);
+    assert_eq!(alpha(&app, footprint), 1., "the footprint starts out opaque");
+
+    // Into the fade
+    for _ in 0..7 {
+        app.update();
+    }
+    let faded = alpha(&app, footprint);
+    assert!(faded > 0. && faded < 1., "the footprint fades out during the last part of its lifetime, not {faded}");
+    assert_eq!(alpha(&app, paint), 1., "decals without a lifetime stay as they are");
+
+    // Nothing ages while the virtual clock is paused
+    app.world_mut().resource_mut::<Time<Virtual>>().pause();
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world().get_entity(footprint).is_ok(), "paused decals don't expire");
+    assert_eq!(alpha(&app, footprint), faded, "paused decals don't fade");
+
+    app.world_mut().resource_mut::<Time<Virtual>>().unpause();
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(app.world().get_entity(footprint).is_err(), "the footprint is despawned at the end of its lifetime");
+    assert!(app.world().get_entity(paint).is_ok());
+    let decalable = app.world().get::<Decalable>(floor).unwrap();
+    assert_eq!(decalable.count(), 1, "the slot of the footprint is free again");
+    assert_eq!(decalable.decals().next(), Some(paint));
+}
+
+// Alpha of the material a decal currently renders with
+fn alpha(app: &App, decal: Entity) -> f32 {
+    let material = app.world().get::<MeshMaterial3d<StandardMaterial>>(decal).unwrap();
+    return app.world().resource::<Assets<StandardMaterial>>().get(material).unwrap().base_color.alpha();
+}
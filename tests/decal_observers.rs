@@ -0,0 +1,34 @@
+// Per target reactions to decals with observers, like a wall crumbling after enough paint hits,
+// next to the DecalAppliedEvent sent for every decal.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[derive(Component, Default)]
+struct Hits(usize);
+
+#[test]
+fn observer_counts_hits_on_its_target() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+
+    let count_hits = |trigger: Trigger<OnDecalApplied>, mut walls: Query<&mut Hits>| {
+        walls.get_mut(trigger.entity()).unwrap().0 += 1;
+    };
+    let wall = app.world_mut().spawn((Mesh3d(quad.clone()), Decalable::default(), Hits::default())).observe(count_hits).id();
+    let other_wall = app.world_mut().spawn((Mesh3d(quad), Transform::from_xyz(10., 0., 0.), Decalable::default(), Hits::default())).observe(count_hits).id();
+    app.update();
+
+    for _ in 0..3 {
+        spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 1.));
+    }
+    app.update();
+
+    assert_eq!(app.world().get::<Hits>(wall).unwrap().0, 3);
+    assert_eq!(app.world().get::<Hits>(other_wall).unwrap().0, 0, "observers only see the decals on their own target");
+    assert_eq!(current_events::<DecalAppliedEvent>(&app).len(), 3, "the global events are still sent");
+}
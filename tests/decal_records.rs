@@ -0,0 +1,74 @@
+// Round trip of decal records, like a save game: decals sprayed onto two quads are recorded,
+// cleared and restored onto the same targets, with the same decal count, transforms and offset
+// layers, even after older decals freed the lowest layers.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 6;
+
+#[test]
+fn records_restore_decals() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let red = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(Color::srgb(1., 0., 0.));
+    let blue = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(Color::srgb(0., 0., 1.));
+    let mut ids = app.world_mut().resource_mut::<DecalMaterialIds>();
+    ids.register("red", red.clone());
+    ids.register("blue", blue.clone());
+
+    let floor = app.world_mut().spawn((Mesh3d(quad.clone()), Decalable::default())).id();
+    let raised = app.world_mut().spawn((Mesh3d(quad), Transform::from_xyz(5., 1., 0.), Decalable::default())).id();
+    app.update();
+
+    for spray in 0..SPRAYS {
+        let material = if spray % 2 == 0 { red.clone() } else { blue.clone() };
+        for center in [Vec3::ZERO, Vec3::new(5., 1., 0.)] {
+            SprayDecal::new(material.clone(), spray_down(center + Vec3::new(spray as f32 * 0.1 - 0.3, 0., 0.), 0.5))
+                .with_uv_rotation(spray as f32)
+                .spray(&mut app.world_mut().commands());
+        }
+    }
+    app.update();
+
+    // The oldest decal on the floor frees layer 1, which the restored decals must keep free
+    let oldest = app.world().get::<Decalable>(floor).unwrap().decals().next().unwrap();
+    app.world_mut().entity_mut(oldest).despawn_recursive();
+    app.update();
+
+    let before = snapshot(&mut app);
+    let records = collect_decal_records(app.world_mut());
+    assert_eq!(records.len(), before.len(), "every decal is recorded");
+    assert!(records.iter().any(|record| record.target == floor) && records.iter().any(|record| record.target == raised));
+
+    let decals: Vec<Entity> = app.world_mut().query_filtered::<Entity, With<Decal>>().iter(app.world()).collect();
+    for decal in decals {
+        app.world_mut().entity_mut(decal).despawn_recursive();
+    }
+    app.update();
+    assert!(snapshot(&mut app).is_empty(), "the decals are cleared");
+
+    restore_decal_records(&mut app.world_mut().commands(), records);
+    app.update();
+
+    let after = snapshot(&mut app);
+    assert_eq!(after.len(), before.len(), "every decal is restored");
+    for (before, after) in before.iter().zip(after.iter()) {
+        assert_eq!((before.0, before.1, before.2), (after.0, after.1, after.2), "same target, layer and material");
+        assert!(before.3.abs_diff_eq(after.3, 0.0001), "same transform: {:?} and {:?}", before.3, after.3);
+    }
+}
+
+// Target, layer, material and world transform of every decal, in a stable order
+fn snapshot(app: &mut App) -> Vec<(Entity, usize, AssetId<StandardMaterial>, Mat4)> {
+    let mut decals: Vec<(Entity, usize, AssetId<StandardMaterial>, Mat4)> = app.world_mut()
+        .query_filtered::<(&DecalOf, &MeshMaterial3d<StandardMaterial>, &GlobalTransform), With<Decal>>()
+        .iter(app.world())
+        .map(|(decal_of, material, transform)| (decal_of.target, decal_of.layer, material.id(), transform.compute_matrix()))
+        .collect();
+    decals.sort_by_key(|(target, layer, ..)| (*target, *layer));
+    return decals;
+}
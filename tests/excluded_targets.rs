@@ -0,0 +1,56 @@
+// Targets left out of sprays that reach them, e.g. the vehicle the player shoots from: for a single
+// spray with SprayDecal::without, or for every spray with DecalBlocked.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn excluded_target_is_left_out() {
+    let mut app = headless_app(DecalPlugin);
+    let (vehicle, ground) = overlapping_targets(&mut app);
+    let material = add_material(&mut app);
+
+    SprayDecal::new(material.clone(), spray_down(Vec3::ZERO, 1.)).without(&[vehicle]).spray(&mut app.world_mut().commands());
+    app.update();
+    assert_eq!(decal_counts(&app, vehicle, ground), (0, 1));
+
+    // Only for that spray
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::ZERO, 1.));
+    app.update();
+    assert_eq!(decal_counts(&app, vehicle, ground), (1, 2));
+}
+
+#[test]
+fn blocked_target_is_left_out() {
+    let mut app = headless_app(DecalPlugin);
+    let (vehicle, ground) = overlapping_targets(&mut app);
+    let material = add_material(&mut app);
+    app.world_mut().entity_mut(vehicle).insert(DecalBlocked);
+
+    for _ in 0..2 {
+        spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 1.));
+    }
+    app.update();
+    assert_eq!(decal_counts(&app, vehicle, ground), (0, 2));
+
+    app.world_mut().entity_mut(vehicle).remove::<DecalBlocked>();
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::ZERO, 1.));
+    app.update();
+    assert_eq!(decal_counts(&app, vehicle, ground), (1, 3));
+}
+
+// Two quads just above each other, both within reach of sprays onto the origin
+fn overlapping_targets(app: &mut App) -> (Entity, Entity) {
+    let quad = add_mesh(app, quad(2.));
+    let vehicle = app.world_mut().spawn((Mesh3d(quad.clone()), Transform::from_xyz(0., 0.2, 0.), Decalable::default())).id();
+    let ground = app.world_mut().spawn((Mesh3d(quad), Transform::default(), Decalable::default())).id();
+    app.update();
+    return (vehicle, ground);
+}
+
+fn decal_counts(app: &App, vehicle: Entity, ground: Entity) -> (usize, usize) {
+    return (app.world().get::<Decalable>(vehicle).unwrap().count(), app.world().get::<Decalable>(ground).unwrap().count());
+}
@@ -0,0 +1,50 @@
+// The frame sliced fallback used on wasm32: asynchronous sprays are projected on the main thread
+// one per frame, and end up the same as synchronous ones.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 3;
+
+#[test]
+fn one_asynchronous_spray_per_frame() {
+    // Decal meshes are read back below
+    let mut app = headless_app(DecalPlugin::new().with_frame_sliced_async(true).with_asset_usage(RenderAssetUsages::default()));
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let target = app.world_mut().spawn((Mesh3d(quad), Decalable::default())).id();
+    app.update();
+
+    let projectors: Vec<Transform> = (0..SPRAYS)
+        .map(|spray| spray_down(Vec3::new(spray as f32 * 0.5 - 0.5, 0., 0.), 0.4))
+        .collect();
+    let sliced: Vec<Entity> = projectors.iter()
+        .flat_map(|projector| spray_decal_immediate(app.world_mut(), material.clone(), *projector, SprayOptions { asynchronous: true, ..default() }))
+        .collect();
+    assert_eq!(sliced.len(), SPRAYS, "asynchronous sprays reserve their slots right away");
+
+    for frame in 1..=SPRAYS {
+        app.update();
+        let done = sliced.iter().filter(|decal| app.world().get::<Decal>(**decal).is_some()).count();
+        assert_eq!(done, frame, "one decal is projected per frame");
+    }
+
+    // The same sprays, synchronously
+    let synchronous: Vec<Entity> = projectors.iter()
+        .flat_map(|projector| spray_decal_immediate(app.world_mut(), material.clone(), *projector, SprayOptions::default()))
+        .collect();
+    app.update();
+    for (sliced, synchronous) in sliced.iter().zip(synchronous.iter()) {
+        assert_eq!(triangles(&app, *sliced), triangles(&app, *synchronous), "both paths project the same decal");
+    }
+    assert_eq!(app.world().get::<Decalable>(target).unwrap().count(), SPRAYS * 2);
+}
+
+fn triangles(app: &App, decal: Entity) -> usize {
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    return mesh.indices().unwrap().len() / 3;
+}
@@ -0,0 +1,43 @@
+// A dedicated server without rendering: the plugin initializes the assets it needs, sprays result
+// in decal entities without visual components, and their meshes stay readable to compute the
+// painted area, e.g. for scoring.
+
+mod common;
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn decals_without_rendering() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let paint = add_material(&mut app);
+    let wall = app.world_mut().spawn((Mesh3d(quad), Decalable::default())).id();
+    app.update();
+
+    spray_decal(&mut app.world_mut().commands(), paint, spray_down(Vec3::ZERO, 1.));
+    app.update();
+
+    let decal = app.world().get::<Decalable>(wall).unwrap().decals().next().expect("the spray hits the wall");
+    assert_eq!(app.world().get::<DecalOf>(decal).map(|decal_of| decal_of.target), Some(wall));
+    assert!(app.world().get::<NotShadowCaster>(decal).is_none(), "decals don't get shadow components without rendering");
+
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).expect("the decal mesh is readable");
+    let area = painted_area(mesh, app.world().get::<GlobalTransform>(decal).unwrap());
+    assert!((area - 1.).abs() < 0.01, "the 1x1 meter decal covers one square meter, not {area}");
+}
+
+// World space area of the triangles of a decal
+fn painted_area(mesh: &Mesh, transform: &GlobalTransform) -> f32 {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let positions: Vec<Vec3> = positions.iter().map(|position| transform.transform_point(Vec3::from(*position))).collect();
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+    return indices.chunks_exact(3)
+        .map(|triangle| (positions[triangle[1]] - positions[triangle[0]]).cross(positions[triangle[2]] - positions[triangle[0]]).length() * 0.5)
+        .sum();
+}
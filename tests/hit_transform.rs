@@ -0,0 +1,38 @@
+// Projectors built from raycast hits, which have to stay valid on floors and ceilings where the
+// normal leaves the up direction of the decal undefined.
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+
+#[test]
+fn vertical_normals_give_orthonormal_rotations() {
+    for normal in [Vec3::Y, Vec3::NEG_Y] {
+        let point = Vec3::new(1., 2., 3.);
+        let transform = decal_transform_from_hit(point, normal, Vec2::ONE, 0.2, 0.);
+        assert_orthonormal(&transform, normal);
+        assert!(transform.translation.abs_diff_eq(point, 1e-5), "the projector is centered on the hit point: {}", transform.translation);
+    }
+}
+
+#[test]
+fn tilted_and_rolled_normals_give_orthonormal_rotations() {
+    let normals = [Vec3::X, Vec3::NEG_Z, Vec3::new(1., 1., 0.), Vec3::new(0.001, 1., 0.), Vec3::new(-0.2, -3., 0.5)];
+    for normal in normals {
+        for roll in [0., 1., -2.5] {
+            let transform = decal_transform_from_hit(Vec3::ZERO, normal, Vec2::ONE, 0.2, roll);
+            assert_orthonormal(&transform, normal.normalize());
+        }
+    }
+}
+
+fn assert_orthonormal(transform: &Transform, normal: Vec3) {
+    let rotation = transform.rotation;
+    assert!(rotation.is_finite() && rotation.is_normalized(), "rotation for {normal} is a unit quaternion: {rotation}");
+
+    let (right, up, forward) = (rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::NEG_Z);
+    for axis in [right, up, forward] {
+        assert!(axis.is_normalized(), "axes for {normal} are unit length: {axis}");
+    }
+    assert!(right.dot(up).abs() < 1e-5 && up.dot(forward).abs() < 1e-5 && forward.dot(right).abs() < 1e-5, "axes for {normal} are orthogonal");
+    assert!(forward.abs_diff_eq(-normal, 1e-5), "the projection for {normal} points into the surface: {forward}");
+}
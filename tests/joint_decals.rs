@@ -0,0 +1,63 @@
+// Joint attachment: a decal on the part of a skinned quad weighted to its second joint is parented
+// to that joint instead of being skinned, and follows it rigidly. The same spray without the option
+// stays a skinned child of the quad.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes};
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn decals_attach_to_joints() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, skinned_quad());
+    let inverse_bindposes = app.world_mut().resource_mut::<Assets<SkinnedMeshInverseBindposes>>().add(vec![Mat4::IDENTITY; 2]);
+    let material = add_material(&mut app);
+    let joints = vec![
+        app.world_mut().spawn(Transform::default()).id(),
+        app.world_mut().spawn(Transform::default()).id(),
+    ];
+    let target = app.world_mut().spawn((
+        Mesh3d(quad),
+        SkinnedMesh { inverse_bindposes, joints: joints.clone() },
+        Decalable::default(),
+    )).id();
+    app.update();
+
+    // Mostly over the second joint, see skinned_quad
+    let projector = spray_down(Vec3::new(0.6, 0., 0.), 0.4);
+    let attached = spray_decal_immediate(app.world_mut(), material.clone(), projector, SprayOptions { attach_to_joint: true, ..default() });
+    let skinned = spray_decal_immediate(app.world_mut(), material, projector, SprayOptions::default());
+    app.update();
+
+    let attached = attached[0];
+    assert_eq!(app.world().get::<Parent>(attached).map(Parent::get), Some(joints[1]), "the decal is attached to the second joint");
+    assert!(app.world().get::<SkinnedMesh>(attached).is_none(), "attached decals aren't skinned");
+    assert_eq!(app.world().get::<DecalOf>(attached).unwrap().target, target, "the quad is still the target");
+
+    let skinned = skinned[0];
+    assert_eq!(app.world().get::<Parent>(skinned).map(Parent::get), Some(target), "decals are skinned children of the target by default");
+    assert!(app.world().get::<SkinnedMesh>(skinned).is_some());
+
+    // The decal rides along with its joint
+    app.world_mut().get_mut::<Transform>(joints[1]).unwrap().translation = Vec3::Y * 2.;
+    app.update();
+    let position = app.world().get::<GlobalTransform>(attached).unwrap().translation();
+    assert!((position.y - 2.).abs() < 0.001, "the decal follows the joint, it's at {position}");
+}
+
+// A 2 meter quad weighted to the first joint on its -X side and blending over to the second
+// joint toward +X
+fn skinned_quad() -> Mesh {
+    let weights: Vec<[f32; 4]> = [-1., -1., 1., 1.].into_iter().map(|x: f32| {
+        let weight = (x + 1.) * 0.5;
+        [1. - weight, weight, 0., 0.]
+    }).collect();
+
+    return quad(2.)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_JOINT_INDEX, VertexAttributeValues::Uint16x4(vec![[0, 1, 0, 0]; 4]))
+        .with_inserted_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights);
+}
@@ -0,0 +1,33 @@
+// Despawned decals don't leak their generated meshes: spraying 100 decals and clearing them brings
+// `Assets<Mesh>` back to its size before spraying.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 100;
+
+#[test]
+fn cleared_decals_free_their_meshes() {
+    let mut app = headless_app(DecalPlugin::new().with_max_decals_per_entity(SPRAYS));
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    app.world_mut().spawn((Mesh3d(quad), Decalable::default()));
+    app.update();
+
+    let baseline = app.world().resource::<Assets<Mesh>>().len();
+    let mut commands = app.world_mut().commands();
+    for _ in 0..SPRAYS {
+        spray_decal(&mut commands, material.clone(), spray_down(Vec3::ZERO, 1.));
+    }
+    app.update();
+    assert_eq!(app.world().resource::<Assets<Mesh>>().len(), baseline + SPRAYS, "every spray adds one decal mesh");
+
+    clear_decals_in_group(&mut app.world_mut().commands(), DecalGroup::default());
+    // Dropped handles are processed when the assets are next tracked
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Assets<Mesh>>().len(), baseline, "clearing decals frees their meshes");
+}
@@ -0,0 +1,40 @@
+// Mirrored projectors: a projector scaled by (-1, 1, 1) paints the same triangles as its
+// unmirrored counterpart, facing the same way, with the texture mirrored.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn mirrored_projector_mirrors_the_texture() {
+    let quad = quad(2.);
+    let projector = spray_down(Vec3::ZERO, 1.);
+    let mirrored = projector.with_scale(projector.scale * Vec3::new(-1., 1., 1.));
+
+    let plain = project_decal(&quad, &GlobalTransform::IDENTITY, &projector, 0.).expect("the projector hits the quad");
+    let mirror = project_decal(&quad, &GlobalTransform::IDENTITY, &mirrored, 0.).expect("the mirrored projector hits the quad");
+
+    let plain = world_triangles(&plain, &projector);
+    let mirror = world_triangles(&mirror, &mirrored);
+    assert_eq!(plain.len(), mirror.len(), "mirroring keeps every triangle");
+
+    for (corners, normal, _) in plain.iter().chain(mirror.iter()) {
+        let winding = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        assert!(winding.dot(*normal) > 0., "triangles wind the way their normals face");
+        assert!(normal.dot(Vec3::Y) > 0.99, "both decals face up like the quad");
+    }
+
+    // The same world space corner gets the horizontally mirrored UV
+    for (corners, _, uvs) in plain.iter() {
+        for (corner, uv) in corners.iter().zip(uvs.iter()) {
+            let mirrored_uv = mirror.iter()
+                .flat_map(|(corners, _, uvs)| corners.iter().zip(uvs.iter()))
+                .find(|(mirrored_corner, _)| mirrored_corner.distance(*corner) < 0.001)
+                .map(|(_, uv)| *uv)
+                .expect("both decals cover the same corners");
+            assert!((mirrored_uv.x - (1. - uv.x)).abs() < 0.001 && (mirrored_uv.y - uv.y).abs() < 0.001, "{uv} mirrors to {mirrored_uv}");
+        }
+    }
+}
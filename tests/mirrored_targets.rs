@@ -0,0 +1,62 @@
+// Mirrored targets, like furniture reusing a mesh with a negative scale: the decal on the mirrored
+// instance comes out the same as on the original, facing out of the surface instead of inside-out.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const MIRRORED_AT: Vec3 = Vec3::new(5., 0., 0.);
+
+#[test]
+fn mirrored_instance_gets_the_same_decal() {
+    let shelf = shelf();
+    let original = GlobalTransform::IDENTITY;
+    let mirrored = GlobalTransform::from(Transform::from_translation(MIRRORED_AT).with_scale(Vec3::new(-1., 1., 1.)));
+
+    // Straight down onto the top, over the seam to its lopsided part
+    let projector = spray_down(Vec3::new(1., 0., 0.), 0.5);
+    let mirrored_projector = spray_down(MIRRORED_AT + Vec3::new(-1., 0., 0.), 0.5);
+
+    let on_original = project_decal(&shelf, &original, &projector, 0.).expect("the spray hits the original");
+    let on_mirrored = project_decal(&shelf, &mirrored, &mirrored_projector, 0.).expect("the spray hits the mirrored instance");
+
+    let on_original = world_triangles(&on_original, &projector);
+    let on_mirrored = world_triangles(&on_mirrored, &mirrored_projector);
+    let (area, mirrored_area) = (area(&on_original), area(&on_mirrored));
+    assert!((area - 0.25).abs() < 0.001 && (mirrored_area - area).abs() < 0.001, "both instances get the whole decal, {area} and {mirrored_area}");
+
+    for (corners, normal, _) in on_original.iter().chain(on_mirrored.iter()) {
+        let winding = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        assert!(winding.dot(*normal) > 0., "triangles wind the way their normals face");
+        assert!(normal.dot(Vec3::Y) > 0.99, "the decals face out of the top");
+    }
+
+    // Mirrored back onto the original, every corner of the mirrored decal matches one of the original
+    for (corners, ..) in on_mirrored.iter() {
+        for corner in corners {
+            let unmirrored = Vec3::new(MIRRORED_AT.x - corner.x, corner.y, corner.z);
+            assert!(on_original.iter().any(|(corners, ..)| corners.iter().any(|corner| corner.distance(unmirrored) < 0.001)));
+        }
+    }
+}
+
+fn area(triangles: &[([Vec3; 3], Vec3, [Vec2; 3])]) -> f32 {
+    return triangles.iter().map(|(corners, ..)| (corners[1] - corners[0]).cross(corners[2] - corners[0]).length() * 0.5).sum();
+}
+
+// The top of a shelf, lopsided so its mirrored instance differs: a 2 meter quad with a 1 meter
+// quad attached to its +X side, with the U16 indices decals need
+fn shelf() -> Mesh {
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![
+            [-1., 0., -1.], [-1., 0., 1.], [1., 0., 1.], [1., 0., -1.],
+            [2., 0., 0.5], [2., 0., -0.5],
+        ])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 1., 0.]; 6])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3, 3, 2, 4, 3, 4, 5]));
+}
@@ -0,0 +1,88 @@
+// Decals on morphed targets get morph targets of their own, with the deltas of the target
+// interpolated at every clipped vertex, so they deform along with the surface under them.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::morph::{MeshMorphWeights, MorphAttributes, MorphTargetImage};
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const LIFT: Vec3 = Vec3::Y;   // Delta of every vertex in the first target
+const SWELL: f32 = 0.5;       // The second target moves every vertex by this much of its position
+
+#[test]
+fn decal_vertices_carry_interpolated_deltas() {
+    let mut app = headless_app(DecalPlugin);
+    let mut cube = cube(2.);
+    let Some(VertexAttributeValues::Float32x3(positions)) = cube.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("cubes have positions");
+    };
+    let positions: Vec<Vec3> = positions.iter().map(|position| Vec3::from(*position)).collect();
+    let targets = [
+        positions.iter().map(|_| MorphAttributes::new(LIFT, Vec3::ZERO, Vec3::ZERO)).collect::<Vec<_>>(),
+        positions.iter().map(|position| MorphAttributes::new(*position * SWELL, Vec3::ZERO, Vec3::ZERO)).collect(),
+    ];
+    let image = MorphTargetImage::new(targets.into_iter().map(|target| target.into_iter()), positions.len(), RenderAssetUsages::default()).unwrap();
+    cube.set_morph_targets(app.world_mut().resource_mut::<Assets<Image>>().add(image.0));
+
+    let cube = add_mesh(&mut app, cube);
+    let material = add_material(&mut app);
+    let weights = MeshMorphWeights::new(vec![0., 0.]).unwrap();
+    let target = app.world_mut().spawn((Mesh3d(cube), Transform::default(), weights, Decalable::default())).id();
+    app.update();
+
+    // Smaller than the top face, so every vertex of the decal is clipped out of the face
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::Y, 1.2));
+    app.update();
+    // Transforms of the new decals are propagated next frame
+    app.update();
+
+    let decal = app.world().get::<Decalable>(target).unwrap().decals().last().expect("the top face is sprayed");
+    assert_eq!(app.world().get::<MeshMorphWeights>(decal).map(|weights| weights.weights().len()), Some(2));
+
+    let transform = app.world().get::<GlobalTransform>(decal).unwrap().compute_matrix();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    let Some(VertexAttributeValues::Float32x3(decal_positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let image = app.world().resource::<Assets<Image>>().get(mesh.morph_targets().expect("the decal is morphed")).unwrap();
+    let deltas = decode(image, decal_positions.len());
+    assert_eq!(deltas.len(), 2);
+
+    for (vertex, position) in decal_positions.iter().enumerate() {
+        // Decals are offset a tiny bit off the surface
+        let position = transform.transform_point3(Vec3::from(*position));
+        assert!((position.y - 1.).abs() < 1e-2, "{position} lies on the top face");
+        let on_face = position.with_y(1.);
+        assert!(position.x.abs() < 0.7 && position.z.abs() < 0.7, "{position} is clipped out of the face");
+
+        let lift = transform.transform_vector3(deltas[0][vertex]);
+        let swell = transform.transform_vector3(deltas[1][vertex]);
+        assert!(lift.abs_diff_eq(LIFT, 1e-4), "the lift at {position} is {lift}");
+        assert!(swell.abs_diff_eq(on_face * SWELL, 1e-4), "the swell at {position} is {swell}");
+    }
+}
+
+// Position deltas of each target and vertex, packed like MorphTargetImage packs MorphAttributes
+fn decode(image: &Image, vertex_count: usize) -> Vec<Vec<Vec3>> {
+    let components = std::mem::size_of::<MorphAttributes>() / std::mem::size_of::<f32>();
+    let size = image.texture_descriptor.size;
+    let layer_size = (size.width * size.height) as usize;
+    let floats: Vec<f32> = image.data.chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+
+    return (0..size.depth_or_array_layers as usize)
+        .map(|target| {
+            (0..vertex_count)
+                .map(|vertex| {
+                    let f = &floats[target * layer_size + vertex * components..];
+                    return Vec3::new(f[0], f[1], f[2]);
+                })
+                .collect()
+        })
+        .collect();
+}
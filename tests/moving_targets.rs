@@ -0,0 +1,46 @@
+// Targets moved in the frame they're sprayed in, like physics props and moving platforms: sprays
+// are applied after transform propagation, so they land where the target is now, not where it was.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const MOVED_TO: Vec3 = Vec3::new(10., 0., 0.);
+
+#[test]
+fn spray_lands_at_the_new_position() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let platform = app.world_mut().spawn((Mesh3d(quad), Transform::default(), Decalable::default())).id();
+    app.update();
+
+    // Moved and sprayed before the transforms of this frame are propagated
+    app.world_mut().get_mut::<Transform>(platform).unwrap().translation = MOVED_TO;
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(MOVED_TO, 1.));
+    app.update();
+    // Transforms of the new decals are propagated next frame
+    app.update();
+
+    let decal = app.world().get::<Decalable>(platform).unwrap().decals().next().expect("the spray hits the moved platform");
+    let transform = app.world().get::<GlobalTransform>(decal).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+
+    let mut area = 0.;
+    for position in positions.iter() {
+        let world = transform.transform_point(Vec3::from(*position));
+        assert!(world.y.abs() < 1e-2, "{world} is on the platform");
+        assert!((world - MOVED_TO).xz().abs().max_element() <= 0.5 + 1e-4, "{world} is under the projector");
+    }
+    for triangle in mesh.indices().unwrap().iter().collect::<Vec<usize>>().chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|corner| transform.transform_point(Vec3::from(positions[triangle[corner]])));
+        area += (b - a).cross(c - a).length() * 0.5;
+    }
+    assert!((area - 1.).abs() < 0.01, "the whole decal is on the platform, not {area} square meters of it");
+}
@@ -0,0 +1,87 @@
+// Props made of several primitives, spawned like Bevy spawns a glTF mesh: one child entity per
+// primitive. Sprays count once for the whole prop and are evicted from all of its primitives at
+// once, instead of vanishing from whichever primitive fills up first.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn group_evicts_oldest_spray_from_every_primitive() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+
+    // Three primitives side by side, the group holds two sprays and replaces the oldest one
+    let crate_prop = spawn_prop(&mut app, &quad, Vec3::ZERO, DecalableGroup::with_limit(2).with_limit_mode(DecalLimitMode::ReplaceOldest));
+    app.update();
+    let primitives: Vec<Entity> = app.world().get::<Children>(crate_prop).unwrap().to_vec();
+    assert!(primitives.iter().all(|primitive| app.world().get::<Decalable>(*primitive).is_some()), "the primitives of the group are made decalable");
+
+    // A spray across the whole prop counts once, with the same layer on every primitive
+    let first = spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 3.));
+    app.update();
+    let first_decals = last_decals(&app, &primitives);
+    assert_eq!(group(&app, crate_prop).count(), 1);
+    assert!(first_decals.iter().all(|decal| app.world().get::<DecalSpray>(*decal) == Some(&DecalSpray(first))));
+    let layers: Vec<usize> = first_decals.iter().map(|decal| app.world().get::<DecalOf>(*decal).unwrap().layer).collect();
+    assert!(layers.iter().all(|layer| *layer == layers[0]), "the primitives are offset alike: {layers:?}");
+
+    // A small one on the left primitive only
+    spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::new(-2., 0., 0.), 0.5));
+    app.update();
+    assert_eq!(group(&app, crate_prop).count(), 2);
+    assert_eq!(app.world().get::<Decalable>(primitives[0]).unwrap().count(), 2);
+
+    // The next spray evicts the first one from all three primitives, even though the
+    // primitives on the right only held a single decal each
+    let third = spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 3.));
+    app.update();
+    assert_eq!(group(&app, crate_prop).count(), 2);
+    assert!(first_decals.iter().all(|decal| app.world().get_entity(*decal).is_err()), "the oldest spray is gone from every primitive");
+    assert!(last_decals(&app, &primitives).iter().all(|decal| app.world().get::<DecalSpray>(*decal) == Some(&DecalSpray(third))));
+    assert_eq!(app.world().get::<Decalable>(primitives[0]).unwrap().count(), 2);
+    assert_eq!(app.world().get::<Decalable>(primitives[1]).unwrap().count(), 1);
+}
+
+#[test]
+fn full_group_refuses_sprays_on_every_primitive() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+
+    let barrel = spawn_prop(&mut app, &quad, Vec3::ZERO, DecalableGroup::with_limit(1));
+    app.update();
+    let primitives: Vec<Entity> = app.world().get::<Children>(barrel).unwrap().to_vec();
+    spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 3.));
+    app.update();
+
+    let refused = spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 3.));
+    app.update();
+    let failures: Vec<DecalFailedEvent> = current_events(&app);
+    assert!(failures.iter().any(|failure| failure.spray == refused && failure.reason == DecalFailureReason::AllTargetsFull));
+    assert!(primitives.iter().all(|primitive| app.world().get::<Decalable>(*primitive).unwrap().count() == 1));
+    assert_eq!(group(&app, barrel).count(), 1);
+}
+
+fn spawn_prop(app: &mut App, quad: &Handle<Mesh>, position: Vec3, group: DecalableGroup) -> Entity {
+    return app.world_mut()
+        .spawn((Transform::from_translation(position), Visibility::default(), group))
+        .with_children(|prop| {
+            for x in [-2., 0., 2.] {
+                prop.spawn((Mesh3d(quad.clone()), Transform::from_xyz(x, 0., 0.)));
+            }
+        })
+        .id();
+}
+
+fn group(app: &App, prop: Entity) -> &DecalableGroup {
+    return app.world().get::<DecalableGroup>(prop).unwrap();
+}
+
+// Newest decal on each primitive
+fn last_decals(app: &App, primitives: &[Entity]) -> Vec<Entity> {
+    return primitives.iter().map(|primitive| app.world().get::<Decalable>(*primitive).unwrap().decals().last().unwrap()).collect();
+}
@@ -0,0 +1,49 @@
+// Targets deep in a hierarchy are projected with their GlobalTransform alone, which already
+// includes their local transform, so decals on them end up on the surface.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn decals_on_nested_targets_sit_on_the_surface() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+
+    let mut target = Entity::PLACEHOLDER;
+    app.world_mut()
+        .spawn(Transform::from_xyz(3., 2., -1.).with_rotation(Quat::from_euler(EulerRot::YXZ, 0.7, 0.2, 0.)))
+        .with_children(|parent| {
+            parent.spawn(Transform::from_xyz(0., 0.5, 0.).with_rotation(Quat::from_rotation_y(0.3)))
+                .with_children(|parent| {
+                    target = parent.spawn((Mesh3d(quad), Transform::from_xyz(1., 0., 0.5), Decalable::default())).id();
+                });
+        });
+    app.update();
+
+    let surface = *app.world().get::<GlobalTransform>(target).unwrap();
+    spray_decal(&mut app.world_mut().commands(), material, spray_down(surface.translation(), 1.));
+    app.update();
+    // Transforms of the new decals are propagated next frame
+    app.update();
+
+    let decal = app.world().get::<Decalable>(target).unwrap().decals().next().expect("the spray hits the nested quad");
+    let transform = app.world().get::<GlobalTransform>(decal).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+
+    let to_surface = surface.compute_matrix().inverse();
+    for position in positions.iter() {
+        let world = transform.transform_point(Vec3::from(*position));
+        let local = to_surface.transform_point3(world);
+        assert!(local.y.abs() < 1e-2, "{world} is {} off the surface", local.y);
+        assert!(local.x.abs() <= 1. + 1e-4 && local.z.abs() <= 1. + 1e-4, "{world} is beside the quad");
+        assert!(world.xz().distance(surface.translation().xz()) < 0.75, "{world} is outside of the projector");
+    }
+}
@@ -0,0 +1,39 @@
+// Occlusion: a projector reaching through two parallel quads only sprays the one closer to it,
+// unless occlusion is turned off.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn without_occlusion_both_quads_are_sprayed() {
+    assert_eq!(spray(None), (1, 1));
+}
+
+#[test]
+fn occlusion_hides_the_far_quad() {
+    assert_eq!(spray(Some(DecalOcclusion::default())), (1, 0));
+}
+
+// Decals on the near and the far quad
+fn spray(occlusion: Option<DecalOcclusion>) -> (usize, usize) {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let near = app.world_mut().spawn((Mesh3d(quad.clone()), Decalable::default())).id();
+    let far = app.world_mut().spawn((Mesh3d(quad), Transform::from_xyz(0., -1., 0.), Decalable::default())).id();
+    app.update();
+
+    // Reaches from 1 meter above the near quad to 1 meter below the far one
+    let projector = projector_transform(Vec3::Y, Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2), Vec2::splat(1.), 0.0..3.);
+    let mut spray = SprayDecal::new(material, projector);
+    spray.options.occlusion = occlusion;
+    spray.spray(&mut app.world_mut().commands());
+    app.update();
+
+    let mut decals = app.world_mut().query::<&DecalOf>();
+    let mut count = |target: Entity| decals.iter(app.world()).filter(|decal_of| decal_of.target == target).count();
+    return (count(near), count(far));
+}
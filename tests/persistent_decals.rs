@@ -0,0 +1,59 @@
+// Persistent decals, like a streamed level chunk: the decals of an anchored quad are persisted
+// before it despawns, and come back exactly once when a quad with the same anchor respawns, only
+// the newest ones if the respawned quad holds fewer decals.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 5;
+const ANCHOR: &str = "warehouse_wall_03";
+
+#[test]
+fn anchored_decals_come_back_once() {
+    let mut app = headless_app(DecalPlugin);
+    app.init_resource::<PersistentDecals>();
+    let quad = add_mesh(&mut app, quad(2.));
+    let paint = add_material(&mut app);
+    app.world_mut().resource_mut::<DecalMaterialIds>().register("paint", paint.clone());
+
+    let chunk = app.world_mut().spawn((Mesh3d(quad.clone()), Decalable::default(), DecalAnchor(ANCHOR.into()))).id();
+    app.update();
+
+    for spray in 0..SPRAYS {
+        SprayDecal::new(paint.clone(), spray_down(Vec3::new(spray as f32 * 0.2 - 0.4, 0., 0.), 0.5)).spray(&mut app.world_mut().commands());
+    }
+    app.update();
+    assert_eq!(decal_count(&mut app), SPRAYS, "every spray hits the quad");
+
+    // The chunk streams out
+    persist_anchored_decals(app.world_mut());
+    app.world_mut().entity_mut(chunk).despawn_recursive();
+    app.update();
+    assert_eq!(decal_count(&mut app), 0, "the decals despawn with the chunk");
+    assert_eq!(app.world().resource::<PersistentDecals>().get(ANCHOR).map(<[DecalRecord]>::len), Some(SPRAYS));
+
+    // And back in, somewhere else
+    let respawned = app.world_mut().spawn((Mesh3d(quad.clone()), Transform::from_xyz(10., 0., 0.), Decalable::default(), DecalAnchor(ANCHOR.into()))).id();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(decal_count(&mut app), SPRAYS, "the decals are restored exactly once");
+    assert_eq!(app.world().get::<Decalable>(respawned).unwrap().count(), SPRAYS);
+
+    // A respawn holding fewer decals only gets the newest ones
+    persist_anchored_decals(app.world_mut());
+    app.world_mut().entity_mut(respawned).despawn_recursive();
+    let limited = app.world_mut().spawn((Mesh3d(quad), Decalable::with_limit(2), DecalAnchor(ANCHOR.into()))).id();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(app.world().get::<Decalable>(limited).unwrap().count(), 2, "the limit of the respawned target holds");
+    assert_eq!(decal_count(&mut app), 2);
+}
+
+fn decal_count(app: &mut App) -> usize {
+    return app.world_mut().query::<&Decal>().iter(app.world()).count();
+}
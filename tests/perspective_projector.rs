@@ -0,0 +1,67 @@
+// Perspective projectors: the same projection onto two parallel walls, one twice as far away as
+// the other, covers twice the width and height on the far wall, while both map the whole texture.
+// A box projector covers the same area on both.
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+
+const NEAR_WALL: f32 = 2.;
+const FAR_WALL: f32 = 4.;
+
+#[test]
+fn perspective_footprint_grows_with_distance() {
+    let (projector, shape) = perspective_projector(Vec3::ZERO, Quat::IDENTITY, 40_f32.to_radians(), 1.5, 1.0..6.);
+    let near = footprint(&projector, shape, NEAR_WALL);
+    let far = footprint(&projector, shape, FAR_WALL);
+    assert!((far.0 / near.0).abs_diff_eq(Vec2::splat(FAR_WALL / NEAR_WALL), 0.01), "the footprint grows with the distance, {near:?} and {far:?}");
+    assert!((near.0.x / near.0.y - 1.5).abs() < 0.01, "the footprint keeps the aspect ratio");
+    assert!(near.1.abs_diff_eq(Vec2::ONE, 0.01) && far.1.abs_diff_eq(Vec2::ONE, 0.01), "both walls map the whole texture");
+}
+
+#[test]
+fn box_footprint_stays_the_same() {
+    let (projector, _) = perspective_projector(Vec3::ZERO, Quat::IDENTITY, 40_f32.to_radians(), 1.5, 1.0..6.);
+    let near = footprint(&projector, DecalShape::Box, NEAR_WALL);
+    let far = footprint(&projector, DecalShape::Box, FAR_WALL);
+    assert!(near.0.abs_diff_eq(far.0, 0.01), "box projections don't grow, {near:?} and {far:?}");
+}
+
+// World space size of the decal on a wall at the given distance, and the size of its UV range
+fn footprint(projector: &Transform, shape: DecalShape, distance: f32) -> (Vec2, Vec2) {
+    let wall_transform = GlobalTransform::from_translation(Vec3::NEG_Z * distance);
+    let options = SprayOptions { shape, ..default() };
+    let decal = project_decal_with(&wall(), &wall_transform, projector, 0., &DecalSettings::default(), &options)
+        .expect("the projection reaches the wall");
+
+    let Some(VertexAttributeValues::Float32x3(positions)) = decal.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let Some(VertexAttributeValues::Float32x2(uvs)) = decal.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        panic!("decals have UVs");
+    };
+
+    // Decal meshes are in projector space
+    let (mut min, mut max) = (Vec3::INFINITY, Vec3::NEG_INFINITY);
+    for position in positions {
+        let position = projector.transform_point(Vec3::from(*position));
+        min = min.min(position);
+        max = max.max(position);
+    }
+    let (mut uv_min, mut uv_max) = (Vec2::INFINITY, Vec2::NEG_INFINITY);
+    for uv in uvs {
+        uv_min = uv_min.min(Vec2::from(*uv));
+        uv_max = uv_max.max(Vec2::from(*uv));
+    }
+    return ((max - min).truncate(), uv_max - uv_min);
+}
+
+// A 20 meter wall facing +Z with the U16 indices decals need, larger than any projection
+fn wall() -> Mesh {
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[-10., -10., 0.], [10., -10., 0.], [10., 10., 0.], [-10., 10., 0.]])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; 4])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+}
@@ -0,0 +1,36 @@
+// Walls shot at all the time: with DecalLimitMode::ReplaceOldest the oldest decal makes room for
+// every new one past the limit, and hands over its layer so the stack doesn't creep upward.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const LIMIT: usize = 16;
+const SPRAYS: usize = 20;
+
+#[test]
+fn newest_decals_are_kept() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let wall = app.world_mut().spawn((Mesh3d(quad), Decalable::with_limit(LIMIT).with_limit_mode(DecalLimitMode::ReplaceOldest))).id();
+    app.update();
+
+    let sprays: Vec<SprayId> = (0..SPRAYS)
+        .map(|_| {
+            let spray = spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::ZERO, 1.));
+            app.update();
+            return spray;
+        })
+        .collect();
+
+    let decalable = app.world().get::<Decalable>(wall).unwrap();
+    assert_eq!(decalable.count(), LIMIT);
+    let kept: Vec<SprayId> = decalable.decals().map(|decal| app.world().get::<DecalSpray>(decal).unwrap().0).collect();
+    assert_eq!(kept, sprays[SPRAYS - LIMIT..], "the oldest decals are replaced");
+
+    let layers = decalable.decals().map(|decal| app.world().get::<DecalOf>(decal).unwrap().layer);
+    assert!(layers.max().unwrap() <= LIMIT, "the replaced layers are reused");
+}
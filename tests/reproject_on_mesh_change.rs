@@ -0,0 +1,64 @@
+// Reprojection: deforming the mesh of a marked quad moves its decal along with the surface, keeping
+// the decal entity, while the decal on an unmarked quad with its own copy of the mesh keeps
+// floating where the surface was.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const RAISE: f32 = 1.;
+
+#[test]
+fn marked_targets_reproject_their_decals() {
+    // Decal meshes are read back below
+    let mut app = headless_app(DecalPlugin::new().with_asset_usage(RenderAssetUsages::default()));
+    let terrain_mesh = add_mesh(&mut app, quad(2.));
+    let static_mesh = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let terrain = app.world_mut().spawn((Mesh3d(terrain_mesh.clone()), Decalable::default(), ReprojectOnMeshChange)).id();
+    let unmarked = app.world_mut().spawn((Mesh3d(static_mesh.clone()), Transform::from_xyz(5., 0., 0.), Decalable::default())).id();
+    app.update();
+
+    // Deep enough to still reach the raised surface
+    let down = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    for center in [Vec3::ZERO, Vec3::new(5., 0., 0.)] {
+        spray_decal(&mut app.world_mut().commands(), material.clone(), projector_transform(center + Vec3::Y * 2., down, Vec2::splat(0.5), 0.0..4.));
+    }
+    app.update();
+
+    let decal = app.world().get::<Decalable>(terrain).unwrap().decals().next().expect("the spray hits the terrain");
+    let unmarked_decal = app.world().get::<Decalable>(unmarked).unwrap().decals().next().expect("the spray hits the unmarked quad");
+    assert!(height(&app, decal).abs() < 0.01);
+
+    // Craters the other way around, raising both meshes in place
+    for mesh in [terrain_mesh, static_mesh] {
+        let mut meshes = app.world_mut().resource_mut::<Assets<Mesh>>();
+        let Some(VertexAttributeValues::Float32x3(positions)) = meshes.get_mut(&mesh).unwrap().attribute_mut(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("quads have positions");
+        };
+        for position in positions.iter_mut() {
+            position[1] += RAISE;
+        }
+    }
+    for _ in 0..3 {
+        app.update();
+    }
+
+    assert_eq!(app.world().get::<Decalable>(terrain).unwrap().decals().next(), Some(decal), "the decal entity survives");
+    assert!((height(&app, decal) - RAISE).abs() < 0.01, "the decal follows the surface, it's at {}", height(&app, decal));
+    assert!(height(&app, unmarked_decal).abs() < 0.01, "unmarked targets keep their decals as they are");
+}
+
+// Average world space height of the vertices of a decal
+fn height(app: &App, decal: Entity) -> f32 {
+    let transform = app.world().get::<GlobalTransform>(decal).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    return positions.iter().map(|position| transform.transform_point(Vec3::from(*position)).y).sum::<f32>() / positions.len() as f32;
+}
@@ -0,0 +1,46 @@
+// Non-uniformly scaled targets, like stretched crates: decal normals are transformed by the inverse
+// transpose, so they stay perpendicular to the surface instead of being skewed by the scale.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn stretched_plane_gets_the_same_normals() {
+    let rotation = Quat::from_euler(EulerRot::YXZ, 0.3, 0.4, 0.);
+    let plain = GlobalTransform::from(Transform::from_rotation(rotation));
+    let stretched = GlobalTransform::from(Transform::from_rotation(rotation).with_scale(Vec3::new(1., 1., 5.)));
+    let projector = projector_transform(Vec3::new(0., 2., 0.), Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2), Vec2::ONE, 0.0..4.);
+
+    let on_plain = project_decal(&quad(2.), &plain, &projector, 0.).expect("the spray hits the plane");
+    let on_stretched = project_decal(&quad(2.), &stretched, &projector, 0.).expect("the spray hits the stretched plane");
+    let surface = rotation * Vec3::Y;
+    for (_, normal, _) in world_triangles(&on_plain, &projector).iter().chain(world_triangles(&on_stretched, &projector).iter()) {
+        assert!(normal.abs_diff_eq(surface, 1e-4), "the decal normal {normal} is the normal of the plane {surface}");
+    }
+}
+
+#[test]
+fn stretched_slope_keeps_perpendicular_normals() {
+    let stretched = GlobalTransform::from(Transform::from_scale(Vec3::new(1., 1., 5.)));
+    let decal = project_decal(&ramp(), &stretched, &spray_down(Vec3::ZERO, 1.), 0.).expect("the spray hits the ramp");
+
+    for (corners, normal, _) in world_triangles(&decal, &spray_down(Vec3::ZERO, 1.)) {
+        let face = (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize();
+        assert!(normal.abs_diff_eq(face, 1e-4), "the decal normal {normal} is perpendicular to its triangle {face}");
+    }
+}
+
+// A 2 meter square rising half a meter per meter along Z
+fn ramp() -> Mesh {
+    let normal = Vec3::new(0., 1., -0.5).normalize().to_array();
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[-1., -0.5, -1.], [-1., 0.5, 1.], [1., 0.5, 1.], [1., -0.5, -1.]])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![normal; 4])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]));
+}
@@ -0,0 +1,55 @@
+// A decal straddling two abutting floor tiles: both halves get the same offset layer, even though
+// one of the tiles already has decals on it, and meet along the seam without a gap.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn halves_share_layer_and_seam() {
+    let mut app = headless_app(DecalPlugin);
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    let left = app.world_mut().spawn((Mesh3d(quad.clone()), Transform::from_xyz(-1., 0., 0.), Decalable::default())).id();
+    let right = app.world_mut().spawn((Mesh3d(quad), Transform::from_xyz(1., 0., 0.), Decalable::default())).id();
+    app.update();
+
+    // The left tile already has a couple of stacked decals
+    for _ in 0..2 {
+        spray_decal(&mut app.world_mut().commands(), material.clone(), spray_down(Vec3::new(-1.5, 0., 0.), 0.5));
+    }
+    app.update();
+    assert_eq!(app.world().get::<Decalable>(left).unwrap().count(), 2);
+
+    let across = spray_decal(&mut app.world_mut().commands(), material, spray_down(Vec3::new(0., 0., 0.2), 1.));
+    app.update();
+    // Transforms of the new decals are propagated next frame
+    app.update();
+
+    let halves: Vec<Entity> = [left, right].iter()
+        .map(|tile| app.world().get::<Decalable>(*tile).unwrap().decals().last().unwrap())
+        .collect();
+    let layers: Vec<usize> = halves.iter().map(|half| app.world().get::<DecalOf>(*half).unwrap().layer).collect();
+    assert!(halves.iter().all(|half| app.world().get::<DecalSpray>(*half) == Some(&DecalSpray(across))));
+    assert_eq!(layers[0], layers[1], "both halves are offset alike");
+    assert_eq!(layers[0], 3, "the layer is free on both tiles");
+
+    // Every vertex of one half on the seam is a vertex of the other half too
+    let seam: Vec<Vec<Vec3>> = halves.iter().map(|half| world_vertices(&app, *half).into_iter().filter(|vertex| vertex.x.abs() < 1e-4).collect()).collect();
+    assert!(!seam[0].is_empty() && !seam[1].is_empty());
+    for vertex in seam[0].iter() {
+        assert!(seam[1].iter().any(|other| other.distance(*vertex) < 1e-5), "{vertex} has no match on the other tile");
+    }
+}
+
+fn world_vertices(app: &App, decal: Entity) -> Vec<Vec3> {
+    let transform = app.world().get::<GlobalTransform>(decal).unwrap();
+    let mesh = app.world().resource::<Assets<Mesh>>().get(app.world().get::<Mesh3d>(decal).unwrap()).unwrap();
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    return positions.iter().map(|position| transform.transform_point(Vec3::from(*position))).collect();
+}
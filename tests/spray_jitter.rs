@@ -0,0 +1,42 @@
+// Seeded jitter: sprays with the same seed get bit for bit the same projector, e.g. to replay a
+// recorded match, while different seeds vary it.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+
+const SEEDS: u64 = 16;
+
+#[test]
+fn same_seed_same_spray() {
+    let jitter = SprayJitter { rotation: 0. ..TAU, scale: 0.8..1.2, flip_chance: 0.5 };
+    let first: Vec<[u32; 11]> = (0..SEEDS).map(|seed| bits(&SprayDecal::new(Handle::default(), projector()).with_jitter(&jitter, seed))).collect();
+    let second: Vec<[u32; 11]> = (0..SEEDS).map(|seed| bits(&SprayDecal::new(Handle::default(), projector()).with_jitter(&jitter, seed))).collect();
+    assert_eq!(first, second, "the same seed results in the same spray");
+
+    for (a, spray) in first.iter().enumerate() {
+        assert!(first[a + 1..].iter().all(|other| other != spray), "different seeds vary the spray");
+    }
+}
+
+#[test]
+fn default_jitter_changes_nothing() {
+    let unchanged = bits(&SprayDecal::new(Handle::default(), projector()).with_jitter(&SprayJitter::default(), 7));
+    assert_eq!(unchanged, bits(&SprayDecal::new(Handle::default(), projector())));
+}
+
+fn projector() -> Transform {
+    return projector_transform(Vec3::new(1., 2., 3.), Quat::from_rotation_y(0.3), Vec2::splat(0.5), 0.0..10.);
+}
+
+// The projector and the flip of a spray, as raw bits
+fn bits(spray: &SprayDecal) -> [u32; 11] {
+    let Transform { translation, rotation, scale } = spray.transform;
+    let mut bits = [0; 11];
+    for (bits, value) in bits.iter_mut().zip(translation.to_array().into_iter().chain(rotation.to_array()).chain(scale.to_array())) {
+        *bits = value.to_bits();
+    }
+    bits[10] = spray.options.flip_x as u32;
+    return bits;
+}
@@ -0,0 +1,47 @@
+// Tinted sprays: 100 sprays of a single material in 5 colors only clone the material once per
+// color, and the clones are freed with their decals.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const SPRAYS: usize = 100;
+const COLORS: [Color; 5] = [
+    Color::srgb(1., 0., 0.),
+    Color::srgb(0., 1., 0.),
+    Color::srgb(0., 0., 1.),
+    Color::srgb(1., 1., 0.),
+    Color::srgb(1., 0., 1.),
+];
+
+#[test]
+fn one_material_per_tint() {
+    let mut app = headless_app(DecalPlugin::new().with_max_decals_per_entity(SPRAYS));
+    let quad = add_mesh(&mut app, quad(2.));
+    let material = add_material(&mut app);
+    app.world_mut().spawn((Mesh3d(quad), Decalable::default()));
+    app.update();
+
+    for spray in 0..SPRAYS {
+        SprayDecal::new(material.clone(), spray_down(Vec3::ZERO, 1.))
+            .with_tint(COLORS[spray % COLORS.len()])
+            .spray(&mut app.world_mut().commands());
+    }
+    app.update();
+
+    let decals = app.world_mut().query::<&Decal>().iter(app.world()).count();
+    assert!(decals > 0, "the sprays hit the quad");
+    assert_eq!(app.world().resource::<Assets<StandardMaterial>>().len(), 1 + COLORS.len(), "one clone of the material per color");
+
+    // Clones no decal uses anymore are dropped
+    let decals: Vec<Entity> = app.world_mut().query_filtered::<Entity, With<Decal>>().iter(app.world()).collect();
+    for decal in decals {
+        app.world_mut().entity_mut(decal).despawn_recursive();
+    }
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<Assets<StandardMaterial>>().len(), 1, "the clones are freed with their decals");
+}
@@ -0,0 +1,103 @@
+// Per-entity limits in triangles: a decal covering a whole terrain chunk takes up the room of many
+// bullet holes, evicting the oldest ones or getting refused depending on the limit mode, and
+// despawned decals return their triangles.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const CELLS: u16 = 16;
+const MAX_TRIANGLES: usize = 600;
+const HOLES: usize = 20;
+
+#[test]
+fn full_chunk_refuses_large_decals() {
+    let mut app = headless_app(DecalPlugin);
+    let grid = add_mesh(&mut app, grid());
+    let material = add_material(&mut app);
+    let chunk = app.world_mut().spawn((Mesh3d(grid), Decalable::with_triangle_limit(MAX_TRIANGLES))).id();
+    app.update();
+
+    // Refused once the chunk is out of triangles, whatever the number of decals
+    let covering = spray_decal_immediate(app.world_mut(), material.clone(), whole_chunk(), SprayOptions::default());
+    app.update();
+    let covering_triangles = app.world().get::<Decalable>(chunk).unwrap().triangle_count();
+    assert_eq!(covering.len(), 1);
+    assert_eq!(covering_triangles, CELLS as usize * CELLS as usize * 2, "the decal covers every triangle of the chunk");
+    assert!(spray_decal_immediate(app.world_mut(), material.clone(), whole_chunk(), SprayOptions::default()).is_empty(), "a second one doesn't fit");
+    let small = spray_decal_immediate(app.world_mut(), material.clone(), hole(0), SprayOptions::default());
+    app.update();
+    assert_eq!(small.len(), 1, "a bullet hole still fits");
+    let small_triangles = app.world().get::<Decalable>(chunk).unwrap().triangle_count() - covering_triangles;
+
+    // Despawned decals return their triangles
+    app.world_mut().commands().entity(covering[0]).despawn_recursive();
+    app.update();
+    assert_eq!(app.world().get::<Decalable>(chunk).unwrap().triangle_count(), small_triangles);
+}
+
+#[test]
+fn oldest_holes_make_room() {
+    let mut app = headless_app(DecalPlugin);
+    let grid = add_mesh(&mut app, grid());
+    let material = add_material(&mut app);
+    let chunk = app.world_mut()
+        .spawn((Mesh3d(grid), Decalable::with_triangle_limit(MAX_TRIANGLES).with_limit_mode(DecalLimitMode::ReplaceOldest)))
+        .id();
+    app.update();
+    let covering_triangles = CELLS as usize * CELLS as usize * 2;
+
+    let holes: Vec<Entity> = (0..HOLES)
+        .flat_map(|index| spray_decal_immediate(app.world_mut(), material.clone(), hole(index), SprayOptions::default()))
+        .collect();
+    app.update();
+    let decalable = app.world().get::<Decalable>(chunk).unwrap();
+    let holes_before = decalable.count();
+    assert_eq!(holes.len(), HOLES);
+    assert!(decalable.triangle_count() + covering_triangles > MAX_TRIANGLES, "the holes and the covering decal don't fit together");
+
+    let covering = spray_decal_immediate(app.world_mut(), material.clone(), whole_chunk(), SprayOptions::default());
+    app.update();
+    let decalable = app.world().get::<Decalable>(chunk).unwrap();
+    let kept: Vec<Entity> = decalable.decals().filter(|decal| holes.contains(decal)).collect();
+    assert_eq!(covering.len(), 1);
+    assert!(decalable.decals().any(|decal| decal == covering[0]));
+    assert!(decalable.triangle_count() <= MAX_TRIANGLES);
+    assert!(kept.len() < holes_before, "some holes had to make room");
+    assert_eq!(kept, holes[HOLES - kept.len()..], "the oldest holes are evicted first");
+}
+
+fn whole_chunk() -> Transform {
+    return spray_down(Vec3::ZERO, 2.2);
+}
+
+// Bullet holes on a 5 by 4 grid
+fn hole(index: usize) -> Transform {
+    return spray_down(Vec3::new((index % 5) as f32 * 0.3 - 0.6, 0., (index / 5) as f32 * 0.3 - 0.45), 0.15);
+}
+
+// A 2 meter terrain chunk of CELLS x CELLS quads, with the U16 indices decals need
+fn grid() -> Mesh {
+    let mut positions = Vec::new();
+    for z in 0..=CELLS {
+        for x in 0..=CELLS {
+            positions.push([x as f32 / CELLS as f32 * 2. - 1., 0., z as f32 / CELLS as f32 * 2. - 1.]);
+        }
+    }
+    let mut indices = Vec::new();
+    for z in 0..CELLS {
+        for x in 0..CELLS {
+            let corner = z * (CELLS + 1) + x;
+            indices.extend_from_slice(&[corner, corner + CELLS + 1, corner + CELLS + 2, corner, corner + CELLS + 2, corner + 1]);
+        }
+    }
+    return Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 1., 0.]; positions.len()])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U16(indices));
+}
@@ -0,0 +1,48 @@
+// Two sided decals: a single quad gets one decal per side, each wound and lit like the side it is
+// on, and offset to that side of the quad.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const OFFSET: f32 = 0.01;
+
+#[test]
+fn one_decal_per_side() {
+    let projector = Transform::from_xyz(0., 1., 0.)
+        .looking_to(Vec3::NEG_Y, Vec3::Z)
+        .with_scale(Vec3::splat(2.));
+    let options = SprayOptions { two_sided: true, ..default() };
+    let decal = project_decal_with(&quad(2.), &GlobalTransform::IDENTITY, &projector, OFFSET, &DecalSettings::default(), &options)
+        .expect("the projector covers the quad");
+
+    let Some(VertexAttributeValues::Float32x3(positions)) = decal.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let Some(VertexAttributeValues::Float32x3(normals)) = decal.attribute(Mesh::ATTRIBUTE_NORMAL) else {
+        panic!("decals have normals");
+    };
+    let indices: Vec<usize> = decal.indices().unwrap().iter().collect();
+
+    let (mut front, mut back) = (0, 0);
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|corner| Vec3::from(positions[triangle[corner]]));
+        let normal = Vec3::from(normals[triangle[0]]);
+        // Back to world space, where the quad lies at y = 0
+        let height = projector.transform_point(a).y;
+
+        assert!((b - a).cross(c - a).dot(normal) > 0., "the winding matches the normal");
+        if normal.z > 0. {
+            assert!(height > 0., "the front side sits above the quad");
+            front += 1;
+        } else {
+            assert!(height < 0., "the back side sits below the quad");
+            back += 1;
+        }
+    }
+    assert_eq!(front, 2);
+    assert_eq!(back, 2);
+}
@@ -0,0 +1,50 @@
+// Options changing how the texture maps onto the decal without changing the covered geometry:
+// UV rotation for randomly rotated bullet holes, and flips for the left foot of footprints.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+const EPSILON: f32 = 1e-4;
+
+#[test]
+fn quarter_uv_rotation_swaps_the_axes() {
+    let projector = spray_down(Vec3::new(0.3, 0., -0.2), 1.);
+    let options = SprayOptions { uv_rotation: std::f32::consts::FRAC_PI_2, ..default() };
+
+    for ((corners, uvs), (rotated_corners, rotated_uvs)) in corners_and_uvs(&projector, &SprayOptions::default()).into_iter().zip(corners_and_uvs(&projector, &options)) {
+        assert_eq!(corners, rotated_corners, "the covered geometry doesn't change");
+        for (uv, rotated) in uvs.iter().zip(rotated_uvs.iter()) {
+            let (uv, rotated) = (*uv - 0.5, *rotated - 0.5);
+            assert!(rotated.abs_diff_eq(Vec2::new(uv.y, -uv.x), EPSILON), "rotating the texture by 90° maps {uv} to {rotated}");
+        }
+    }
+}
+
+#[test]
+fn flip_x_mirrors_u() {
+    // Hanging off the left edge of the quad, which clips the decal at u = 0.2
+    let projector = spray_down(Vec3::new(-0.7, 0., 0.), 1.);
+    let options = SprayOptions { flip_x: true, ..default() };
+
+    let mut clipped = 0;
+    for ((corners, uvs), (flipped_corners, flipped_uvs)) in corners_and_uvs(&projector, &SprayOptions::default()).into_iter().zip(corners_and_uvs(&projector, &options)) {
+        assert_eq!(corners, flipped_corners, "the covered geometry doesn't change");
+        for (uv, flipped) in uvs.iter().zip(flipped_uvs.iter()) {
+            assert!(flipped.abs_diff_eq(Vec2::new(1. - uv.x, uv.y), EPSILON), "flipping maps {uv} to {flipped}");
+            if (uv.x - 0.2).abs() < EPSILON {
+                assert!((flipped.x - 0.8).abs() < EPSILON);
+                clipped += 1;
+            }
+        }
+    }
+    assert!(clipped > 0, "some corners are on the edge of the quad");
+}
+
+// Corners and UVs of every triangle of the decal sprayed onto a quad
+fn corners_and_uvs(projector: &Transform, options: &SprayOptions) -> Vec<([Vec3; 3], [Vec2; 3])> {
+    let decal = project_decal_with(&quad(2.), &GlobalTransform::IDENTITY, projector, 0., &DecalSettings::default(), options).expect("the projector hits the quad");
+    return world_triangles(&decal, projector).into_iter().map(|(corners, _, uvs)| (corners, uvs)).collect();
+}
@@ -0,0 +1,37 @@
+// Vertex colors of the target, e.g. splat weights of terrain, are carried to the decal and
+// interpolated at the clipped vertices like positions and normals are.
+
+mod common;
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy_mesh_decal::prelude::*;
+use common::*;
+
+#[test]
+fn colors_are_interpolated_at_clipped_vertices() {
+    // Red along the left edge, green along the right one
+    let (red, green) = (Vec4::new(1., 0., 0., 1.), Vec4::new(0., 1., 0., 1.));
+    let gradient = quad(2.).with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![red.to_array(), red.to_array(), green.to_array(), green.to_array()]);
+    let projector = spray_down(Vec3::new(0.25, 0., 0.), 1.);
+
+    let decal = project_decal(&gradient, &GlobalTransform::IDENTITY, &projector, 0.).expect("the projector hits the quad");
+    let Some(VertexAttributeValues::Float32x3(positions)) = decal.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("decals have positions");
+    };
+    let Some(VertexAttributeValues::Float32x4(colors)) = decal.attribute(Mesh::ATTRIBUTE_COLOR) else {
+        panic!("decals of colored targets have colors");
+    };
+
+    let matrix = projector.compute_matrix();
+    let mut boundary = 0;
+    for (position, color) in positions.iter().zip(colors.iter()) {
+        let position = matrix.transform_point3(Vec3::from(*position));
+        let expected = red.lerp(green, (position.x + 1.) * 0.5);
+        assert!(Vec4::from(*color).abs_diff_eq(expected, 1e-4), "the color at {position} is {color:?} instead of {expected}");
+        if (position.x + 0.25).abs() < 1e-4 || (position.x - 0.75).abs() < 1e-4 {
+            boundary += 1;
+        }
+    }
+    assert!(boundary >= 4, "the decal is clipped on both sides of the gradient");
+}